@@ -2,6 +2,8 @@
 
 use sea_orm::entity::prelude::*;
 
+use super::sea_orm_active_enums::LikeAttachmentKind;
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "datings")]
 pub struct Model {
@@ -13,6 +15,16 @@ pub struct Model {
     pub time: DateTime,
     pub initiator_reaction: Option<bool>,
     pub partner_reaction: Option<bool>,
+    pub partner_msg_id: Option<i32>,
+    pub reminded: bool,
+    pub expired: bool,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub like_message: Option<String>,
+    pub initiator_reveal: bool,
+    pub partner_reveal: bool,
+    pub like_attachment_kind: Option<LikeAttachmentKind>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub like_attachment: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
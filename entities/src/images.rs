@@ -12,6 +12,10 @@ pub struct Model {
     pub user_id: i64,
     pub telegram_id: String,
     pub kind: ImageKind,
+    pub position: i16,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub checksum: Option<String>,
+    pub phash: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -3,7 +3,12 @@
 pub mod prelude;
 
 pub mod datings;
+pub mod events;
+pub mod flags;
 pub mod images;
+pub mod notifications;
+pub mod partner_scores;
 pub mod sea_orm_active_enums;
 pub mod states;
+pub mod texts;
 pub mod users;
@@ -1,6 +1,9 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.11.3
 
 pub use super::{
-    datings::Entity as Datings, images::Entity as Images,
-    states::Entity as States, users::Entity as Users,
+    datings::Entity as Datings, events::Entity as Events,
+    flags::Entity as Flags, images::Entity as Images,
+    notifications::Entity as Notifications,
+    partner_scores::Entity as PartnerScores, states::Entity as States,
+    texts::Entity as Texts, users::Entity as Users,
 };
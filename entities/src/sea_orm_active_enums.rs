@@ -2,6 +2,54 @@
 
 use sea_orm::entity::prelude::*;
 
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "deactivation_reason"
+)]
+pub enum DeactivationReason {
+    #[sea_orm(string_value = "bot_blocked")]
+    BotBlocked,
+    #[sea_orm(string_value = "user_deactivated")]
+    UserDeactivated,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "event_kind")]
+pub enum EventKind {
+    #[sea_orm(string_value = "experiment_assigned")]
+    ExperimentAssigned,
+    #[sea_orm(string_value = "like_contact")]
+    LikeContact,
+    #[sea_orm(string_value = "like_delivered")]
+    LikeDelivered,
+    #[sea_orm(string_value = "match")]
+    Match,
+    #[sea_orm(string_value = "profile_disabled")]
+    ProfileDisabled,
+    #[sea_orm(string_value = "profile_enabled")]
+    ProfileEnabled,
+    #[sea_orm(string_value = "reaction")]
+    Reaction,
+    #[sea_orm(string_value = "recommendation_shown")]
+    RecommendationShown,
+    #[sea_orm(string_value = "step_completed")]
+    StepCompleted,
+    #[sea_orm(string_value = "step_entered")]
+    StepEntered,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "flag_reason")]
+pub enum FlagReason {
+    #[sea_orm(string_value = "duplicate_photo")]
+    DuplicatePhoto,
+    #[sea_orm(string_value = "suspicious_activity")]
+    SuspiciousActivity,
+    #[sea_orm(string_value = "spam")]
+    Spam,
+    #[sea_orm(string_value = "text")]
+    Text,
+}
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "gender")]
 pub enum Gender {
@@ -17,6 +65,26 @@ pub enum ImageKind {
     Image,
     #[sea_orm(string_value = "video")]
     Video,
+    #[sea_orm(string_value = "video_note")]
+    VideoNote,
+    #[sea_orm(string_value = "voice")]
+    Voice,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "like_attachment_kind"
+)]
+pub enum LikeAttachmentKind {
+    #[sea_orm(string_value = "photo")]
+    Photo,
+    #[sea_orm(string_value = "sticker")]
+    Sticker,
+    #[sea_orm(string_value = "video_note")]
+    VideoNote,
+    #[sea_orm(string_value = "voice")]
+    Voice,
 }
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "location_filter")]
@@ -30,3 +98,31 @@ pub enum LocationFilter {
     #[sea_orm(string_value = "same_subject")]
     SameSubject,
 }
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "notification_kind"
+)]
+pub enum NotificationKind {
+    #[sea_orm(string_value = "chat_left")]
+    ChatLeft,
+    #[sea_orm(string_value = "like")]
+    Like,
+    #[sea_orm(string_value = "like_expired")]
+    LikeExpired,
+    #[sea_orm(string_value = "like_reminder")]
+    LikeReminder,
+    #[sea_orm(string_value = "mutual_like")]
+    MutualLike,
+}
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "review_status")]
+pub enum ReviewStatus {
+    #[sea_orm(string_value = "approved")]
+    Approved,
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    #[sea_orm(string_value = "rejected")]
+    Rejected,
+}
@@ -2,7 +2,9 @@
 
 use sea_orm::entity::prelude::*;
 
-use super::sea_orm_active_enums::{Gender, LocationFilter};
+use super::sea_orm_active_enums::{
+    DeactivationReason, Gender, LocationFilter, ReviewStatus,
+};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "users")]
@@ -23,6 +25,12 @@ pub struct Model {
     pub dating_purpose: i16,
     pub city: Option<i32>,
     pub location_filter: LocationFilter,
+    pub deactivation_reason: Option<DeactivationReason>,
+    pub deactivated_at: Option<DateTime>,
+    pub created: DateTime,
+    pub shadow_banned_at: Option<DateTime>,
+    pub review_status: ReviewStatus,
+    pub review_version: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -45,6 +45,14 @@
           pg_ctl -D $PGDATA stop | true
         '';
 
+        miniostart = pkgs.writeShellScriptBin "miniostart" ''
+          mkdir -p $MINIO_DATA
+          minio server $MINIO_DATA --address 127.0.0.1:9000 >$PWD/minio/LOG 2>&1 &
+          sleep 1
+          mc alias set local $AWS_ENDPOINT $AWS_ACCESS_KEY_ID $AWS_SECRET_ACCESS_KEY >/dev/null
+          mc mb --ignore-existing local/$AWS_BUCKET
+        '';
+
         buildInputs = with pkgs; [openssl];
         nativeBuildInputs = with pkgs; [pkg-config];
 
@@ -56,10 +64,13 @@
             postgresql
             sea-orm-cli
             sqlx-cli
+            minio
+            minio-client
           ])
           ++ [
             pgstart
             pgstop
+            miniostart
           ];
 
         bvilovebot = pkgs.stdenv.mkDerivation {
@@ -94,6 +105,14 @@
           export LOG_PATH=$PWD/postgres/LOG
           export PGDATABASE=bvilovebot
           export DATABASE_URL=postgresql:///bvilovebot?host=$PWD/postgres;
+          # Local MinIO, used with STORAGE_BACKEND=s3
+          export MINIO_DATA=$PWD/minio/data
+          export AWS_ENDPOINT=http://127.0.0.1:9000
+          export AWS_ALLOW_HTTP=true
+          export AWS_ACCESS_KEY_ID=minioadmin
+          export AWS_SECRET_ACCESS_KEY=minioadmin
+          export AWS_DEFAULT_REGION=us-east-1
+          export AWS_BUCKET=bvilovebot
         '';
       in {
         packages = {
@@ -3,6 +3,31 @@ pub use sea_orm_migration::prelude::*;
 mod m20230526_000001_create_users;
 mod m20230610_110326_add_image_type;
 mod m20230610_172346_create_states;
+mod m20230615_120000_add_like_expiration;
+mod m20230617_090000_create_notifications;
+mod m20230618_150000_add_deactivation_reason;
+mod m20230620_180000_add_contact_reveal;
+mod m20230622_120000_add_like_attachment;
+mod m20230623_100000_add_image_position;
+mod m20230624_110000_add_voice_image_kinds;
+mod m20230625_090000_add_image_checksum;
+mod m20230627_140000_create_flags;
+mod m20230629_100000_add_state_language;
+mod m20230701_120000_create_texts;
+mod m20230703_090000_escape_user_texts;
+mod m20230705_100000_create_events;
+mod m20230706_090000_add_event_variant;
+mod m20230707_100000_add_experiment_event;
+mod m20230708_090000_create_partner_scores;
+mod m20230709_090000_add_exposure_tracking;
+mod m20230710_090000_add_suspicious_flag;
+mod m20230711_090000_add_shadow_ban;
+mod m20230712_090000_add_text_flag;
+mod m20230713_090000_add_profile_review;
+mod m20230714_090000_add_notification_step;
+mod m20230715_090000_add_experiment_assignment_index;
+mod m20230716_090000_add_like_contact_event;
+mod m20230717_090000_add_chat_left_notification;
 
 pub struct Migrator;
 
@@ -13,6 +38,33 @@ impl MigratorTrait for Migrator {
             Box::new(m20230526_000001_create_users::Migration),
             Box::new(m20230610_110326_add_image_type::Migration),
             Box::new(m20230610_172346_create_states::Migration),
+            Box::new(m20230615_120000_add_like_expiration::Migration),
+            Box::new(m20230617_090000_create_notifications::Migration),
+            Box::new(m20230618_150000_add_deactivation_reason::Migration),
+            Box::new(m20230620_180000_add_contact_reveal::Migration),
+            Box::new(m20230622_120000_add_like_attachment::Migration),
+            Box::new(m20230623_100000_add_image_position::Migration),
+            Box::new(m20230624_110000_add_voice_image_kinds::Migration),
+            Box::new(m20230625_090000_add_image_checksum::Migration),
+            Box::new(m20230627_140000_create_flags::Migration),
+            Box::new(m20230629_100000_add_state_language::Migration),
+            Box::new(m20230701_120000_create_texts::Migration),
+            Box::new(m20230703_090000_escape_user_texts::Migration),
+            Box::new(m20230705_100000_create_events::Migration),
+            Box::new(m20230706_090000_add_event_variant::Migration),
+            Box::new(m20230707_100000_add_experiment_event::Migration),
+            Box::new(m20230708_090000_create_partner_scores::Migration),
+            Box::new(m20230709_090000_add_exposure_tracking::Migration),
+            Box::new(m20230710_090000_add_suspicious_flag::Migration),
+            Box::new(m20230711_090000_add_shadow_ban::Migration),
+            Box::new(m20230712_090000_add_text_flag::Migration),
+            Box::new(m20230713_090000_add_profile_review::Migration),
+            Box::new(m20230714_090000_add_notification_step::Migration),
+            Box::new(
+                m20230715_090000_add_experiment_assignment_index::Migration,
+            ),
+            Box::new(m20230716_090000_add_like_contact_event::Migration),
+            Box::new(m20230717_090000_add_chat_left_notification::Migration),
         ]
     }
 }
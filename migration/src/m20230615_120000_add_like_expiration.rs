@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Datings::Table)
+                    .add_column(ColumnDef::new(Datings::PartnerMsgId).integer())
+                    .add_column(
+                        ColumnDef::new(Datings::Reminded)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column(
+                        ColumnDef::new(Datings::Expired)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Datings::Table)
+                    .drop_column(Datings::PartnerMsgId)
+                    .drop_column(Datings::Reminded)
+                    .drop_column(Datings::Expired)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Datings {
+    Table,
+    PartnerMsgId,
+    Reminded,
+    Expired,
+}
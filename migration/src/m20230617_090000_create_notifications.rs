@@ -0,0 +1,157 @@
+use sea_orm::{sea_query::extension::postgres::Type, EnumIter, Iterable};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(NotificationKind::Table)
+                    .values(NotificationKind::iter().skip(1))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Datings::Table)
+                    .add_column(ColumnDef::new(Datings::LikeMessage).text())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Notifications::Table)
+                    .col(
+                        ColumnDef::new(Notifications::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Notifications::UserId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Notifications::Table, Notifications::UserId)
+                            .to(Users::Table, Users::Id),
+                    )
+                    .col(
+                        ColumnDef::new(Notifications::Kind)
+                            .enumeration(
+                                NotificationKind::Table,
+                                NotificationKind::iter().skip(1),
+                            )
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Notifications::DatingId).integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Notifications::Table, Notifications::DatingId)
+                            .to(Datings::Table, Datings::Id),
+                    )
+                    .col(
+                        ColumnDef::new(Notifications::Attempts)
+                            .small_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(Notifications::Created)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Notifications::NextAttempt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(Notifications::Delivered).date_time())
+                    .col(ColumnDef::new(Notifications::Failed).date_time())
+                    .col(ColumnDef::new(Notifications::LastError).text())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Notifications::Table)
+                    .col(Notifications::Delivered)
+                    .col(Notifications::Failed)
+                    .col(Notifications::NextAttempt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Notifications::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Datings::Table)
+                    .drop_column(Datings::LikeMessage)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(NotificationKind::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden, EnumIter)]
+enum NotificationKind {
+    Table,
+    Like,
+    MutualLike,
+    LikeReminder,
+    LikeExpired,
+}
+
+#[derive(Iden)]
+enum Notifications {
+    Table,
+    Id,
+    UserId,
+    Kind,
+    DatingId,
+    Attempts,
+    Created,
+    NextAttempt,
+    Delivered,
+    Failed,
+    LastError,
+}
+
+#[derive(Iden)]
+enum Datings {
+    Table,
+    Id,
+    LikeMessage,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,66 @@
+use sea_orm::{sea_query::extension::postgres::Type, EnumIter, Iterable};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(DeactivationReason::Table)
+                    .values(DeactivationReason::iter().skip(1))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(
+                        ColumnDef::new(Users::DeactivationReason).enumeration(
+                            DeactivationReason::Table,
+                            DeactivationReason::iter().skip(1),
+                        ),
+                    )
+                    .add_column(
+                        ColumnDef::new(Users::DeactivatedAt).date_time(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::DeactivationReason)
+                    .drop_column(Users::DeactivatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(DeactivationReason::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden, EnumIter)]
+enum DeactivationReason {
+    Table,
+    BotBlocked,
+    UserDeactivated,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    DeactivationReason,
+    DeactivatedAt,
+}
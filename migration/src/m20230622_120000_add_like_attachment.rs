@@ -0,0 +1,67 @@
+use sea_orm::{sea_query::extension::postgres::Type, EnumIter, Iterable};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(LikeAttachmentKind::Table)
+                    .values(LikeAttachmentKind::iter().skip(1))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Datings::Table)
+                    .add_column(
+                        ColumnDef::new(Datings::LikeAttachmentKind)
+                            .enumeration(
+                                LikeAttachmentKind::Table,
+                                LikeAttachmentKind::iter().skip(1),
+                            ),
+                    )
+                    .add_column(ColumnDef::new(Datings::LikeAttachment).text())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Datings::Table)
+                    .drop_column(Datings::LikeAttachmentKind)
+                    .drop_column(Datings::LikeAttachment)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(LikeAttachmentKind::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden, EnumIter)]
+enum LikeAttachmentKind {
+    Table,
+    Photo,
+    Voice,
+    VideoNote,
+    Sticker,
+}
+
+#[derive(Iden)]
+enum Datings {
+    Table,
+    LikeAttachmentKind,
+    LikeAttachment,
+}
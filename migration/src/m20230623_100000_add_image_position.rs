@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Images::Table)
+                    .add_column(
+                        ColumnDef::new(Images::Position)
+                            .small_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Keep the upload order of existing images
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE images SET position = ordered.position FROM (SELECT \
+                 id, row_number() OVER (PARTITION BY user_id ORDER BY id) - 1 \
+                 AS position FROM images) AS ordered WHERE images.id = \
+                 ordered.id",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Images::Table)
+                    .drop_column(Images::Position)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Images {
+    Table,
+    Position,
+}
@@ -0,0 +1,46 @@
+use sea_orm::sea_query::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(ImageKind::Table)
+                    .add_value(ImageKind::Voice)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(ImageKind::Table)
+                    .add_value(ImageKind::VideoNote)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Postgres can't drop enum values, so only the media is removed
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DELETE FROM images WHERE kind IN ('voice', 'video_note')",
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum ImageKind {
+    Table,
+    Voice,
+    VideoNote,
+}
@@ -0,0 +1,123 @@
+use sea_orm::{sea_query::extension::postgres::Type, EnumIter, Iterable};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Images::Table)
+                    .add_column(ColumnDef::new(Images::Phash).big_integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(FlagReason::Table)
+                    .values(FlagReason::iter().skip(1))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Flags::Table)
+                    .col(
+                        ColumnDef::new(Flags::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Flags::UserId).big_integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Flags::Table, Flags::UserId)
+                            .to(Users::Table, Users::Id),
+                    )
+                    .col(
+                        ColumnDef::new(Flags::Reason)
+                            .enumeration(
+                                FlagReason::Table,
+                                FlagReason::iter().skip(1),
+                            )
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Flags::Details).text().not_null())
+                    .col(
+                        ColumnDef::new(Flags::Created)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(Flags::Resolved).date_time())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Flags::Table)
+                    .col(Flags::Resolved)
+                    .col(Flags::Reason)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Flags::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(FlagReason::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Images::Table)
+                    .drop_column(Images::Phash)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden, EnumIter)]
+enum FlagReason {
+    Table,
+    DuplicatePhoto,
+}
+
+#[derive(Iden)]
+enum Flags {
+    Table,
+    Id,
+    UserId,
+    Reason,
+    Details,
+    Created,
+    Resolved,
+}
+
+#[derive(Iden)]
+enum Images {
+    Table,
+    Phash,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
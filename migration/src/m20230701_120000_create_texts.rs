@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Texts::Table)
+                    .col(ColumnDef::new(Texts::Key).text().not_null())
+                    .col(ColumnDef::new(Texts::Lang).text().not_null())
+                    .col(ColumnDef::new(Texts::Value).text().not_null())
+                    .col(
+                        ColumnDef::new(Texts::UpdatedBy)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Texts::Updated)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .primary_key(
+                        Index::create().col(Texts::Key).col(Texts::Lang),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Texts::Table).to_owned()).await
+    }
+}
+
+#[derive(Iden)]
+enum Texts {
+    Table,
+    Key,
+    Lang,
+    Value,
+    UpdatedBy,
+    Updated,
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Columns which are now stored as HTML
+const COLUMNS: [(&str, &str); 2] =
+    [("users", "about"), ("datings", "like_message")];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let conn = manager.get_connection();
+        for (table, column) in COLUMNS {
+            conn.execute_unprepared(&format!(
+                "UPDATE {table} SET {column} = \
+                 replace(replace(replace({column}, '&', '&amp;'), '<', \
+                 '&lt;'), '>', '&gt;')"
+            ))
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Formatting tags added after the migration are kept
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let conn = manager.get_connection();
+        for (table, column) in COLUMNS {
+            conn.execute_unprepared(&format!(
+                "UPDATE {table} SET {column} = \
+                 replace(replace(replace({column}, '&gt;', '>'), '&lt;', \
+                 '<'), '&amp;', '&')"
+            ))
+            .await?;
+        }
+        Ok(())
+    }
+}
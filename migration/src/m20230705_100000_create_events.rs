@@ -0,0 +1,96 @@
+use sea_orm::{sea_query::extension::postgres::Type, EnumIter, Iterable};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(EventKind::Table)
+                    .values(EventKind::iter().skip(1))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Users don't exist yet during the first wizard steps, so there is
+        // no foreign key
+        manager
+            .create_table(
+                Table::create()
+                    .table(Events::Table)
+                    .col(
+                        ColumnDef::new(Events::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Events::UserId).big_integer().not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Events::Kind)
+                            .enumeration(
+                                EventKind::Table,
+                                EventKind::iter().skip(1),
+                            )
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Events::DatingId).integer())
+                    .col(ColumnDef::new(Events::Details).text())
+                    .col(
+                        ColumnDef::new(Events::Created)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(Events::Table)
+                    .col(Events::Created)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Events::Table).to_owned())
+            .await?;
+
+        manager.drop_type(Type::drop().name(EventKind::Table).to_owned()).await
+    }
+}
+
+#[derive(Iden, EnumIter)]
+enum EventKind {
+    Table,
+    StepEntered,
+    StepCompleted,
+    RecommendationShown,
+    Reaction,
+    LikeDelivered,
+    Match,
+    ProfileEnabled,
+    ProfileDisabled,
+}
+
+#[derive(Iden)]
+enum Events {
+    Table,
+    Id,
+    UserId,
+    Kind,
+    DatingId,
+    Details,
+    Created,
+}
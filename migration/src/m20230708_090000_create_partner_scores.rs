@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PartnerScores::Table)
+                    .col(
+                        ColumnDef::new(PartnerScores::UserId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PartnerScores::PartnerId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PartnerScores::Score).float().not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(PartnerScores::UserId)
+                            .col(PartnerScores::PartnerId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PartnerScores::Table, PartnerScores::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                PartnerScores::Table,
+                                PartnerScores::PartnerId,
+                            )
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PartnerScores::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum PartnerScores {
+    Table,
+    UserId,
+    PartnerId,
+    Score,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
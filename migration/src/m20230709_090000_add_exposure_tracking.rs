@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(
+                        ColumnDef::new(Users::Created)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing profiles shouldn't look new, their first dating is the
+        // best estimate of the creation time
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE users SET created = COALESCE((SELECT MIN(time) FROM \
+                 datings WHERE initiator_id = users.id OR partner_id = \
+                 users.id), last_activity)",
+            )
+            .await?;
+
+        // Views and incoming likes of candidates are counted in `get_partner`
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX)
+                    .table(Datings::Table)
+                    .col(Datings::PartnerId)
+                    .col(Datings::Time)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop().name(INDEX).table(Datings::Table).to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::Created)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+const INDEX: &str = "idx-datings-partner-time";
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Created,
+}
+
+#[derive(Iden)]
+enum Datings {
+    Table,
+    PartnerId,
+    Time,
+}
@@ -0,0 +1,34 @@
+use sea_orm::sea_query::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(FlagReason::Table)
+                    .add_value(FlagReason::Text)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Postgres can't drop enum values, so only the flags are removed
+        manager
+            .get_connection()
+            .execute_unprepared("DELETE FROM flags WHERE reason = 'text'")
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum FlagReason {
+    Table,
+    Text,
+}
@@ -0,0 +1,76 @@
+use sea_orm::{sea_query::extension::postgres::Type, EnumIter, Iterable};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ReviewStatus::Table)
+                    .values(ReviewStatus::iter().skip(1))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing profiles were published without a review
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(
+                        ColumnDef::new(Users::ReviewStatus)
+                            .enumeration(
+                                ReviewStatus::Table,
+                                ReviewStatus::iter().skip(1),
+                            )
+                            .not_null()
+                            .default(SimpleExpr::Custom(
+                                "CAST('approved' AS review_status)".to_string(),
+                            )),
+                    )
+                    .add_column(
+                        ColumnDef::new(Users::ReviewVersion)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::ReviewStatus)
+                    .drop_column(Users::ReviewVersion)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(ReviewStatus::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden, EnumIter)]
+enum ReviewStatus {
+    Table,
+    Pending,
+    Approved,
+    Rejected,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    ReviewStatus,
+    ReviewVersion,
+}
@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // Concurrent exposures could save the same assignment several times
+        db.execute_unprepared(
+            "DELETE FROM events a USING events b WHERE a.kind = \
+             'experiment_assigned' AND b.kind = 'experiment_assigned' AND \
+             a.user_id = b.user_id AND a.details = b.details AND a.id > b.id",
+        )
+        .await?;
+        // sea-query can't create partial indexes yet
+        db.execute_unprepared(
+            "CREATE UNIQUE INDEX idx_events_experiment_assigned ON events \
+             (user_id, details) WHERE kind = 'experiment_assigned'",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_events_experiment_assigned")
+                    .table(Events::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Events {
+    Table,
+}
@@ -0,0 +1,36 @@
+use sea_orm::sea_query::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(EventKind::Table)
+                    .add_value(EventKind::LikeContact)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Postgres can't drop enum values, so only the events are removed
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DELETE FROM events WHERE kind = 'like_contact'",
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum EventKind {
+    Table,
+    LikeContact,
+}
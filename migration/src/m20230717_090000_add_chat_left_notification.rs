@@ -0,0 +1,37 @@
+use sea_orm::sea_query::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(NotificationKind::Table)
+                    .add_value(NotificationKind::ChatLeft)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Postgres can't drop enum values, so only the notifications are
+        // removed
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DELETE FROM notifications WHERE kind = 'chat_left'",
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum NotificationKind {
+    Table,
+    ChatLeft,
+}
@@ -0,0 +1,424 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use anyhow::Context;
+use chrono::NaiveDate;
+use entities::{events, sea_orm_active_enums::FlagReason};
+use sea_orm::ActiveEnum;
+use teloxide::{
+    prelude::*,
+    types::{InputFile, ParseMode},
+    utils::command::BotCommands,
+};
+
+use crate::{
+    db::Database,
+    text::{self, Lang, Text},
+    Bot,
+};
+
+/// Changed texts waiting for `/publish`, by admin id
+#[derive(Debug, Default)]
+pub struct TextDrafts(Mutex<HashMap<i64, (Text, Lang, String)>>);
+
+/// Apply texts changed by admins, which were saved in the database
+pub async fn load_texts(db: &Database) -> anyhow::Result<()> {
+    for row in db.get_texts().await? {
+        let (Some(text), Ok(lang)) = (text::find(&row.key), row.lang.parse()) else {
+            tracing::warn!(key = row.key, lang = row.lang, "unknown text");
+            continue;
+        };
+        text::set_override(text, lang, Some(row.value));
+    }
+    Ok(())
+}
+
+#[derive(Debug, BotCommands, Clone)]
+#[command(rename_rule = "lowercase", description = "Команды администратора:")]
+pub enum AdminCommand {
+    #[command(description = "команды администратора")]
+    Admin,
+    #[command(description = "анкеты с похожими фото")]
+    Dupes,
+    #[command(description = "подозрительная активность")]
+    Suspicious,
+    #[command(description = "скрытые за спам анкеты")]
+    Spam,
+    #[command(description = "снять скрытие за спам: /unban USER_ID")]
+    Unban(i64),
+    #[command(description = "тексты на проверке")]
+    Flagged,
+    #[command(description = "закрыть жалобу, кроме жалоб на спам")]
+    Resolve(i32),
+    #[command(description = "ключи всех текстов")]
+    Texts,
+    #[command(description = "текст по ключу: /text KEY")]
+    Text(String),
+    #[command(description = "изменить текст: /settext KEY LANG текст")]
+    SetText(String),
+    #[command(description = "опубликовать изменённый текст")]
+    Publish,
+    #[command(description = "вернуть исходный текст: /resettext KEY LANG")]
+    ResetText(String),
+    #[command(
+        description = "выгрузить события в CSV: /events 2023-07-01 2023-07-31"
+    )]
+    Events(String),
+    #[command(description = "конверсия создания анкеты: /funnel [недель]")]
+    Funnel(String),
+    #[command(description = "результаты эксперимента: /experiment NAME")]
+    Experiment(String),
+    #[command(description = "самые показываемые анкеты")]
+    Exposure,
+}
+
+#[tracing::instrument(err, skip(db, bot, drafts))]
+pub async fn answer(
+    db: Arc<Database>,
+    bot: Bot,
+    drafts: Arc<TextDrafts>,
+    msg: Message,
+    cmd: AdminCommand,
+) -> anyhow::Result<()> {
+    match cmd {
+        AdminCommand::Admin => {
+            bot.send_message(
+                msg.chat.id,
+                AdminCommand::descriptions().to_string(),
+            )
+            .await?;
+        }
+        AdminCommand::Dupes => {
+            send_flags(
+                &db,
+                &bot,
+                msg.chat.id,
+                FlagReason::DuplicatePhoto,
+                "Похожих фото не найдено",
+            )
+            .await?;
+        }
+        AdminCommand::Suspicious => {
+            send_flags(
+                &db,
+                &bot,
+                msg.chat.id,
+                FlagReason::SuspiciousActivity,
+                "Подозрительной активности не найдено",
+            )
+            .await?;
+        }
+        AdminCommand::Spam => {
+            send_flags(
+                &db,
+                &bot,
+                msg.chat.id,
+                FlagReason::Spam,
+                "Скрытых за спам анкет нет",
+            )
+            .await?;
+        }
+        AdminCommand::Flagged => {
+            send_flags(
+                &db,
+                &bot,
+                msg.chat.id,
+                FlagReason::Text,
+                "Текстов на проверке нет",
+            )
+            .await?;
+        }
+        AdminCommand::Unban(user) => {
+            let text = if db.lift_shadow_ban(user).await? {
+                "Анкета снова видна другим"
+            } else {
+                "Анкета не скрыта"
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+        AdminCommand::Resolve(id) => {
+            let text = if db.resolve_flag(id).await? {
+                "Жалоба закрыта"
+            } else {
+                "Открытой жалобы с таким номером нет, скрытие за спам снимает \
+                 /unban USER_ID"
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+        AdminCommand::Texts => {
+            let keys: Vec<_> = text::ALL.iter().map(|t| t.key).collect();
+            for chunk in keys.chunks(50) {
+                bot.send_message(msg.chat.id, chunk.join("\n")).await?;
+            }
+        }
+        AdminCommand::Text(key) => {
+            let Some(text) = text::find(key.trim()) else {
+                bot.send_message(msg.chat.id, "Текста с таким ключом нет")
+                    .await?;
+                return Ok(());
+            };
+            for lang in Lang::ALL {
+                let status = if text::is_overridden(text, lang) {
+                    "изменён"
+                } else {
+                    "исходный"
+                };
+                bot.send_message(
+                    msg.chat.id,
+                    format!("{} ({status}):\n{}", lang.code(), text.get(lang)),
+                )
+                .await?;
+            }
+        }
+        AdminCommand::SetText(args) => {
+            let (text, lang, value) = match parse_text_args(&args) {
+                Ok(parsed) => parsed,
+                Err(error) => {
+                    bot.send_message(msg.chat.id, error).await?;
+                    return Ok(());
+                }
+            };
+            let expected = text.placeholders(lang);
+            if value.matches("{}").count() != expected {
+                bot.send_message(
+                    msg.chat.id,
+                    format!("В тексте должно быть {expected} шт. {{}}"),
+                )
+                .await?;
+                return Ok(());
+            }
+            // Texts are escaped in HTML messages, see `Text::html`
+            bot.send_message(msg.chat.id, crate::html::escape(value))
+                .parse_mode(ParseMode::Html)
+                .await?;
+            bot.send_message(
+                msg.chat.id,
+                "Так будет выглядеть текст, /publish чтобы опубликовать",
+            )
+            .await?;
+            drafts
+                .0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(msg.chat.id.0, (text, lang, value.to_owned()));
+        }
+        AdminCommand::Publish => {
+            let draft = drafts
+                .0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&msg.chat.id.0);
+            let Some((text, lang, value)) = draft else {
+                bot.send_message(msg.chat.id, "Нет изменённого текста").await?;
+                return Ok(());
+            };
+            db.set_text(text.key, lang, value.clone(), msg.chat.id.0).await?;
+            text::set_override(text, lang, Some(value));
+            bot.send_message(msg.chat.id, "Текст опубликован").await?;
+        }
+        AdminCommand::ResetText(args) => {
+            let (text, lang) = match parse_text_args(&args) {
+                Ok((text, lang, rest)) if rest.trim().is_empty() => {
+                    (text, lang)
+                }
+                Ok(_) => {
+                    bot.send_message(msg.chat.id, "Лишние аргументы").await?;
+                    return Ok(());
+                }
+                Err(error) => {
+                    bot.send_message(msg.chat.id, error).await?;
+                    return Ok(());
+                }
+            };
+            db.delete_text(text.key, lang).await?;
+            text::set_override(text, lang, None);
+            bot.send_message(msg.chat.id, "Восстановлен исходный текст")
+                .await?;
+        }
+        AdminCommand::Events(args) => {
+            let Some((from, to)) = parse_period(&args) else {
+                bot.send_message(
+                    msg.chat.id,
+                    "Укажите период: /events 2023-07-01 2023-07-31",
+                )
+                .await?;
+                return Ok(());
+            };
+            let events = db.get_events(from, to).await?;
+            if events.is_empty() {
+                bot.send_message(msg.chat.id, "За этот период событий нет")
+                    .await?;
+                return Ok(());
+            }
+            let file = InputFile::memory(events_csv(&events)?)
+                .file_name(format!("events_{from}_{to}.csv"));
+            bot.send_document(msg.chat.id, file).await?;
+        }
+        AdminCommand::Funnel(args) => {
+            let weeks = match args.trim() {
+                "" => Some(4),
+                weeks => weeks.parse().ok().filter(|w| (1..=52).contains(w)),
+            };
+            let Some(weeks) = weeks else {
+                bot.send_message(
+                    msg.chat.id,
+                    "Количество недель должно быть от 1 до 52",
+                )
+                .await?;
+                return Ok(());
+            };
+            let report = crate::funnel::report(&db, weeks).await?;
+            if report.is_empty() {
+                bot.send_message(
+                    msg.chat.id,
+                    "Анкеты за этот период не создавали",
+                )
+                .await?;
+            }
+            for cohort in report {
+                bot.send_message(msg.chat.id, cohort).await?;
+            }
+        }
+        AdminCommand::Experiment(name) => {
+            let experiments = &bot.config().experiments;
+            let Some(experiment) = experiments.find(name.trim()) else {
+                let names: Vec<_> =
+                    experiments.iter().map(|e| e.name.as_str()).collect();
+                let text = if names.is_empty() {
+                    "Экспериментов нет".to_owned()
+                } else {
+                    format!("Эксперименты: {}", names.join(", "))
+                };
+                bot.send_message(msg.chat.id, text).await?;
+                return Ok(());
+            };
+            let report = crate::experiments::report(&db, experiment).await?;
+            bot.send_message(msg.chat.id, report).await?;
+        }
+        AdminCommand::Exposure => {
+            let days = bot.config().matching.exposure_days;
+            let lines: Vec<_> = db
+                .get_exposure(30)
+                .await?
+                .into_iter()
+                .map(|(id, name, views, pending)| {
+                    format!(
+                        "{name} ({id}): показов за {days} дн. {views}, без \
+                         ответа {pending}"
+                    )
+                })
+                .collect();
+            let text = if lines.is_empty() {
+                "Активных анкет нет".to_owned()
+            } else {
+                lines.join("\n")
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Parse `YYYY-MM-DD YYYY-MM-DD`, both days are included
+fn parse_period(args: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let mut dates = args.split_whitespace().map(str::parse::<NaiveDate>);
+    let (Some(Ok(from)), Some(Ok(to)), None) =
+        (dates.next(), dates.next(), dates.next())
+    else {
+        return None;
+    };
+    (from <= to).then_some((from, to))
+}
+
+fn events_csv(events: &[events::Model]) -> anyhow::Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record([
+        "id",
+        "user_id",
+        "kind",
+        "dating_id",
+        "details",
+        "created",
+        "variant",
+    ])?;
+    for e in events {
+        writer.write_record([
+            e.id.to_string(),
+            e.user_id.to_string(),
+            e.kind.to_value(),
+            e.dating_id.map(|d| d.to_string()).unwrap_or_default(),
+            e.details.clone().unwrap_or_default(),
+            e.created.format("%Y-%m-%d %H:%M:%S").to_string(),
+            e.variant.clone().unwrap_or_default(),
+        ])?;
+    }
+    writer.into_inner().context("can't write CSV")
+}
+
+/// List unresolved flags for the reason
+async fn send_flags(
+    db: &Database,
+    bot: &Bot,
+    chat: ChatId,
+    reason: FlagReason,
+    empty: &str,
+) -> anyhow::Result<()> {
+    let flags = db.get_unresolved_flags(reason).await?;
+    if flags.is_empty() {
+        bot.send_message(chat, empty).await?;
+    }
+    let lines: Vec<_> = flags
+        .into_iter()
+        .map(|(flag, user)| {
+            let name = user.map_or_else(String::new, |u| u.name);
+            format!(
+                "#{} {}: {} ({}), {}",
+                flag.id,
+                flag.created.format("%d.%m %H:%M"),
+                name,
+                flag.user_id,
+                flag.details
+            )
+        })
+        .collect();
+    // Split the report to fit into message length limit
+    for chunk in lines.chunks(30) {
+        bot.send_message(chat, chunk.join("\n")).await?;
+    }
+    Ok(())
+}
+
+/// Parse `KEY LANG rest`, the rest is kept verbatim after the separator, so
+/// it can start on the next line
+fn parse_text_args(args: &str) -> Result<(Text, Lang, &str), &'static str> {
+    let (key, rest) = split_word(args);
+    let text = text::find(key).ok_or("Текста с таким ключом нет")?;
+    let (lang, rest) = split_word(rest);
+    let lang = lang.parse().map_err(|()| "Язык должен быть ru или en")?;
+    let rest = rest.strip_prefix(char::is_whitespace).unwrap_or(rest);
+    Ok((text, lang, rest))
+}
+
+/// First word and the rest starting with the whitespace after it
+fn split_word(args: &str) -> (&str, &str) {
+    let args = args.trim_start();
+    args.split_at(args.find(char::is_whitespace).unwrap_or(args.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_args() {
+        let (text, lang, rest) =
+            parse_text_args("SEND en\nLine\n  two").unwrap();
+        assert_eq!((text.key, lang, rest), ("SEND", Lang::En, "Line\n  two"));
+        let (_, lang, rest) = parse_text_args(" SEND\tru").unwrap();
+        assert_eq!((lang, rest), (Lang::Ru, ""));
+        assert!(parse_text_args("SEND de text").is_err());
+        assert!(parse_text_args("NO_SUCH_TEXT ru text").is_err());
+    }
+}
@@ -0,0 +1,1760 @@
+use std::{
+    future::{Future, IntoFuture},
+    pin::Pin,
+    sync::Arc,
+};
+
+use entities::sea_orm_active_enums::DeactivationReason;
+use teloxide::{
+    adaptors::Throttle,
+    net::Download,
+    prelude::*,
+    requests::{HasPayload, Output},
+    types::*,
+    ApiError, DownloadError, RequestError,
+};
+use tracing::*;
+use url::Url;
+
+use crate::{config::Config, db::Database, metrics::Metrics, storage::Storage};
+
+type Inner = Throttle<teloxide::Bot>;
+
+/// Bot adaptor which deactivates users who blocked the bot.
+///
+/// All requests are wrapped into [`BlockAwareRequest`], which counts them in
+/// the metrics and checks the errors of requests to user chats. Also holds
+/// the media [`Storage`] used to restore files Telegram doesn't accept anymore
+/// and the [`Config`].
+#[derive(Clone)]
+pub struct Bot {
+    inner: Inner,
+    db: Arc<Database>,
+    storage: Arc<Storage>,
+    config: Arc<Config>,
+    metrics: Arc<Metrics>,
+}
+
+impl Bot {
+    pub const fn new(
+        inner: Inner,
+        db: Arc<Database>,
+        storage: Arc<Storage>,
+        config: Arc<Config>,
+        metrics: Arc<Metrics>,
+    ) -> Self {
+        Self { inner, db, storage, config, metrics }
+    }
+
+    pub const fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    pub fn storage(&self) -> &Storage {
+        &self.storage
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Download the file like the inner bot, counting it in the metrics
+    pub async fn download_file(
+        &self,
+        path: &str,
+        data: &mut Vec<u8>,
+    ) -> Result<(), DownloadError> {
+        self.metrics.telegram_requests_in_flight.inc();
+        let result = self.inner.download_file(path, data).await;
+        self.metrics.telegram_requests_in_flight.dec();
+        if let Err(e) = &result {
+            self.metrics.download_error(e);
+        }
+        result
+    }
+
+    /// Request which isn't sent to a chat, it's only counted in the metrics
+    fn track<R>(&self, request: R) -> BlockAwareRequest<R> {
+        BlockAwareRequest {
+            inner: request,
+            user: None,
+            db: self.db.clone(),
+            metrics: self.metrics.clone(),
+        }
+    }
+
+    fn wrap<R>(&self, request: R, chat: &Recipient) -> BlockAwareRequest<R> {
+        let user = match chat {
+            Recipient::Id(id) if id.is_user() => Some(*id),
+            _ => None,
+        };
+        BlockAwareRequest {
+            inner: request,
+            user,
+            db: self.db.clone(),
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+/// Requests are wrapped into [`BlockAwareRequest`], so the trait has to be
+/// implemented in full and a new method can't bypass the check
+impl Requester for Bot {
+    type AddStickerToSet =
+        BlockAwareRequest<<Inner as Requester>::AddStickerToSet>;
+    type AnswerCallbackQuery =
+        BlockAwareRequest<<Inner as Requester>::AnswerCallbackQuery>;
+    type AnswerInlineQuery =
+        BlockAwareRequest<<Inner as Requester>::AnswerInlineQuery>;
+    type AnswerPreCheckoutQuery =
+        BlockAwareRequest<<Inner as Requester>::AnswerPreCheckoutQuery>;
+    type AnswerShippingQuery =
+        BlockAwareRequest<<Inner as Requester>::AnswerShippingQuery>;
+    type AnswerWebAppQuery =
+        BlockAwareRequest<<Inner as Requester>::AnswerWebAppQuery>;
+    type ApproveChatJoinRequest =
+        BlockAwareRequest<<Inner as Requester>::ApproveChatJoinRequest>;
+    type BanChatMember = BlockAwareRequest<<Inner as Requester>::BanChatMember>;
+    type BanChatSenderChat =
+        BlockAwareRequest<<Inner as Requester>::BanChatSenderChat>;
+    type Close = BlockAwareRequest<<Inner as Requester>::Close>;
+    type CloseForumTopic =
+        BlockAwareRequest<<Inner as Requester>::CloseForumTopic>;
+    type CloseGeneralForumTopic =
+        BlockAwareRequest<<Inner as Requester>::CloseGeneralForumTopic>;
+    type CopyMessage = BlockAwareRequest<<Inner as Requester>::CopyMessage>;
+    type CreateChatInviteLink =
+        BlockAwareRequest<<Inner as Requester>::CreateChatInviteLink>;
+    type CreateForumTopic =
+        BlockAwareRequest<<Inner as Requester>::CreateForumTopic>;
+    type CreateInvoiceLink =
+        BlockAwareRequest<<Inner as Requester>::CreateInvoiceLink>;
+    type CreateNewStickerSet =
+        BlockAwareRequest<<Inner as Requester>::CreateNewStickerSet>;
+    type DeclineChatJoinRequest =
+        BlockAwareRequest<<Inner as Requester>::DeclineChatJoinRequest>;
+    type DeleteChatPhoto =
+        BlockAwareRequest<<Inner as Requester>::DeleteChatPhoto>;
+    type DeleteChatStickerSet =
+        BlockAwareRequest<<Inner as Requester>::DeleteChatStickerSet>;
+    type DeleteForumTopic =
+        BlockAwareRequest<<Inner as Requester>::DeleteForumTopic>;
+    type DeleteMessage = BlockAwareRequest<<Inner as Requester>::DeleteMessage>;
+    type DeleteMyCommands =
+        BlockAwareRequest<<Inner as Requester>::DeleteMyCommands>;
+    type DeleteStickerFromSet =
+        BlockAwareRequest<<Inner as Requester>::DeleteStickerFromSet>;
+    type DeleteWebhook = BlockAwareRequest<<Inner as Requester>::DeleteWebhook>;
+    type EditChatInviteLink =
+        BlockAwareRequest<<Inner as Requester>::EditChatInviteLink>;
+    type EditForumTopic =
+        BlockAwareRequest<<Inner as Requester>::EditForumTopic>;
+    type EditGeneralForumTopic =
+        BlockAwareRequest<<Inner as Requester>::EditGeneralForumTopic>;
+    type EditMessageCaption =
+        BlockAwareRequest<<Inner as Requester>::EditMessageCaption>;
+    type EditMessageCaptionInline =
+        BlockAwareRequest<<Inner as Requester>::EditMessageCaptionInline>;
+    type EditMessageLiveLocation =
+        BlockAwareRequest<<Inner as Requester>::EditMessageLiveLocation>;
+    type EditMessageLiveLocationInline =
+        BlockAwareRequest<<Inner as Requester>::EditMessageLiveLocationInline>;
+    type EditMessageMedia =
+        BlockAwareRequest<<Inner as Requester>::EditMessageMedia>;
+    type EditMessageMediaInline =
+        BlockAwareRequest<<Inner as Requester>::EditMessageMediaInline>;
+    type EditMessageReplyMarkup =
+        BlockAwareRequest<<Inner as Requester>::EditMessageReplyMarkup>;
+    type EditMessageReplyMarkupInline =
+        BlockAwareRequest<<Inner as Requester>::EditMessageReplyMarkupInline>;
+    type EditMessageText =
+        BlockAwareRequest<<Inner as Requester>::EditMessageText>;
+    type EditMessageTextInline =
+        BlockAwareRequest<<Inner as Requester>::EditMessageTextInline>;
+    type Err = RequestError;
+    type ExportChatInviteLink =
+        BlockAwareRequest<<Inner as Requester>::ExportChatInviteLink>;
+    type ForwardMessage =
+        BlockAwareRequest<<Inner as Requester>::ForwardMessage>;
+    type GetChat = BlockAwareRequest<<Inner as Requester>::GetChat>;
+    type GetChatAdministrators =
+        BlockAwareRequest<<Inner as Requester>::GetChatAdministrators>;
+    type GetChatMember = BlockAwareRequest<<Inner as Requester>::GetChatMember>;
+    type GetChatMemberCount =
+        BlockAwareRequest<<Inner as Requester>::GetChatMemberCount>;
+    type GetChatMembersCount =
+        BlockAwareRequest<<Inner as Requester>::GetChatMembersCount>;
+    type GetChatMenuButton =
+        BlockAwareRequest<<Inner as Requester>::GetChatMenuButton>;
+    type GetCustomEmojiStickers =
+        BlockAwareRequest<<Inner as Requester>::GetCustomEmojiStickers>;
+    type GetFile = BlockAwareRequest<<Inner as Requester>::GetFile>;
+    type GetForumTopicIconStickers =
+        BlockAwareRequest<<Inner as Requester>::GetForumTopicIconStickers>;
+    type GetGameHighScores =
+        BlockAwareRequest<<Inner as Requester>::GetGameHighScores>;
+    type GetMe = BlockAwareRequest<<Inner as Requester>::GetMe>;
+    type GetMyCommands = BlockAwareRequest<<Inner as Requester>::GetMyCommands>;
+    type GetMyDefaultAdministratorRights = BlockAwareRequest<
+        <Inner as Requester>::GetMyDefaultAdministratorRights,
+    >;
+    type GetStickerSet = BlockAwareRequest<<Inner as Requester>::GetStickerSet>;
+    type GetUpdates = BlockAwareRequest<<Inner as Requester>::GetUpdates>;
+    type GetUserProfilePhotos =
+        BlockAwareRequest<<Inner as Requester>::GetUserProfilePhotos>;
+    type GetWebhookInfo =
+        BlockAwareRequest<<Inner as Requester>::GetWebhookInfo>;
+    type HideGeneralForumTopic =
+        BlockAwareRequest<<Inner as Requester>::HideGeneralForumTopic>;
+    type KickChatMember =
+        BlockAwareRequest<<Inner as Requester>::KickChatMember>;
+    type LeaveChat = BlockAwareRequest<<Inner as Requester>::LeaveChat>;
+    type LogOut = BlockAwareRequest<<Inner as Requester>::LogOut>;
+    type PinChatMessage =
+        BlockAwareRequest<<Inner as Requester>::PinChatMessage>;
+    type PromoteChatMember =
+        BlockAwareRequest<<Inner as Requester>::PromoteChatMember>;
+    type ReopenForumTopic =
+        BlockAwareRequest<<Inner as Requester>::ReopenForumTopic>;
+    type ReopenGeneralForumTopic =
+        BlockAwareRequest<<Inner as Requester>::ReopenGeneralForumTopic>;
+    type RestrictChatMember =
+        BlockAwareRequest<<Inner as Requester>::RestrictChatMember>;
+    type RevokeChatInviteLink =
+        BlockAwareRequest<<Inner as Requester>::RevokeChatInviteLink>;
+    type SendAnimation = BlockAwareRequest<<Inner as Requester>::SendAnimation>;
+    type SendAudio = BlockAwareRequest<<Inner as Requester>::SendAudio>;
+    type SendChatAction =
+        BlockAwareRequest<<Inner as Requester>::SendChatAction>;
+    type SendContact = BlockAwareRequest<<Inner as Requester>::SendContact>;
+    type SendDice = BlockAwareRequest<<Inner as Requester>::SendDice>;
+    type SendDocument = BlockAwareRequest<<Inner as Requester>::SendDocument>;
+    type SendGame = BlockAwareRequest<<Inner as Requester>::SendGame>;
+    type SendInvoice = BlockAwareRequest<<Inner as Requester>::SendInvoice>;
+    type SendLocation = BlockAwareRequest<<Inner as Requester>::SendLocation>;
+    type SendMediaGroup =
+        BlockAwareRequest<<Inner as Requester>::SendMediaGroup>;
+    type SendMessage = BlockAwareRequest<<Inner as Requester>::SendMessage>;
+    type SendPhoto = BlockAwareRequest<<Inner as Requester>::SendPhoto>;
+    type SendPoll = BlockAwareRequest<<Inner as Requester>::SendPoll>;
+    type SendSticker = BlockAwareRequest<<Inner as Requester>::SendSticker>;
+    type SendVenue = BlockAwareRequest<<Inner as Requester>::SendVenue>;
+    type SendVideo = BlockAwareRequest<<Inner as Requester>::SendVideo>;
+    type SendVideoNote = BlockAwareRequest<<Inner as Requester>::SendVideoNote>;
+    type SendVoice = BlockAwareRequest<<Inner as Requester>::SendVoice>;
+    type SetChatAdministratorCustomTitle = BlockAwareRequest<
+        <Inner as Requester>::SetChatAdministratorCustomTitle,
+    >;
+    type SetChatDescription =
+        BlockAwareRequest<<Inner as Requester>::SetChatDescription>;
+    type SetChatMenuButton =
+        BlockAwareRequest<<Inner as Requester>::SetChatMenuButton>;
+    type SetChatPermissions =
+        BlockAwareRequest<<Inner as Requester>::SetChatPermissions>;
+    type SetChatPhoto = BlockAwareRequest<<Inner as Requester>::SetChatPhoto>;
+    type SetChatStickerSet =
+        BlockAwareRequest<<Inner as Requester>::SetChatStickerSet>;
+    type SetChatTitle = BlockAwareRequest<<Inner as Requester>::SetChatTitle>;
+    type SetGameScore = BlockAwareRequest<<Inner as Requester>::SetGameScore>;
+    type SetGameScoreInline =
+        BlockAwareRequest<<Inner as Requester>::SetGameScoreInline>;
+    type SetMyCommands = BlockAwareRequest<<Inner as Requester>::SetMyCommands>;
+    type SetMyDefaultAdministratorRights = BlockAwareRequest<
+        <Inner as Requester>::SetMyDefaultAdministratorRights,
+    >;
+    type SetPassportDataErrors =
+        BlockAwareRequest<<Inner as Requester>::SetPassportDataErrors>;
+    type SetStickerPositionInSet =
+        BlockAwareRequest<<Inner as Requester>::SetStickerPositionInSet>;
+    type SetStickerSetThumb =
+        BlockAwareRequest<<Inner as Requester>::SetStickerSetThumb>;
+    type SetWebhook = BlockAwareRequest<<Inner as Requester>::SetWebhook>;
+    type StopMessageLiveLocation =
+        BlockAwareRequest<<Inner as Requester>::StopMessageLiveLocation>;
+    type StopMessageLiveLocationInline =
+        BlockAwareRequest<<Inner as Requester>::StopMessageLiveLocationInline>;
+    type StopPoll = BlockAwareRequest<<Inner as Requester>::StopPoll>;
+    type UnbanChatMember =
+        BlockAwareRequest<<Inner as Requester>::UnbanChatMember>;
+    type UnbanChatSenderChat =
+        BlockAwareRequest<<Inner as Requester>::UnbanChatSenderChat>;
+    type UnhideGeneralForumTopic =
+        BlockAwareRequest<<Inner as Requester>::UnhideGeneralForumTopic>;
+    type UnpinAllChatMessages =
+        BlockAwareRequest<<Inner as Requester>::UnpinAllChatMessages>;
+    type UnpinAllForumTopicMessages =
+        BlockAwareRequest<<Inner as Requester>::UnpinAllForumTopicMessages>;
+    type UnpinChatMessage =
+        BlockAwareRequest<<Inner as Requester>::UnpinChatMessage>;
+    type UploadStickerFile =
+        BlockAwareRequest<<Inner as Requester>::UploadStickerFile>;
+
+    fn get_updates(&self) -> Self::GetUpdates {
+        self.track(self.inner.get_updates())
+    }
+
+    fn set_webhook(&self, url: Url) -> Self::SetWebhook {
+        self.track(self.inner.set_webhook(url))
+    }
+
+    fn delete_webhook(&self) -> Self::DeleteWebhook {
+        self.track(self.inner.delete_webhook())
+    }
+
+    fn get_webhook_info(&self) -> Self::GetWebhookInfo {
+        self.track(self.inner.get_webhook_info())
+    }
+
+    fn get_me(&self) -> Self::GetMe {
+        self.track(self.inner.get_me())
+    }
+
+    fn log_out(&self) -> Self::LogOut {
+        self.track(self.inner.log_out())
+    }
+
+    fn close(&self) -> Self::Close {
+        self.track(self.inner.close())
+    }
+
+    fn send_message<C, T>(&self, chat_id: C, text: T) -> Self::SendMessage
+    where
+        C: Into<Recipient>,
+        T: Into<String>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.send_message(chat_id.clone(), text), &chat_id)
+    }
+
+    fn forward_message<C, F>(
+        &self,
+        chat_id: C,
+        from_chat_id: F,
+        message_id: MessageId,
+    ) -> Self::ForwardMessage
+    where
+        C: Into<Recipient>,
+        F: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.forward_message(
+                chat_id.clone(),
+                from_chat_id,
+                message_id,
+            ),
+            &chat_id,
+        )
+    }
+
+    fn copy_message<C, F>(
+        &self,
+        chat_id: C,
+        from_chat_id: F,
+        message_id: MessageId,
+    ) -> Self::CopyMessage
+    where
+        C: Into<Recipient>,
+        F: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.copy_message(chat_id.clone(), from_chat_id, message_id),
+            &chat_id,
+        )
+    }
+
+    fn send_photo<C>(&self, chat_id: C, photo: InputFile) -> Self::SendPhoto
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.send_photo(chat_id.clone(), photo), &chat_id)
+    }
+
+    fn send_audio<C>(&self, chat_id: C, audio: InputFile) -> Self::SendAudio
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.send_audio(chat_id.clone(), audio), &chat_id)
+    }
+
+    fn send_document<C>(
+        &self,
+        chat_id: C,
+        document: InputFile,
+    ) -> Self::SendDocument
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.send_document(chat_id.clone(), document), &chat_id)
+    }
+
+    fn send_video<C>(&self, chat_id: C, video: InputFile) -> Self::SendVideo
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.send_video(chat_id.clone(), video), &chat_id)
+    }
+
+    fn send_animation<C>(
+        &self,
+        chat_id: C,
+        animation: InputFile,
+    ) -> Self::SendAnimation
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.send_animation(chat_id.clone(), animation),
+            &chat_id,
+        )
+    }
+
+    fn send_voice<C>(&self, chat_id: C, voice: InputFile) -> Self::SendVoice
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.send_voice(chat_id.clone(), voice), &chat_id)
+    }
+
+    fn send_video_note<C>(
+        &self,
+        chat_id: C,
+        video_note: InputFile,
+    ) -> Self::SendVideoNote
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.send_video_note(chat_id.clone(), video_note),
+            &chat_id,
+        )
+    }
+
+    fn send_media_group<C, M>(
+        &self,
+        chat_id: C,
+        media: M,
+    ) -> Self::SendMediaGroup
+    where
+        C: Into<Recipient>,
+        M: IntoIterator<Item = InputMedia>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.send_media_group(chat_id.clone(), media), &chat_id)
+    }
+
+    fn send_location<C>(
+        &self,
+        chat_id: C,
+        latitude: f64,
+        longitude: f64,
+    ) -> Self::SendLocation
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.send_location(chat_id.clone(), latitude, longitude),
+            &chat_id,
+        )
+    }
+
+    fn edit_message_live_location<C>(
+        &self,
+        chat_id: C,
+        message_id: MessageId,
+        latitude: f64,
+        longitude: f64,
+    ) -> Self::EditMessageLiveLocation
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.edit_message_live_location(
+                chat_id.clone(),
+                message_id,
+                latitude,
+                longitude,
+            ),
+            &chat_id,
+        )
+    }
+
+    fn edit_message_live_location_inline<I>(
+        &self,
+        inline_message_id: I,
+        latitude: f64,
+        longitude: f64,
+    ) -> Self::EditMessageLiveLocationInline
+    where
+        I: Into<String>,
+    {
+        self.track(self.inner.edit_message_live_location_inline(
+            inline_message_id,
+            latitude,
+            longitude,
+        ))
+    }
+
+    fn stop_message_live_location<C>(
+        &self,
+        chat_id: C,
+        message_id: MessageId,
+        latitude: f64,
+        longitude: f64,
+    ) -> Self::StopMessageLiveLocation
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.stop_message_live_location(
+                chat_id.clone(),
+                message_id,
+                latitude,
+                longitude,
+            ),
+            &chat_id,
+        )
+    }
+
+    fn stop_message_live_location_inline<I>(
+        &self,
+        inline_message_id: I,
+        latitude: f64,
+        longitude: f64,
+    ) -> Self::StopMessageLiveLocationInline
+    where
+        I: Into<String>,
+    {
+        self.track(self.inner.stop_message_live_location_inline(
+            inline_message_id,
+            latitude,
+            longitude,
+        ))
+    }
+
+    fn send_venue<C, T, A>(
+        &self,
+        chat_id: C,
+        latitude: f64,
+        longitude: f64,
+        title: T,
+        address: A,
+    ) -> Self::SendVenue
+    where
+        C: Into<Recipient>,
+        T: Into<String>,
+        A: Into<String>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.send_venue(
+                chat_id.clone(),
+                latitude,
+                longitude,
+                title,
+                address,
+            ),
+            &chat_id,
+        )
+    }
+
+    fn send_contact<C, P, F>(
+        &self,
+        chat_id: C,
+        phone_number: P,
+        first_name: F,
+    ) -> Self::SendContact
+    where
+        C: Into<Recipient>,
+        P: Into<String>,
+        F: Into<String>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.send_contact(chat_id.clone(), phone_number, first_name),
+            &chat_id,
+        )
+    }
+
+    fn send_poll<C, Q, O>(
+        &self,
+        chat_id: C,
+        question: Q,
+        options: O,
+    ) -> Self::SendPoll
+    where
+        C: Into<Recipient>,
+        Q: Into<String>,
+        O: IntoIterator<Item = String>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.send_poll(chat_id.clone(), question, options),
+            &chat_id,
+        )
+    }
+
+    fn send_dice<C>(&self, chat_id: C) -> Self::SendDice
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.send_dice(chat_id.clone()), &chat_id)
+    }
+
+    fn send_chat_action<C>(
+        &self,
+        chat_id: C,
+        action: ChatAction,
+    ) -> Self::SendChatAction
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.send_chat_action(chat_id.clone(), action),
+            &chat_id,
+        )
+    }
+
+    fn get_user_profile_photos(
+        &self,
+        user_id: UserId,
+    ) -> Self::GetUserProfilePhotos {
+        self.track(self.inner.get_user_profile_photos(user_id))
+    }
+
+    fn get_file<F>(&self, file_id: F) -> Self::GetFile
+    where
+        F: Into<String>,
+    {
+        self.track(self.inner.get_file(file_id))
+    }
+
+    fn ban_chat_member<C>(
+        &self,
+        chat_id: C,
+        user_id: UserId,
+    ) -> Self::BanChatMember
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.ban_chat_member(chat_id.clone(), user_id),
+            &chat_id,
+        )
+    }
+
+    fn kick_chat_member<C>(
+        &self,
+        chat_id: C,
+        user_id: UserId,
+    ) -> Self::KickChatMember
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.kick_chat_member(chat_id.clone(), user_id),
+            &chat_id,
+        )
+    }
+
+    fn unban_chat_member<C>(
+        &self,
+        chat_id: C,
+        user_id: UserId,
+    ) -> Self::UnbanChatMember
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.unban_chat_member(chat_id.clone(), user_id),
+            &chat_id,
+        )
+    }
+
+    fn restrict_chat_member<C>(
+        &self,
+        chat_id: C,
+        user_id: UserId,
+        permissions: ChatPermissions,
+    ) -> Self::RestrictChatMember
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.restrict_chat_member(
+                chat_id.clone(),
+                user_id,
+                permissions,
+            ),
+            &chat_id,
+        )
+    }
+
+    fn promote_chat_member<C>(
+        &self,
+        chat_id: C,
+        user_id: UserId,
+    ) -> Self::PromoteChatMember
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.promote_chat_member(chat_id.clone(), user_id),
+            &chat_id,
+        )
+    }
+
+    fn set_chat_administrator_custom_title<Ch, C>(
+        &self,
+        chat_id: Ch,
+        user_id: UserId,
+        custom_title: C,
+    ) -> Self::SetChatAdministratorCustomTitle
+    where
+        Ch: Into<Recipient>,
+        C: Into<String>,
+    {
+        self.track(self.inner.set_chat_administrator_custom_title(
+            chat_id,
+            user_id,
+            custom_title,
+        ))
+    }
+
+    fn ban_chat_sender_chat<C, S>(
+        &self,
+        chat_id: C,
+        sender_chat_id: S,
+    ) -> Self::BanChatSenderChat
+    where
+        C: Into<Recipient>,
+        S: Into<ChatId>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.ban_chat_sender_chat(chat_id.clone(), sender_chat_id),
+            &chat_id,
+        )
+    }
+
+    fn unban_chat_sender_chat<C, S>(
+        &self,
+        chat_id: C,
+        sender_chat_id: S,
+    ) -> Self::UnbanChatSenderChat
+    where
+        C: Into<Recipient>,
+        S: Into<ChatId>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.unban_chat_sender_chat(chat_id.clone(), sender_chat_id),
+            &chat_id,
+        )
+    }
+
+    fn set_chat_permissions<C>(
+        &self,
+        chat_id: C,
+        permissions: ChatPermissions,
+    ) -> Self::SetChatPermissions
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.set_chat_permissions(chat_id.clone(), permissions),
+            &chat_id,
+        )
+    }
+
+    fn export_chat_invite_link<C>(
+        &self,
+        chat_id: C,
+    ) -> Self::ExportChatInviteLink
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.export_chat_invite_link(chat_id.clone()), &chat_id)
+    }
+
+    fn create_chat_invite_link<C>(
+        &self,
+        chat_id: C,
+    ) -> Self::CreateChatInviteLink
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.create_chat_invite_link(chat_id.clone()), &chat_id)
+    }
+
+    fn edit_chat_invite_link<C, I>(
+        &self,
+        chat_id: C,
+        invite_link: I,
+    ) -> Self::EditChatInviteLink
+    where
+        C: Into<Recipient>,
+        I: Into<String>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.edit_chat_invite_link(chat_id.clone(), invite_link),
+            &chat_id,
+        )
+    }
+
+    fn revoke_chat_invite_link<C, I>(
+        &self,
+        chat_id: C,
+        invite_link: I,
+    ) -> Self::RevokeChatInviteLink
+    where
+        C: Into<Recipient>,
+        I: Into<String>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.revoke_chat_invite_link(chat_id.clone(), invite_link),
+            &chat_id,
+        )
+    }
+
+    fn approve_chat_join_request<C>(
+        &self,
+        chat_id: C,
+        user_id: UserId,
+    ) -> Self::ApproveChatJoinRequest
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.approve_chat_join_request(chat_id.clone(), user_id),
+            &chat_id,
+        )
+    }
+
+    fn decline_chat_join_request<C>(
+        &self,
+        chat_id: C,
+        user_id: UserId,
+    ) -> Self::DeclineChatJoinRequest
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.decline_chat_join_request(chat_id.clone(), user_id),
+            &chat_id,
+        )
+    }
+
+    fn set_chat_photo<C>(
+        &self,
+        chat_id: C,
+        photo: InputFile,
+    ) -> Self::SetChatPhoto
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.set_chat_photo(chat_id.clone(), photo), &chat_id)
+    }
+
+    fn delete_chat_photo<C>(&self, chat_id: C) -> Self::DeleteChatPhoto
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.delete_chat_photo(chat_id.clone()), &chat_id)
+    }
+
+    fn set_chat_title<C, T>(&self, chat_id: C, title: T) -> Self::SetChatTitle
+    where
+        C: Into<Recipient>,
+        T: Into<String>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.set_chat_title(chat_id.clone(), title), &chat_id)
+    }
+
+    fn set_chat_description<C>(&self, chat_id: C) -> Self::SetChatDescription
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.set_chat_description(chat_id.clone()), &chat_id)
+    }
+
+    fn pin_chat_message<C>(
+        &self,
+        chat_id: C,
+        message_id: MessageId,
+    ) -> Self::PinChatMessage
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.pin_chat_message(chat_id.clone(), message_id),
+            &chat_id,
+        )
+    }
+
+    fn unpin_chat_message<C>(&self, chat_id: C) -> Self::UnpinChatMessage
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.unpin_chat_message(chat_id.clone()), &chat_id)
+    }
+
+    fn unpin_all_chat_messages<C>(
+        &self,
+        chat_id: C,
+    ) -> Self::UnpinAllChatMessages
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.unpin_all_chat_messages(chat_id.clone()), &chat_id)
+    }
+
+    fn leave_chat<C>(&self, chat_id: C) -> Self::LeaveChat
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.leave_chat(chat_id.clone()), &chat_id)
+    }
+
+    fn get_chat<C>(&self, chat_id: C) -> Self::GetChat
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.get_chat(chat_id.clone()), &chat_id)
+    }
+
+    fn get_chat_administrators<C>(
+        &self,
+        chat_id: C,
+    ) -> Self::GetChatAdministrators
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.get_chat_administrators(chat_id.clone()), &chat_id)
+    }
+
+    fn get_chat_member_count<C>(&self, chat_id: C) -> Self::GetChatMemberCount
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.get_chat_member_count(chat_id.clone()), &chat_id)
+    }
+
+    fn get_chat_members_count<C>(&self, chat_id: C) -> Self::GetChatMembersCount
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.get_chat_members_count(chat_id.clone()), &chat_id)
+    }
+
+    fn get_chat_member<C>(
+        &self,
+        chat_id: C,
+        user_id: UserId,
+    ) -> Self::GetChatMember
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.get_chat_member(chat_id.clone(), user_id),
+            &chat_id,
+        )
+    }
+
+    fn set_chat_sticker_set<C, S>(
+        &self,
+        chat_id: C,
+        sticker_set_name: S,
+    ) -> Self::SetChatStickerSet
+    where
+        C: Into<Recipient>,
+        S: Into<String>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.set_chat_sticker_set(chat_id.clone(), sticker_set_name),
+            &chat_id,
+        )
+    }
+
+    fn delete_chat_sticker_set<C>(
+        &self,
+        chat_id: C,
+    ) -> Self::DeleteChatStickerSet
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.delete_chat_sticker_set(chat_id.clone()), &chat_id)
+    }
+
+    fn get_forum_topic_icon_stickers(&self) -> Self::GetForumTopicIconStickers {
+        self.track(self.inner.get_forum_topic_icon_stickers())
+    }
+
+    fn create_forum_topic<C, N, I>(
+        &self,
+        chat_id: C,
+        name: N,
+        icon_color: u32,
+        icon_custom_emoji_id: I,
+    ) -> Self::CreateForumTopic
+    where
+        C: Into<Recipient>,
+        N: Into<String>,
+        I: Into<String>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.create_forum_topic(
+                chat_id.clone(),
+                name,
+                icon_color,
+                icon_custom_emoji_id,
+            ),
+            &chat_id,
+        )
+    }
+
+    fn edit_forum_topic<C>(
+        &self,
+        chat_id: C,
+        message_thread_id: i32,
+    ) -> Self::EditForumTopic
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.edit_forum_topic(chat_id.clone(), message_thread_id),
+            &chat_id,
+        )
+    }
+
+    fn close_forum_topic<C>(
+        &self,
+        chat_id: C,
+        message_thread_id: i32,
+    ) -> Self::CloseForumTopic
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.close_forum_topic(chat_id.clone(), message_thread_id),
+            &chat_id,
+        )
+    }
+
+    fn reopen_forum_topic<C>(
+        &self,
+        chat_id: C,
+        message_thread_id: i32,
+    ) -> Self::ReopenForumTopic
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.reopen_forum_topic(chat_id.clone(), message_thread_id),
+            &chat_id,
+        )
+    }
+
+    fn delete_forum_topic<C>(
+        &self,
+        chat_id: C,
+        message_thread_id: i32,
+    ) -> Self::DeleteForumTopic
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.delete_forum_topic(chat_id.clone(), message_thread_id),
+            &chat_id,
+        )
+    }
+
+    fn unpin_all_forum_topic_messages<C>(
+        &self,
+        chat_id: C,
+        message_thread_id: i32,
+    ) -> Self::UnpinAllForumTopicMessages
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.unpin_all_forum_topic_messages(
+                chat_id.clone(),
+                message_thread_id,
+            ),
+            &chat_id,
+        )
+    }
+
+    fn edit_general_forum_topic<C, N>(
+        &self,
+        chat_id: C,
+        name: N,
+    ) -> Self::EditGeneralForumTopic
+    where
+        C: Into<Recipient>,
+        N: Into<String>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.edit_general_forum_topic(chat_id.clone(), name),
+            &chat_id,
+        )
+    }
+
+    fn close_general_forum_topic<C>(
+        &self,
+        chat_id: C,
+    ) -> Self::CloseGeneralForumTopic
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.close_general_forum_topic(chat_id.clone()),
+            &chat_id,
+        )
+    }
+
+    fn reopen_general_forum_topic<C>(
+        &self,
+        chat_id: C,
+    ) -> Self::ReopenGeneralForumTopic
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.reopen_general_forum_topic(chat_id.clone()),
+            &chat_id,
+        )
+    }
+
+    fn hide_general_forum_topic<C>(
+        &self,
+        chat_id: C,
+    ) -> Self::HideGeneralForumTopic
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.hide_general_forum_topic(chat_id.clone()),
+            &chat_id,
+        )
+    }
+
+    fn unhide_general_forum_topic<C>(
+        &self,
+        chat_id: C,
+    ) -> Self::UnhideGeneralForumTopic
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.unhide_general_forum_topic(chat_id.clone()),
+            &chat_id,
+        )
+    }
+
+    fn answer_callback_query<C>(
+        &self,
+        callback_query_id: C,
+    ) -> Self::AnswerCallbackQuery
+    where
+        C: Into<String>,
+    {
+        self.track(self.inner.answer_callback_query(callback_query_id))
+    }
+
+    fn set_my_commands<C>(&self, commands: C) -> Self::SetMyCommands
+    where
+        C: IntoIterator<Item = BotCommand>,
+    {
+        self.track(self.inner.set_my_commands(commands))
+    }
+
+    fn get_my_commands(&self) -> Self::GetMyCommands {
+        self.track(self.inner.get_my_commands())
+    }
+
+    fn set_chat_menu_button(&self) -> Self::SetChatMenuButton {
+        self.track(self.inner.set_chat_menu_button())
+    }
+
+    fn get_chat_menu_button(&self) -> Self::GetChatMenuButton {
+        self.track(self.inner.get_chat_menu_button())
+    }
+
+    fn set_my_default_administrator_rights(
+        &self,
+    ) -> Self::SetMyDefaultAdministratorRights {
+        self.track(self.inner.set_my_default_administrator_rights())
+    }
+
+    fn get_my_default_administrator_rights(
+        &self,
+    ) -> Self::GetMyDefaultAdministratorRights {
+        self.track(self.inner.get_my_default_administrator_rights())
+    }
+
+    fn delete_my_commands(&self) -> Self::DeleteMyCommands {
+        self.track(self.inner.delete_my_commands())
+    }
+
+    fn answer_inline_query<I, R>(
+        &self,
+        inline_query_id: I,
+        results: R,
+    ) -> Self::AnswerInlineQuery
+    where
+        I: Into<String>,
+        R: IntoIterator<Item = InlineQueryResult>,
+    {
+        self.track(self.inner.answer_inline_query(inline_query_id, results))
+    }
+
+    fn answer_web_app_query<W>(
+        &self,
+        web_app_query_id: W,
+        result: InlineQueryResult,
+    ) -> Self::AnswerWebAppQuery
+    where
+        W: Into<String>,
+    {
+        self.track(self.inner.answer_web_app_query(web_app_query_id, result))
+    }
+
+    fn edit_message_text<C, T>(
+        &self,
+        chat_id: C,
+        message_id: MessageId,
+        text: T,
+    ) -> Self::EditMessageText
+    where
+        C: Into<Recipient>,
+        T: Into<String>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.edit_message_text(chat_id.clone(), message_id, text),
+            &chat_id,
+        )
+    }
+
+    fn edit_message_text_inline<I, T>(
+        &self,
+        inline_message_id: I,
+        text: T,
+    ) -> Self::EditMessageTextInline
+    where
+        I: Into<String>,
+        T: Into<String>,
+    {
+        self.track(self.inner.edit_message_text_inline(inline_message_id, text))
+    }
+
+    fn edit_message_caption<C>(
+        &self,
+        chat_id: C,
+        message_id: MessageId,
+    ) -> Self::EditMessageCaption
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.edit_message_caption(chat_id.clone(), message_id),
+            &chat_id,
+        )
+    }
+
+    fn edit_message_caption_inline<I>(
+        &self,
+        inline_message_id: I,
+    ) -> Self::EditMessageCaptionInline
+    where
+        I: Into<String>,
+    {
+        self.track(self.inner.edit_message_caption_inline(inline_message_id))
+    }
+
+    fn edit_message_media<C>(
+        &self,
+        chat_id: C,
+        message_id: MessageId,
+        media: InputMedia,
+    ) -> Self::EditMessageMedia
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.edit_message_media(chat_id.clone(), message_id, media),
+            &chat_id,
+        )
+    }
+
+    fn edit_message_media_inline<I>(
+        &self,
+        inline_message_id: I,
+        media: InputMedia,
+    ) -> Self::EditMessageMediaInline
+    where
+        I: Into<String>,
+    {
+        self.track(
+            self.inner.edit_message_media_inline(inline_message_id, media),
+        )
+    }
+
+    fn edit_message_reply_markup<C>(
+        &self,
+        chat_id: C,
+        message_id: MessageId,
+    ) -> Self::EditMessageReplyMarkup
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.edit_message_reply_markup(chat_id.clone(), message_id),
+            &chat_id,
+        )
+    }
+
+    fn edit_message_reply_markup_inline<I>(
+        &self,
+        inline_message_id: I,
+    ) -> Self::EditMessageReplyMarkupInline
+    where
+        I: Into<String>,
+    {
+        self.track(
+            self.inner.edit_message_reply_markup_inline(inline_message_id),
+        )
+    }
+
+    fn stop_poll<C>(&self, chat_id: C, message_id: MessageId) -> Self::StopPoll
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.stop_poll(chat_id.clone(), message_id), &chat_id)
+    }
+
+    fn delete_message<C>(
+        &self,
+        chat_id: C,
+        message_id: MessageId,
+    ) -> Self::DeleteMessage
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(
+            self.inner.delete_message(chat_id.clone(), message_id),
+            &chat_id,
+        )
+    }
+
+    fn send_sticker<C>(
+        &self,
+        chat_id: C,
+        sticker: InputFile,
+    ) -> Self::SendSticker
+    where
+        C: Into<Recipient>,
+    {
+        let chat_id = chat_id.into();
+        self.wrap(self.inner.send_sticker(chat_id.clone(), sticker), &chat_id)
+    }
+
+    fn get_sticker_set<N>(&self, name: N) -> Self::GetStickerSet
+    where
+        N: Into<String>,
+    {
+        self.track(self.inner.get_sticker_set(name))
+    }
+
+    fn get_custom_emoji_stickers<C>(
+        &self,
+        custom_emoji_ids: C,
+    ) -> Self::GetCustomEmojiStickers
+    where
+        C: IntoIterator<Item = String>,
+    {
+        self.track(self.inner.get_custom_emoji_stickers(custom_emoji_ids))
+    }
+
+    fn upload_sticker_file(
+        &self,
+        user_id: UserId,
+        png_sticker: InputFile,
+    ) -> Self::UploadStickerFile {
+        self.track(self.inner.upload_sticker_file(user_id, png_sticker))
+    }
+
+    fn create_new_sticker_set<N, T, E>(
+        &self,
+        user_id: UserId,
+        name: N,
+        title: T,
+        sticker: InputSticker,
+        emojis: E,
+    ) -> Self::CreateNewStickerSet
+    where
+        N: Into<String>,
+        T: Into<String>,
+        E: Into<String>,
+    {
+        self.track(
+            self.inner
+                .create_new_sticker_set(user_id, name, title, sticker, emojis),
+        )
+    }
+
+    fn add_sticker_to_set<N, E>(
+        &self,
+        user_id: UserId,
+        name: N,
+        sticker: InputSticker,
+        emojis: E,
+    ) -> Self::AddStickerToSet
+    where
+        N: Into<String>,
+        E: Into<String>,
+    {
+        self.track(
+            self.inner.add_sticker_to_set(user_id, name, sticker, emojis),
+        )
+    }
+
+    fn set_sticker_position_in_set<S>(
+        &self,
+        sticker: S,
+        position: u32,
+    ) -> Self::SetStickerPositionInSet
+    where
+        S: Into<String>,
+    {
+        self.track(self.inner.set_sticker_position_in_set(sticker, position))
+    }
+
+    fn delete_sticker_from_set<S>(
+        &self,
+        sticker: S,
+    ) -> Self::DeleteStickerFromSet
+    where
+        S: Into<String>,
+    {
+        self.track(self.inner.delete_sticker_from_set(sticker))
+    }
+
+    fn set_sticker_set_thumb<N>(
+        &self,
+        name: N,
+        user_id: UserId,
+    ) -> Self::SetStickerSetThumb
+    where
+        N: Into<String>,
+    {
+        self.track(self.inner.set_sticker_set_thumb(name, user_id))
+    }
+
+    fn send_invoice<Ch, T, D, Pa, P, C, Pri>(
+        &self,
+        chat_id: Ch,
+        title: T,
+        description: D,
+        payload: Pa,
+        provider_token: P,
+        currency: C,
+        prices: Pri,
+    ) -> Self::SendInvoice
+    where
+        Ch: Into<Recipient>,
+        T: Into<String>,
+        D: Into<String>,
+        Pa: Into<String>,
+        P: Into<String>,
+        C: Into<String>,
+        Pri: IntoIterator<Item = LabeledPrice>,
+    {
+        self.track(self.inner.send_invoice(
+            chat_id,
+            title,
+            description,
+            payload,
+            provider_token,
+            currency,
+            prices,
+        ))
+    }
+
+    fn create_invoice_link<T, D, Pa, P, C, Pri>(
+        &self,
+        title: T,
+        description: D,
+        payload: Pa,
+        provider_token: P,
+        currency: C,
+        prices: Pri,
+    ) -> Self::CreateInvoiceLink
+    where
+        T: Into<String>,
+        D: Into<String>,
+        Pa: Into<String>,
+        P: Into<String>,
+        C: Into<String>,
+        Pri: IntoIterator<Item = LabeledPrice>,
+    {
+        self.track(self.inner.create_invoice_link(
+            title,
+            description,
+            payload,
+            provider_token,
+            currency,
+            prices,
+        ))
+    }
+
+    fn answer_shipping_query<S>(
+        &self,
+        shipping_query_id: S,
+        ok: bool,
+    ) -> Self::AnswerShippingQuery
+    where
+        S: Into<String>,
+    {
+        self.track(self.inner.answer_shipping_query(shipping_query_id, ok))
+    }
+
+    fn answer_pre_checkout_query<P>(
+        &self,
+        pre_checkout_query_id: P,
+        ok: bool,
+    ) -> Self::AnswerPreCheckoutQuery
+    where
+        P: Into<String>,
+    {
+        self.track(
+            self.inner.answer_pre_checkout_query(pre_checkout_query_id, ok),
+        )
+    }
+
+    fn set_passport_data_errors<E>(
+        &self,
+        user_id: UserId,
+        errors: E,
+    ) -> Self::SetPassportDataErrors
+    where
+        E: IntoIterator<Item = PassportElementError>,
+    {
+        self.track(self.inner.set_passport_data_errors(user_id, errors))
+    }
+
+    fn send_game<G>(&self, chat_id: u32, game_short_name: G) -> Self::SendGame
+    where
+        G: Into<String>,
+    {
+        self.track(self.inner.send_game(chat_id, game_short_name))
+    }
+
+    fn set_game_score(
+        &self,
+        user_id: UserId,
+        score: u64,
+        chat_id: u32,
+        message_id: MessageId,
+    ) -> Self::SetGameScore {
+        self.track(
+            self.inner.set_game_score(user_id, score, chat_id, message_id),
+        )
+    }
+
+    fn set_game_score_inline<I>(
+        &self,
+        user_id: UserId,
+        score: u64,
+        inline_message_id: I,
+    ) -> Self::SetGameScoreInline
+    where
+        I: Into<String>,
+    {
+        self.track(self.inner.set_game_score_inline(
+            user_id,
+            score,
+            inline_message_id,
+        ))
+    }
+
+    fn get_game_high_scores<T>(
+        &self,
+        user_id: UserId,
+        target: T,
+    ) -> Self::GetGameHighScores
+    where
+        T: Into<TargetMessage>,
+    {
+        self.track(self.inner.get_game_high_scores(user_id, target))
+    }
+}
+
+/// Request counted in the metrics. If it's sent to a user, the user is marked
+/// inactive when they have blocked the bot.
+pub struct BlockAwareRequest<R> {
+    inner: R,
+    user: Option<ChatId>,
+    db: Arc<Database>,
+    metrics: Arc<Metrics>,
+}
+
+impl<R> HasPayload for BlockAwareRequest<R>
+where
+    R: HasPayload,
+{
+    type Payload = R::Payload;
+
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        self.inner.payload_mut()
+    }
+
+    fn payload_ref(&self) -> &Self::Payload {
+        self.inner.payload_ref()
+    }
+}
+
+type BlockAwareSend<R> = Pin<
+    Box<dyn Future<Output = Result<Output<R>, RequestError>> + Send + 'static>,
+>;
+
+impl<R> Request for BlockAwareRequest<R>
+where
+    R: Request<Err = RequestError>,
+    R::Send: 'static,
+    R::SendRef: 'static,
+    Output<R>: Send + 'static,
+{
+    type Err = RequestError;
+    type Send = BlockAwareSend<R>;
+    type SendRef = BlockAwareSend<R>;
+
+    fn send(self) -> Self::Send {
+        Box::pin(check_blocked(
+            self.inner.send(),
+            self.user,
+            self.db,
+            self.metrics,
+        ))
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        Box::pin(check_blocked(
+            self.inner.send_ref(),
+            self.user,
+            self.db.clone(),
+            self.metrics.clone(),
+        ))
+    }
+}
+
+impl<R> IntoFuture for BlockAwareRequest<R>
+where
+    R: Request<Err = RequestError>,
+    R::Send: 'static,
+    R::SendRef: 'static,
+    Output<R>: Send + 'static,
+{
+    type IntoFuture = BlockAwareSend<R>;
+    type Output = Result<Output<R>, RequestError>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send()
+    }
+}
+
+async fn check_blocked<T>(
+    request: impl Future<Output = Result<T, RequestError>>,
+    user: Option<ChatId>,
+    db: Arc<Database>,
+    metrics: Arc<Metrics>,
+) -> Result<T, RequestError> {
+    metrics.telegram_requests_in_flight.inc();
+    let result = request.await;
+    metrics.telegram_requests_in_flight.dec();
+    if let Err(e) = &result {
+        metrics.telegram_error(e);
+    }
+
+    if let (Some(user), Err(RequestError::Api(e))) = (user, &result) {
+        let reason = match e {
+            ApiError::BotBlocked => Some(DeactivationReason::BotBlocked),
+            ApiError::UserDeactivated => {
+                Some(DeactivationReason::UserDeactivated)
+            }
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            warn!("deactivating user {user}: {e}");
+            if let Err(e) = db.deactivate_user(user.0, reason).await {
+                sentry_anyhow::capture_anyhow(
+                    &e.context("error deactivating user"),
+                );
+            }
+        }
+    }
+
+    result
+}
@@ -41,6 +41,76 @@ impl TryFrom<char> for RateCode {
     }
 }
 
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum PhotoAction {
+    Delete,
+    Up,
+    Down,
+    Cover,
+}
+
+impl From<&PhotoAction> for char {
+    fn from(a: &PhotoAction) -> Self {
+        match a {
+            PhotoAction::Delete => '🗑',
+            PhotoAction::Up => '⬆',
+            PhotoAction::Down => '⬇',
+            PhotoAction::Cover => '⭐',
+        }
+    }
+}
+
+impl TryFrom<char> for PhotoAction {
+    type Error = anyhow::Error;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Ok(match c {
+            '🗑' => Self::Delete,
+            '⬆' => Self::Up,
+            '⬇' => Self::Down,
+            '⭐' => Self::Cover,
+            _ => bail!("can't parse PhotoAction"),
+        })
+    }
+}
+
+/// Moderator's decision on the profile
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum ReviewCode {
+    Approve,
+    RejectName,
+    RejectAbout,
+    RejectPhotos,
+    RejectOther,
+}
+
+impl From<&ReviewCode> for char {
+    fn from(c: &ReviewCode) -> Self {
+        match c {
+            ReviewCode::Approve => '✅',
+            ReviewCode::RejectName => '🔤',
+            ReviewCode::RejectAbout => '📝',
+            ReviewCode::RejectPhotos => '🖼',
+            ReviewCode::RejectOther => '🚫',
+        }
+    }
+}
+
+impl TryFrom<char> for ReviewCode {
+    type Error = anyhow::Error;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Ok(match c {
+            '✅' => Self::Approve,
+            '🔤' => Self::RejectName,
+            '📝' => Self::RejectAbout,
+            '🖼' => Self::RejectPhotos,
+            '🚫' => Self::RejectOther,
+            _ => bail!("can't parse ReviewCode"),
+        })
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub enum UpdateBitflags<T> {
     Update(T),
@@ -68,9 +138,27 @@ pub enum Callback {
     SetSubjectsFilter(UpdateBitflags<Subjects>),
     SetDatingPurpose(UpdateBitflags<DatingPurpose>),
     Edit,
-    Dating { dating_id: i32, code: RateCode },
+    Dating {
+        dating_id: i32,
+        code: RateCode,
+    },
     CreateProfile,
     FindPartner,
+    /// Start relay chat with the partner of mutual dating
+    Chat {
+        dating_id: i32,
+    },
+    /// Change the profile photo in the photo manager
+    Photo {
+        image_id: i32,
+        action: PhotoAction,
+    },
+    /// Decision on the profile in the review chat
+    Review {
+        user_id: i64,
+        version: i32,
+        code: ReviewCode,
+    },
 }
 
 impl Callback {
@@ -83,6 +171,9 @@ impl Callback {
             Self::Dating { code, .. } => code.into(),
             Self::CreateProfile => '✍',
             Self::FindPartner => '🚀',
+            Self::Chat { .. } => '💬',
+            Self::Photo { action, .. } => action.into(),
+            Self::Review { code, .. } => code.into(),
         }
     }
 }
@@ -95,9 +186,15 @@ impl Display for Callback {
                 f.write_fmt(format_args!("{u}"))?;
             }
             Self::SetDatingPurpose(u) => f.write_fmt(format_args!("{u}"))?,
-            Self::Dating { dating_id, code: _ } => {
+            Self::Dating { dating_id, code: _ } | Self::Chat { dating_id } => {
                 f.write_fmt(format_args!("{dating_id}"))?;
             }
+            Self::Photo { image_id, action: _ } => {
+                f.write_fmt(format_args!("{image_id}"))?;
+            }
+            Self::Review { user_id, version, code: _ } => {
+                f.write_fmt(format_args!("{user_id}_{version}"))?;
+            }
             Self::Edit | Self::CreateProfile | Self::FindPartner => {}
         };
         Ok(())
@@ -151,11 +248,26 @@ impl FromStr for Callback {
             'e' => Self::Edit,
             '✍' => Self::CreateProfile,
             '🚀' => Self::FindPartner,
+            '💬' => Self::Chat { dating_id: data.parse()? },
             '👎' | '💌' | '👍' | '💔' | '❤' => {
                 let dating_id = data.parse()?;
                 let code = first_char.try_into()?;
                 Self::Dating { dating_id, code }
             }
+            '🗑' | '⬆' | '⬇' | '⭐' => {
+                let image_id = data.parse()?;
+                let action = first_char.try_into()?;
+                Self::Photo { image_id, action }
+            }
+            '✅' | '🔤' | '📝' | '🖼' | '🚫' => {
+                let (user_id, version) =
+                    data.split_once('_').context("can't parse review")?;
+                Self::Review {
+                    user_id: user_id.parse()?,
+                    version: version.parse()?,
+                    code: first_char.try_into()?,
+                }
+            }
             _ => bail!("unknown code"),
         })
     }
@@ -0,0 +1,224 @@
+use std::{io::Cursor, sync::OnceLock};
+
+use anyhow::Context;
+use image::{ImageOutputFormat, Rgb, RgbImage};
+use imageproc::drawing::{draw_filled_circle_mut, draw_text_mut};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rusttype::{Font, Scale};
+use serde::Deserialize;
+
+use crate::{
+    text::{self, Lang},
+    types::PublicProfile,
+};
+
+const WIDTH: i32 = 800;
+const MIN_HEIGHT: i32 = 450;
+const MARGIN: i32 = 48;
+const LINE_SPACING: i32 = 10;
+
+static REGULAR_FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSans.ttf");
+static BOLD_FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSans-Bold.ttf");
+static EMOJI_FONT: OnceLock<Option<Font<'static>>> = OnceLock::new();
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CardSettings {
+    /// Font drawing characters missing in the bundled fonts, like Noto
+    /// Emoji, they're dropped without it
+    pub emoji_font: Option<String>,
+}
+
+/// Load the emoji font once, the card is drawn without it if it's broken
+fn emoji_font(settings: &CardSettings) -> Option<&'static Font<'static>> {
+    EMOJI_FONT
+        .get_or_init(|| {
+            let path = settings.emoji_font.as_ref()?;
+            let font = std::fs::read(path).ok().and_then(Font::try_from_vec);
+            if font.is_none() {
+                tracing::warn!("can't load emoji font {path}");
+            }
+            font
+        })
+        .as_ref()
+}
+
+/// Render profile as a PNG card, `seed` selects the background
+pub fn render(
+    profile: &PublicProfile,
+    seed: i64,
+    lang: Lang,
+    settings: &CardSettings,
+) -> anyhow::Result<Vec<u8>> {
+    let regular =
+        Font::try_from_bytes(REGULAR_FONT).context("can't load font")?;
+    let bold = Font::try_from_bytes(BOLD_FONT).context("can't load font")?;
+    let emoji = emoji_font(settings);
+    // Every character is drawn with the first font which has it
+    let regular_chain: Vec<_> =
+        [Some(&regular), emoji].into_iter().flatten().collect();
+    let bold_chain: Vec<_> =
+        [Some(&bold), Some(&regular), emoji].into_iter().flatten().collect();
+
+    let title = format!(
+        "{} {}, {}",
+        profile.gender,
+        profile.name,
+        profile.grade.name(lang)
+    );
+    let paragraphs = [
+        (&bold_chain, Scale::uniform(52.0), title),
+        (
+            &regular_chain,
+            Scale::uniform(30.0),
+            text::PURPOSE.fill(lang, &[&profile.dating_purpose.names(lang)]),
+        ),
+        (&regular_chain, Scale::uniform(30.0), profile.subjects.name(lang)),
+        (&regular_chain, Scale::uniform(30.0), profile.city.name(lang)),
+    ];
+
+    let max_width = WIDTH - 2 * MARGIN;
+    let lines: Vec<_> = paragraphs
+        .into_iter()
+        .flat_map(|(fonts, scale, text)| {
+            wrap(fonts, scale, &supported_text(fonts, &text), max_width)
+                .into_iter()
+                .map(move |line| (fonts, scale, line))
+        })
+        .collect();
+
+    let text_height: i32 =
+        lines.iter().map(|(_, scale, _)| scale.y as i32 + LINE_SPACING).sum();
+    let height = (text_height + 2 * MARGIN).max(MIN_HEIGHT);
+
+    let mut image = background(seed, height);
+    let mut y = MARGIN;
+    for (fonts, scale, line) in lines {
+        let mut x = MARGIN;
+        for (font, run) in runs(fonts, &line) {
+            draw_text_mut(
+                &mut image,
+                Rgb([255, 255, 255]),
+                x,
+                y,
+                scale,
+                font,
+                &run,
+            );
+            x += width(font, scale, &run);
+        }
+        y += scale.y as i32 + LINE_SPACING;
+    }
+
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)?;
+    Ok(png)
+}
+
+fn font_for<'a>(fonts: &[&'a Font<'a>], c: char) -> Option<&'a Font<'a>> {
+    fonts.iter().copied().find(|font| font.glyph(c).id().0 != 0)
+}
+
+/// Drop characters none of the fonts can draw
+fn supported_text(fonts: &[&Font], text: &str) -> String {
+    text.chars()
+        .filter(|&c| c.is_whitespace() || font_for(fonts, c).is_some())
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Split the text into parts drawn with the same font, spaces go with the
+/// preceding part
+fn runs<'a>(fonts: &[&'a Font<'a>], text: &str) -> Vec<(&'a Font<'a>, String)> {
+    let mut runs: Vec<(&Font, String)> = Vec::new();
+    for c in text.chars() {
+        let font = match (font_for(fonts, c), runs.last()) {
+            (Some(font), _) if !c.is_whitespace() => font,
+            (_, Some((last, _))) => last,
+            (font, None) => font.unwrap_or(fonts[0]),
+        };
+        match runs.last_mut() {
+            Some((last, run)) if std::ptr::eq(*last, font) => run.push(c),
+            _ => runs.push((font, c.to_string())),
+        }
+    }
+    runs
+}
+
+/// Advance of the text drawn with one font
+fn width(font: &Font, scale: Scale, text: &str) -> i32 {
+    font.layout(text, scale, rusttype::point(0.0, 0.0))
+        .map(|g| g.unpositioned().h_metrics().advance_width)
+        .sum::<f32>()
+        .ceil() as i32
+}
+
+fn line_width(fonts: &[&Font], scale: Scale, text: &str) -> i32 {
+    runs(fonts, text).iter().map(|(font, run)| width(font, scale, run)).sum()
+}
+
+fn wrap(
+    fonts: &[&Font],
+    scale: Scale,
+    text: &str,
+    max_width: i32,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        let candidate = if line.is_empty() {
+            word.to_owned()
+        } else {
+            format!("{line} {word}")
+        };
+        if !line.is_empty() && line_width(fonts, scale, &candidate) > max_width
+        {
+            lines.push(std::mem::replace(&mut line, word.to_owned()));
+        } else {
+            line = candidate;
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Vertical gradient with a few circles, colors depend on `seed`
+fn background(seed: i64, height: i32) -> RgbImage {
+    let mut rng = StdRng::seed_from_u64(u64::from_ne_bytes(seed.to_ne_bytes()));
+    let hue: f32 = rng.gen_range(0.0..360.0);
+    let top = hsv_to_rgb(hue, 0.55, 0.85);
+    let bottom = hsv_to_rgb((hue + 40.0) % 360.0, 0.7, 0.5);
+
+    let mut image = RgbImage::from_fn(WIDTH as u32, height as u32, |_, y| {
+        let t = f64::from(y) / f64::from(height);
+        Rgb([0, 1, 2].map(|i| {
+            f64::from(top[i]).mul_add(1.0 - t, f64::from(bottom[i]) * t) as u8
+        }))
+    });
+
+    let light = hsv_to_rgb(hue, 0.35, 0.95);
+    for _ in 0..4 {
+        let center = (rng.gen_range(0..WIDTH), rng.gen_range(0..height));
+        let radius = rng.gen_range(30..120);
+        draw_filled_circle_mut(&mut image, center, radius, Rgb(light));
+    }
+    image
+}
+
+fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [u8; 3] {
+    let chroma = value * saturation;
+    let second = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let rgb = match hue as u32 / 60 {
+        0 => [chroma, second, 0.0],
+        1 => [second, chroma, 0.0],
+        2 => [0.0, chroma, second],
+        3 => [0.0, second, chroma],
+        4 => [second, 0.0, chroma],
+        _ => [chroma, 0.0, second],
+    };
+    rgb.map(|v| ((v + value - chroma) * 255.0) as u8)
+}
@@ -1,36 +1,31 @@
-use std::{fmt::Display, str::FromStr};
+use std::str::FromStr;
 
 use anyhow::Context;
 use itertools::Itertools;
 use strsim::jaro_winkler;
 
+use crate::text::{self, Lang};
+
 include!(concat!(env!("OUT_DIR"), "/citiesmap.rs"));
 
 #[derive(Copy, Clone)]
 pub struct City(Option<i32>);
 
-impl Display for City {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.0 {
-            Some(id) => {
-                let county =
-                    county_by_id(id).context("county not found").unwrap();
-                let subject =
-                    subject_by_id(id).context("subject not found").unwrap();
-                let city = city_by_id(id).context("city not found").unwrap();
-
-                if subject == city {
-                    f.write_fmt(format_args!("{county} ФО, {city}"))?;
-                } else {
-                    f.write_fmt(format_args!(
-                        "{county} ФО, {subject}, {city}"
-                    ))?;
-                }
-            }
-            None => f.write_str("Город не указан")?,
+impl City {
+    pub fn name(&self, lang: Lang) -> String {
+        let Some(id) = self.0 else {
+            return text::CITY_NOT_SET.get(lang).into_owned();
+        };
+        let county = county_by_id(id).context("county not found").unwrap();
+        let subject = subject_by_id(id).context("subject not found").unwrap();
+        let city = city_by_id(id).context("city not found").unwrap();
+
+        let county = text::COUNTY.fill(lang, &[&county]);
+        if subject == city {
+            format!("{county}, {city}")
+        } else {
+            format!("{county}, {subject}, {city}")
         }
-
-        Ok(())
     }
 }
 
@@ -0,0 +1,368 @@
+use std::{collections::HashSet, path::Path};
+
+use anyhow::{ensure, Context};
+use serde::Deserialize;
+use teloxide::adaptors::throttle::Limits;
+use toml::{value::Table, Value};
+
+use crate::{
+    experiments::Experiments, limits::LimitsSettings,
+    metrics::MonitoringSettings, moderation::ModerationSettings,
+    recommendations::RecommendationsSettings, scheduler::LikesSettings,
+    spam::SpamSettings, storage::StorageSettings,
+};
+
+/// Default path of the config file, it's optional unlike the file set by
+/// `CONFIG` variable
+const CONFIG_PATH: &str = "config.toml";
+/// Prefix of variables which override any setting, `__` separates sections,
+/// e.g. `BOT_MATCHING__ACTIVE_DAYS=30`. Variables which aren't settings are
+/// ignored, other programs may use the prefix too.
+const ENV_PREFIX: &str = "BOT_";
+/// Variable, the setting it sets and the conversion of its value
+type EnvAlias = (&'static str, &'static str, fn(String) -> Value);
+/// Conventional variables which are still accepted, `BOT_` variables take
+/// precedence over them
+const ENV_ALIASES: [EnvAlias; 5] = [
+    ("TELOXIDE_TOKEN", "token", Value::String),
+    ("DATABASE_URL", "database_url", Value::String),
+    ("RUST_LOG", "log_level", Value::String),
+    ("SENTRY_DSN", "sentry_dsn", Value::String),
+    ("CHANNEL_ID", "channel_id", parse_env),
+];
+
+/// Settings of the bot, see `config.example.toml`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub token: String,
+    pub database_url: String,
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    #[serde(default)]
+    pub sentry_dsn: Option<String>,
+    /// Users must be subscribed to the channel to get recommendations
+    #[serde(default)]
+    pub channel_id: Option<i64>,
+    /// Users allowed to use [`crate::admin::AdminCommand`]s
+    #[serde(default)]
+    pub admins: HashSet<i64>,
+    #[serde(default)]
+    pub throttle: ThrottleSettings,
+    #[serde(default)]
+    pub matching: MatchingSettings,
+    #[serde(default)]
+    pub recommendations: RecommendationsSettings,
+    #[serde(default)]
+    pub profile: ProfileSettings,
+    #[serde(default)]
+    pub likes: LikesSettings,
+    #[serde(default)]
+    pub limits: LimitsSettings,
+    #[serde(default)]
+    pub spam: SpamSettings,
+    /// Checks of names, descriptions and like messages
+    #[serde(default)]
+    pub moderation: ModerationSettings,
+    #[serde(default)]
+    pub storage: StorageSettings,
+    /// Serve metrics and health checks
+    #[serde(default)]
+    pub monitoring: Option<MonitoringSettings>,
+    /// A/B tests, users are split between the arms of each experiment
+    #[serde(default)]
+    pub experiments: Experiments,
+    /// Rendering of profile cards
+    #[cfg(feature = "card")]
+    #[serde(default)]
+    pub card: crate::card::CardSettings,
+    /// Receive updates with a webhook instead of long polling
+    #[cfg(feature = "webhook")]
+    #[serde(default)]
+    pub webhook: Option<crate::webhook::WebhookSettings>,
+}
+
+fn default_log_level() -> String {
+    "info".to_owned()
+}
+
+/// Limits of outgoing messages per second or minute
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThrottleSettings {
+    pub chat_per_sec: u32,
+    pub chat_per_min: u32,
+    pub channel_per_min: u32,
+    pub overall_per_sec: u32,
+}
+
+impl Default for ThrottleSettings {
+    fn default() -> Self {
+        Self { chat_per_sec: 2, chat_per_min: 120, ..Limits::default().into() }
+    }
+}
+
+impl From<Limits> for ThrottleSettings {
+    fn from(limits: Limits) -> Self {
+        Self {
+            chat_per_sec: limits.messages_per_sec_chat,
+            chat_per_min: limits.messages_per_min_chat,
+            channel_per_min: limits.messages_per_min_channel,
+            overall_per_sec: limits.messages_per_sec_overall,
+        }
+    }
+}
+
+impl From<&ThrottleSettings> for Limits {
+    fn from(settings: &ThrottleSettings) -> Self {
+        Self {
+            messages_per_sec_chat: settings.chat_per_sec,
+            messages_per_min_chat: settings.chat_per_min,
+            messages_per_min_channel: settings.channel_per_min,
+            messages_per_sec_overall: settings.overall_per_sec,
+        }
+    }
+}
+
+/// Which profiles are recommended
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatchingSettings {
+    /// Only recommend users active during this number of days
+    pub active_days: u32,
+    /// Don't recommend the same partner again during this number of hours
+    pub reshow_after_hours: u32,
+    /// Same as `reshow_after_hours`, but in days for liked partners
+    pub reshow_liked_after_days: u32,
+    /// Order of suitable partners, experiments can change it for some users
+    pub ranking: Ranking,
+    /// Profiles created during this number of days are new
+    pub new_profile_days: u32,
+    /// New profiles are recommended first until they're shown this number
+    /// of times
+    pub new_profile_views: u32,
+    /// Views of the profile are counted during this number of days
+    pub exposure_days: u32,
+    /// How much every view during `exposure_days` lowers the chance of the
+    /// profile to be recommended
+    pub view_penalty: f64,
+    /// Same as `view_penalty` for every unanswered incoming like
+    pub pending_like_penalty: f64,
+}
+
+impl Default for MatchingSettings {
+    fn default() -> Self {
+        Self {
+            active_days: 14,
+            reshow_after_hours: 4,
+            reshow_liked_after_days: 7,
+            ranking: Ranking::default(),
+            new_profile_days: 3,
+            new_profile_views: 20,
+            exposure_days: 7,
+            view_penalty: 0.02,
+            pending_like_penalty: 0.5,
+        }
+    }
+}
+
+/// Which of the suitable partners is recommended first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ranking {
+    #[default]
+    Random,
+    /// Recently active users first
+    RecentlyActive,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileSettings {
+    /// Maximum number of photos and videos in the profile
+    pub max_media: u8,
+    /// Label of the profile creation wizard saved with its steps, change it
+    /// with the wizard to compare the versions in `/funnel`. Arms of the
+    /// experiments with the wizard texts are appended to it.
+    pub wizard_variant: String,
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        Self { max_media: 10, wizard_variant: "default".to_owned() }
+    }
+}
+
+impl Config {
+    /// Load the file set by `CONFIG` or `config.toml` and apply overrides
+    /// from the environment
+    pub fn load() -> anyhow::Result<Self> {
+        let mut table = match std::env::var("CONFIG") {
+            Ok(path) => read(Path::new(&path))?,
+            Err(_) if Path::new(CONFIG_PATH).exists() => {
+                read(Path::new(CONFIG_PATH))?
+            }
+            Err(_) => Table::new(),
+        };
+
+        for (name, key, parse) in ENV_ALIASES {
+            if let Ok(value) = std::env::var(name) {
+                set(&mut table, &[key], parse(value))?;
+            }
+        }
+        for (name, value) in std::env::vars() {
+            if let Some(path) = name.strip_prefix(ENV_PREFIX) {
+                let path = path.to_lowercase();
+                let keys: Vec<_> = path.split("__").collect();
+                let value = parse_env(value);
+                if is_setting(&keys, &value) {
+                    set(&mut table, &keys, value)
+                        .with_context(|| format!("invalid {name}"))?;
+                }
+            }
+        }
+
+        let config: Self =
+            Value::Table(table).try_into().context("invalid config")?;
+        config.validate().context("invalid config")?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        ensure!(!self.token.is_empty(), "token is empty");
+        ensure!(!self.database_url.is_empty(), "database_url is empty");
+        let throttle = &self.throttle;
+        ensure!(
+            [
+                throttle.chat_per_sec,
+                throttle.chat_per_min,
+                throttle.channel_per_min,
+                throttle.overall_per_sec,
+            ]
+            .iter()
+            .all(|&l| l > 0),
+            "throttle limits must be positive"
+        );
+        ensure!(
+            self.matching.active_days > 0,
+            "matching.active_days must be positive"
+        );
+        ensure!(
+            self.matching.exposure_days > 0,
+            "matching.exposure_days must be positive"
+        );
+        ensure!(
+            [self.matching.view_penalty, self.matching.pending_like_penalty]
+                .iter()
+                .all(|p| p.is_finite() && *p >= 0.0),
+            "matching penalties must be non-negative"
+        );
+        ensure!(
+            (0.0..=1.0).contains(&self.limits.suspicious_like_ratio),
+            "limits.suspicious_like_ratio must be from 0 to 1"
+        );
+        ensure!(
+            self.spam.threshold.is_finite() && self.spam.threshold >= 0.0,
+            "spam.threshold must be non-negative"
+        );
+        let recommendations = &self.recommendations;
+        ensure!(
+            recommendations.weight.is_finite() && recommendations.weight >= 0.0,
+            "recommendations.weight must be non-negative"
+        );
+        ensure!(
+            recommendations.rebuild_hours > 0 && recommendations.candidates > 0,
+            "recommendations.rebuild_hours and candidates must be positive"
+        );
+        // Media is sent as a single album
+        ensure!(
+            (1..=10).contains(&self.profile.max_media),
+            "profile.max_media must be from 1 to 10"
+        );
+        ensure!(
+            !self.profile.wizard_variant.is_empty(),
+            "profile.wizard_variant is empty"
+        );
+        ensure!(
+            self.likes.remind_after < self.likes.expire_after,
+            "likes.remind_after must be less than likes.expire_after"
+        );
+        self.moderation.validate()?;
+        self.experiments.validate()?;
+        #[cfg(feature = "card")]
+        if let Some(path) = &self.card.emoji_font {
+            ensure!(
+                Path::new(path).is_file(),
+                "card.emoji_font {path} not found"
+            );
+        }
+        #[cfg(feature = "webhook")]
+        if let Some(webhook) = &self.webhook {
+            webhook.validate()?;
+        }
+        Ok(())
+    }
+}
+
+fn read(path: &Path) -> anyhow::Result<Table> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("can't read {}", path.display()))?;
+    toml::from_str(&content)
+        .with_context(|| format!("can't parse {}", path.display()))
+}
+
+/// Value of the variable as TOML, or as a string if it's not valid TOML
+fn parse_env(value: String) -> Value {
+    toml::from_str::<Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut t| t.remove("value"))
+        .unwrap_or(Value::String(value))
+}
+
+/// Whether the path is known to the config. Only this setting is
+/// deserialized, so the config can fail on an unknown field only if it's the
+/// path, other errors like missing fields don't matter.
+fn is_setting(keys: &[&str], value: &Value) -> bool {
+    let mut table = Table::new();
+    if set(&mut table, keys, value.clone()).is_err() {
+        return false;
+    }
+    Value::Table(table)
+        .try_into::<Config>()
+        .map_or_else(|e| !e.to_string().contains("unknown field"), |_| true)
+}
+
+fn set(table: &mut Table, keys: &[&str], value: Value) -> anyhow::Result<()> {
+    let Some((last, sections)) = keys.split_last() else {
+        return Ok(());
+    };
+    let mut table = table;
+    for section in sections {
+        table = table
+            .entry(*section)
+            .or_insert_with(|| Value::Table(Table::new()))
+            .as_table_mut()
+            .with_context(|| format!("{section} is not a section"))?;
+    }
+    table.insert((*last).to_owned(), value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_settings() {
+        let number = Value::Integer(30);
+        assert!(is_setting(&["token"], &Value::String("1:a".to_owned())));
+        assert!(is_setting(&["matching", "active_days"], &number));
+        // Wrong values are reported when the config is loaded
+        assert!(is_setting(
+            &["matching", "active_days"],
+            &Value::Boolean(true)
+        ));
+        assert!(!is_setting(&["something"], &number));
+        assert!(!is_setting(&["matching", "something"], &number));
+    }
+}
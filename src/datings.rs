@@ -1,55 +1,108 @@
 use anyhow::Context;
-use entities::{datings, sea_orm_active_enums::ImageKind};
+use entities::{
+    datings, images,
+    sea_orm_active_enums::{EventKind, ImageKind, LikeAttachmentKind},
+};
 use teloxide::{
     prelude::*,
     types::{
         Chat, InlineKeyboardButton, InlineKeyboardMarkup, InputFile,
         InputMedia, InputMediaPhoto, InputMediaVideo, KeyboardButton,
-        KeyboardMarkup, KeyboardRemove, MessageId,
+        KeyboardMarkup, KeyboardRemove, MessageId, ParseMode,
     },
     ApiError, RequestError,
 };
-use tracing::*;
 
 use crate::{
-    callbacks::{Callback, RateCode},
+    callbacks::{Callback, PhotoAction, RateCode},
     db::Database,
-    text,
+    experiments::Exposure,
+    text::{self, Lang},
     types::PublicProfile,
-    Bot, EditProfile,
+    AppError, Bot,
 };
 
+/// Maximum length of the text sent with a like
+pub const MAX_LIKE_MESSAGE_LEN: usize = 1000;
+/// Maximum duration of voice and video notes in profiles and likes, in
+/// seconds, the texts say it's a minute
+pub const MAX_MEDIA_DURATION: u32 = 60;
+/// Maximum size of a file sent with a like, in bytes
+pub const MAX_LIKE_FILE_SIZE: u32 = 5 * 1024 * 1024;
+
+/// Message which is previewed before sending it with a like
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LikeDraft {
+    /// HTML, see [`crate::html::from_entities`]
+    pub message: Option<String>,
+    pub attachment: Option<(LikeAttachmentKind, String)>,
+}
+
 pub async fn send_profile(
     bot: &Bot,
     db: &Database,
     id: i64,
+    lang: Lang,
 ) -> anyhow::Result<()> {
     let user =
         db.get_user(id).await?.context("user to send profile not found")?;
 
     let profile: PublicProfile = (&user).try_into()?;
 
-    send_user_photos(bot, db, id, id).await?;
+    send_user_photos(bot, db, id, id, lang).await?;
 
-    let msg = format!("Так выглядит ваша анкета:\n\n{profile}");
+    let msg = format!(
+        "{}\n\n{}",
+        text::YOUR_PROFILE.html(lang),
+        profile.describe(lang)
+    );
 
     bot.send_message(ChatId(id), msg)
+        .parse_mode(ParseMode::Html)
         .reply_markup(KeyboardRemove::new())
         .await?;
 
-    send_ready_to_datings(bot, id).await?;
+    send_ready_to_datings(bot, id, lang).await?;
 
     Ok(())
 }
 
-async fn send_ready_to_datings(bot: &Bot, id: i64) -> anyhow::Result<()> {
+/// Send the profile card to share it, it's sent for `/profile` only
+#[cfg(feature = "card")]
+pub async fn send_profile_card(
+    bot: &Bot,
+    db: &Database,
+    id: i64,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    // Without media the card is already sent as the cover
+    if db.get_user_images(id).await?.is_empty() {
+        return Ok(());
+    }
+    let user = db.get_user(id).await?.context("user to send card not found")?;
+    let profile: PublicProfile = (&user).try_into()?;
+    let card = crate::card::render(&profile, id, lang, &bot.config().card)?;
+    bot.send_photo(
+        ChatId(id),
+        InputFile::memory(card).file_name("profile.png"),
+    )
+    .caption(text::PROFILE_CARD.get(lang))
+    .await?;
+    Ok(())
+}
+
+async fn send_ready_to_datings(
+    bot: &Bot,
+    id: i64,
+    lang: Lang,
+) -> anyhow::Result<()> {
     let keyboard = vec![vec![InlineKeyboardButton::callback(
-        "Смотреть анкеты 🚀",
+        text::FIND_PARTNER.get(lang),
         Callback::FindPartner.to_string(),
     )]];
     let keyboard_markup = InlineKeyboardMarkup::new(keyboard);
 
-    bot.send_message(ChatId(id), text::READY_FOR_DATINGS)
+    bot.send_message(ChatId(id), text::READY_FOR_DATINGS.get(lang))
         .reply_markup(keyboard_markup)
         .await?;
     Ok(())
@@ -59,35 +112,31 @@ pub async fn send_recommendation(
     bot: &Bot,
     db: &Database,
     chat: ChatId,
+    lang: Lang,
 ) -> anyhow::Result<()> {
     if !crate::utils::check_user_subscribed_channel(bot, chat.0).await? {
         let keyboard = vec![vec![InlineKeyboardButton::callback(
-            "Я подписался на канал",
+            text::SUBSCRIBED.get(lang),
             Callback::FindPartner.to_string(),
         )]];
         let keyboard_markup = InlineKeyboardMarkup::new(keyboard);
-        bot.send_message(
-            chat,
-            "Пожалуйста, подпишитесь на наш канал https://t.me/bvilove",
-        )
-        .reply_markup(keyboard_markup)
-        .await?;
-        return Ok(());
-    };
-
-    if crate::utils::user_url(bot, chat.0).await?.is_none() {
-        let keyboard = vec![vec![InlineKeyboardButton::callback(
-            "Я сделал юзернейм",
-            Callback::FindPartner.to_string(),
-        )]];
-        let keyboard_markup = InlineKeyboardMarkup::new(keyboard);
-        bot.send_message(chat, text::PLEASE_ALLOW_FORWARDING)
+        bot.send_message(chat, text::SUBSCRIBE_TEXT.get(lang))
             .reply_markup(keyboard_markup)
             .await?;
         return Ok(());
-    }
+    };
+
+    let experiments = &bot.config().experiments;
+    experiments.record(db, chat.0, Exposure::Matching).await?;
+    let ranking = experiments.ranking(chat.0, bot.config().matching.ranking);
+
+    let timer = bot.metrics().get_partner_duration.start_timer();
+    let partner = db.get_partner(chat.0, ranking).await?;
+    timer.observe_duration();
+    let result = if partner.is_some() { "found" } else { "empty" };
+    bot.metrics().get_partner.with_label_values(&[result]).inc();
 
-    if let Some((dating, partner)) = db.get_partner(chat.0).await? {
+    if let Some((dating, partner)) = partner {
         // Clean buttons of old message with this dating if it exist
         if let Some(msg) = dating.initiator_msg_id {
             if let Err(e) = bot
@@ -104,7 +153,7 @@ pub async fn send_recommendation(
             }
         }
 
-        send_user_photos(bot, db, partner.id, chat.0).await?;
+        send_user_photos(bot, db, partner.id, chat.0, lang).await?;
 
         let keyboard = vec![vec![
             InlineKeyboardButton::callback(
@@ -134,18 +183,26 @@ pub async fn send_recommendation(
         let partner_profile: PublicProfile = (&partner).try_into()?;
 
         let sent_msg = bot
-            .send_message(chat, partner_profile.to_string())
+            .send_message(chat, partner_profile.describe(lang))
+            .parse_mode(ParseMode::Html)
             .reply_markup(keyboard_markup)
             .await?;
 
         db.set_dating_initiator_msg(dating.id, sent_msg.id.0).await?;
+        db.log_event(
+            chat.0,
+            EventKind::RecommendationShown,
+            Some(dating.id),
+            None,
+        )
+        .await?;
     } else {
         let keyboard = vec![vec![InlineKeyboardButton::callback(
-            "Попробовать ещё раз",
+            text::TRY_AGAIN.get(lang),
             Callback::FindPartner.to_string(),
         )]];
         let keyboard_markup = InlineKeyboardMarkup::new(keyboard);
-        bot.send_message(chat, text::PARTNER_NOT_FOUND)
+        bot.send_message(chat, text::PARTNER_NOT_FOUND.get(lang))
             .reply_markup(keyboard_markup)
             .await?;
     }
@@ -168,51 +225,86 @@ pub async fn send_recommendation(
     Ok(())
 }
 
+/// Telegram won't deliver anything to the user, so retrying is useless
+pub const fn is_unreachable(e: &AppError) -> bool {
+    matches!(
+        e,
+        AppError::Telegram(RequestError::Api(
+            ApiError::BotBlocked
+                | ApiError::UserDeactivated
+                | ApiError::ChatNotFound
+        ))
+    )
+}
+
+/// Skip a part of a notification which Telegram rejects, like a file which
+/// isn't available anymore, the rest is delivered without it
+fn skip_rejected(
+    result: Result<(), AppError>,
+    part: &'static str,
+) -> Result<(), AppError> {
+    match result {
+        Err(e @ AppError::Telegram(RequestError::Api(_)))
+            if !is_unreachable(&e) =>
+        {
+            sentry_anyhow::capture_anyhow(
+                &anyhow::Error::from(e)
+                    .context(format!("error while sending {part}, skipped")),
+            );
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// Send photos of `user` which are shown with a like or a mutual like
+pub async fn send_like_photos(
+    bot: &Bot,
+    db: &Database,
+    user: i64,
+    chat: i64,
+) -> Result<(), AppError> {
+    let lang = db.get_language(chat).await?;
+    let result = send_user_photos(bot, db, user, chat, lang).await;
+    skip_rejected(result, "user photos with like")
+}
+
+/// Send the file attached to the like to the dating partner
+pub async fn send_dating_attachment(
+    bot: &Bot,
+    dating: &entities::datings::Model,
+) -> Result<(), AppError> {
+    let (Some(kind), Some(file)) =
+        (&dating.like_attachment_kind, &dating.like_attachment)
+    else {
+        return Ok(());
+    };
+    let result =
+        send_like_attachment(bot, ChatId(dating.partner_id), kind, file).await;
+    skip_rejected(result.map(drop).map_err(Into::into), "like attachment")
+}
+
+/// Deliver like notification to the dating partner, it goes after
+/// [`send_like_photos`] and [`send_dating_attachment`]
 pub async fn send_like(
     db: &Database,
     bot: &Bot,
     dating: &entities::datings::Model,
-    msg: Option<String>,
-) -> anyhow::Result<()> {
+) -> Result<(), AppError> {
     let user = db
         .get_user(dating.initiator_id)
         .await?
         .context("dating initiator not found")?;
 
     let user_profile: PublicProfile = (&user).try_into()?;
+    let lang = db.get_language(dating.partner_id).await?;
 
-    let like_msg = msg.map_or_else(
-        || format!("Кому-то понравилась ваша анкета:\n\n{user_profile}"),
-        |m| {
-            format!(
-                "Кому-то понравилась ваша анкета и он оставил вам \
-                 сообщение:\n{m}\n\n{user_profile}"
-            )
-        },
-    );
-
-    match send_user_photos(bot, db, dating.initiator_id, dating.partner_id)
-        .await
-    {
-        Err(crate::AppError::Telegram(RequestError::Api(
-            ApiError::BotBlocked | ApiError::UserDeactivated,
-        ))) => {
-            warn!("bot was blocked");
-            db.create_or_update_user(EditProfile {
-                active: Some(false),
-                ..EditProfile::new(dating.partner_id)
-            })
-            .await?;
-            return Ok(());
-        }
-        Err(e) => {
-            sentry_anyhow::capture_anyhow(
-                &anyhow::Error::from(e)
-                    .context("error sending like while sending user photos"),
-            );
-        }
-        Ok(_) => {}
-    }
+    let header = match (&dating.like_message, &dating.like_attachment) {
+        (Some(m), _) => text::LIKE_RECEIVED_MESSAGE.fill_html(lang, &[m]),
+        (None, Some(_)) => text::LIKE_RECEIVED_ATTACHMENT.html(lang),
+        (None, None) => text::LIKE_RECEIVED.html(lang),
+    };
+    let like_msg = format!("{header}\n\n{}", user_profile.describe(lang));
 
     let keyboard = vec![vec![
         InlineKeyboardButton::callback(
@@ -234,111 +326,297 @@ pub async fn send_like(
     ]];
     let keyboard_markup = InlineKeyboardMarkup::new(keyboard);
 
-    match bot
+    let sent_msg = bot
         .send_message(ChatId(dating.partner_id), like_msg)
+        .parse_mode(ParseMode::Html)
         .reply_markup(keyboard_markup)
-        .await
-    {
-        Err(RequestError::Api(
-            ApiError::BotBlocked | ApiError::UserDeactivated,
-        )) => {
-            warn!("bot was blocked");
-            db.create_or_update_user(EditProfile {
-                active: Some(false),
-                ..EditProfile::new(dating.partner_id)
-            })
-            .await?;
-            return Ok(());
-        }
-        Err(e) => {
-            sentry_anyhow::capture_anyhow(
-                &anyhow::Error::from(e)
-                    .context("error sending like while sending profile"),
-            );
-        }
-        Ok(_) => {}
+        .await?;
+    // The like is already delivered, so retrying would send it twice
+    if let Err(e) = db.set_like_delivered(dating, sent_msg.id.0).await {
+        sentry_anyhow::capture_anyhow(
+            &e.context("error while saving delivered like"),
+        );
     }
 
     Ok(())
 }
 
+/// Deliver mutual like notification to the dating initiator, it goes after
+/// [`send_like_photos`] of the partner
 pub async fn mutual_like(
     bot: &Bot,
     db: &Database,
     dating: &datings::Model,
-) -> anyhow::Result<()> {
+) -> Result<(), AppError> {
     let partner = db
         .get_user(dating.partner_id)
         .await?
         .context("dating partner not found")?;
 
     let partner_profile: PublicProfile = (&partner).try_into()?;
+    let lang = db.get_language(dating.initiator_id).await?;
 
-    db.set_dating_partner_reaction(dating.id, true).await?;
-
-    if let Err(e) =
-        send_user_photos(bot, db, dating.partner_id, dating.initiator_id).await
-    {
-        sentry_anyhow::capture_anyhow(
-            &anyhow::Error::from(e)
-                .context("error sending user photos to dating initiator"),
-        );
-    };
-
-    let initiator_keyboard = vec![vec![InlineKeyboardButton::url(
-        "Открыть чат",
-        crate::utils::user_url(bot, partner.id)
-            .await?
-            .context("can't get url")?,
+    let initiator_keyboard = vec![vec![InlineKeyboardButton::callback(
+        text::START_CHAT.get(lang),
+        Callback::Chat { dating_id: dating.id }.to_string(),
     )]];
     let initiator_keyboard_markup =
         InlineKeyboardMarkup::new(initiator_keyboard);
-    let initiator_msg = format!("Взаимный лайк!\n\n{partner_profile}");
-    if let Err(e) = bot
-        .send_message(ChatId(dating.initiator_id), initiator_msg)
+    let initiator_msg = format!(
+        "{}\n\n{}",
+        text::MUTUAL_LIKE.html(lang),
+        partner_profile.describe(lang)
+    );
+    bot.send_message(ChatId(dating.initiator_id), initiator_msg)
+        .parse_mode(ParseMode::Html)
         .reply_markup(initiator_keyboard_markup)
-        .await
-    {
-        sentry_anyhow::capture_anyhow(
-            &anyhow::Error::from(e)
-                .context("error sending profile to dating initiator"),
-        );
+        .await?;
+
+    Ok(())
+}
+
+pub async fn send_user_photos(
+    bot: &Bot,
+    db: &Database,
+    user: i64,
+    chat: i64,
+    #[cfg_attr(not(feature = "card"), allow(unused_variables))] lang: Lang,
+) -> Result<(), AppError> {
+    // Voice and video notes can't be sent in a media group, so they go last
+    let (album, separate): (Vec<_>, Vec<_>) =
+        db.get_user_images(user).await?.into_iter().partition(|i| {
+            matches!(i.kind, ImageKind::Image | ImageKind::Video)
+        });
+
+    #[cfg(feature = "card")]
+    if album.is_empty() && separate.is_empty() {
+        return send_card_cover(bot, db, user, chat, lang).await;
     }
 
+    // Each part is restored on its own, so the sent ones aren't repeated
+    if !album.is_empty() {
+        send_restoring(bot, db, user, chat, &album).await?;
+    }
+    for image in &separate {
+        send_restoring(bot, db, user, chat, std::slice::from_ref(image))
+            .await?;
+    }
     Ok(())
 }
 
-async fn send_user_photos(
+/// Send the media group or a single media, files whose ids Telegram doesn't
+/// accept anymore are uploaded from the archive
+async fn send_restoring(
     bot: &Bot,
     db: &Database,
     user: i64,
     chat: i64,
-) -> std::result::Result<(), crate::AppError> {
-    let user_images = db.get_images(user).await?;
-
-    if !user_images.is_empty() {
-        let medias = user_images.into_iter().map(|(id, kind)| {
-            let input_file = InputFile::file_id(id);
-            match kind {
-                ImageKind::Image => {
-                    let input_media_photo = InputMediaPhoto::new(input_file);
-                    InputMedia::Photo(input_media_photo)
-                }
-                ImageKind::Video => {
-                    let input_media_video = InputMediaVideo::new(input_file);
-                    InputMedia::Video(input_media_video)
-                }
+    images: &[images::Model],
+) -> Result<(), AppError> {
+    let files =
+        images.iter().map(|i| InputFile::file_id(&i.telegram_id)).collect();
+    match send_media(bot, ChatId(chat), images, files).await {
+        Err(RequestError::Api(
+            ApiError::WrongFileId
+            | ApiError::WrongFileIdOrUrl
+            | ApiError::FileIdInvalid,
+        )) => {}
+        result => {
+            result?;
+            return Ok(());
+        }
+    }
+
+    // Some file id is not valid anymore, upload archived media again
+    tracing::warn!("restoring media of user {user}");
+    let mut files = Vec::with_capacity(images.len());
+    for image in images {
+        files.push(match &image.checksum {
+            Some(checksum) => {
+                InputFile::memory(bot.storage().load(checksum).await?)
+            }
+            None => InputFile::file_id(&image.telegram_id),
+        });
+    }
+    let sent = send_media(bot, ChatId(chat), images, files).await?;
+
+    for (image, msg) in images.iter().zip(&sent) {
+        let file_id = msg
+            .photo()
+            .and_then(|p| p.last())
+            .map(|p| &p.file.id)
+            .or_else(|| msg.video().map(|v| &v.file.id))
+            .or_else(|| msg.voice().map(|v| &v.file.id))
+            .or_else(|| msg.video_note().map(|v| &v.file.id));
+        if let Some(file_id) = file_id {
+            if *file_id != image.telegram_id {
+                db.set_image_telegram_id(image.id, file_id.clone()).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rendered profile card used instead of media the user doesn't have
+#[cfg(feature = "card")]
+async fn send_card_cover(
+    bot: &Bot,
+    db: &Database,
+    user: i64,
+    chat: i64,
+    lang: Lang,
+) -> Result<(), AppError> {
+    let model = db.get_user(user).await?.context("user not found")?;
+    let profile: PublicProfile = (&model).try_into()?;
+    let card = crate::card::render(&profile, user, lang, &bot.config().card)?;
+    bot.send_photo(ChatId(chat), InputFile::memory(card).file_name("card.png"))
+        .await?;
+    Ok(())
+}
+
+/// Send photos and videos in a media group followed by voice and video
+/// notes, returns sent messages in the order of `images`
+async fn send_media(
+    bot: &Bot,
+    chat: ChatId,
+    images: &[images::Model],
+    files: Vec<InputFile>,
+) -> Result<Vec<Message>, RequestError> {
+    let mut medias = Vec::new();
+    let mut separate = Vec::new();
+    for (image, input_file) in images.iter().zip(files) {
+        match image.kind {
+            ImageKind::Image => {
+                let input_media_photo = InputMediaPhoto::new(input_file);
+                medias.push(InputMedia::Photo(input_media_photo));
+            }
+            ImageKind::Video => {
+                let input_media_video = InputMediaVideo::new(input_file);
+                medias.push(InputMedia::Video(input_media_video));
+            }
+            ImageKind::VideoNote | ImageKind::Voice => {
+                separate.push((input_file, &image.kind));
             }
+        }
+    }
+
+    let mut sent = if medias.is_empty() {
+        Vec::new()
+    } else {
+        bot.send_media_group(chat, medias).await?
+    };
+    for (input_file, kind) in separate {
+        sent.push(if *kind == ImageKind::Voice {
+            bot.send_voice(chat, input_file).await?
+        } else {
+            bot.send_video_note(chat, input_file).await?
         });
-        bot.send_media_group(ChatId(chat), medias).await?;
     }
+    Ok(sent)
+}
+
+async fn send_like_attachment(
+    bot: &Bot,
+    chat: ChatId,
+    kind: &LikeAttachmentKind,
+    file: &str,
+) -> Result<Message, RequestError> {
+    let file = InputFile::file_id(file);
+    match kind {
+        LikeAttachmentKind::Photo => bot.send_photo(chat, file).await,
+        LikeAttachmentKind::Sticker => bot.send_sticker(chat, file).await,
+        LikeAttachmentKind::VideoNote => bot.send_video_note(chat, file).await,
+        LikeAttachmentKind::Voice => bot.send_voice(chat, file).await,
+    }
+}
+
+/// Send user's media with buttons to delete and reorder them, returns the
+/// sent messages
+pub async fn send_photo_manager(
+    bot: &Bot,
+    db: &Database,
+    user: i64,
+    lang: Lang,
+) -> anyhow::Result<Vec<MessageId>> {
+    let mut messages = Vec::new();
+    for image in db.get_user_images(user).await? {
+        let keyboard_markup = InlineKeyboardMarkup::new([[
+            (PhotoAction::Delete, "🗑"),
+            (PhotoAction::Up, "⬆️"),
+            (PhotoAction::Down, "⬇️"),
+            (PhotoAction::Cover, &*text::PHOTO_COVER.get(lang)),
+        ]
+        .map(|(action, label)| {
+            InlineKeyboardButton::callback(
+                label,
+                Callback::Photo { image_id: image.id, action }.to_string(),
+            )
+        })]);
+
+        let input_file = InputFile::file_id(image.telegram_id);
+        let message = match image.kind {
+            ImageKind::Image => {
+                bot.send_photo(ChatId(user), input_file)
+                    .reply_markup(keyboard_markup)
+                    .await?
+            }
+            ImageKind::Video => {
+                bot.send_video(ChatId(user), input_file)
+                    .reply_markup(keyboard_markup)
+                    .await?
+            }
+            ImageKind::VideoNote => {
+                bot.send_video_note(ChatId(user), input_file)
+                    .reply_markup(keyboard_markup)
+                    .await?
+            }
+            ImageKind::Voice => {
+                bot.send_voice(ChatId(user), input_file)
+                    .reply_markup(keyboard_markup)
+                    .await?
+            }
+        };
+        messages.push(message.id);
+    }
+    Ok(messages)
+}
+
+pub async fn request_like_msg(
+    bot: &Bot,
+    chat: &Chat,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let keyboard = vec![vec![KeyboardButton::new(text::CANCEL.get(lang))]];
+    let keyboard_markup = KeyboardMarkup::new(keyboard).resize_keyboard(true);
+    bot.send_message(chat.id, text::SEND_LIKE.get(lang))
+        .reply_markup(keyboard_markup)
+        .await?;
     Ok(())
 }
 
-pub async fn request_like_msg(bot: &Bot, chat: &Chat) -> anyhow::Result<()> {
-    let keyboard = vec![vec![KeyboardButton::new("Отмена")]];
+/// Show the user how their like message will look like
+pub async fn preview_like(
+    bot: &Bot,
+    chat: &Chat,
+    draft: &LikeDraft,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    if let Some((kind, file)) = &draft.attachment {
+        send_like_attachment(bot, chat.id, kind, file).await?;
+    }
+
+    let preview = text::LIKE_PREVIEW.html(lang);
+    let msg = draft
+        .message
+        .as_ref()
+        .map_or_else(|| preview.clone(), |m| format!("{m}\n\n{preview}"));
+    let keyboard = vec![vec![
+        KeyboardButton::new(text::SEND.get(lang)),
+        KeyboardButton::new(text::CANCEL.get(lang)),
+    ]];
     let keyboard_markup = KeyboardMarkup::new(keyboard).resize_keyboard(true);
-    bot.send_message(chat.id, text::SEND_LIKE)
+    bot.send_message(chat.id, msg)
+        .parse_mode(ParseMode::Html)
         .reply_markup(keyboard_markup)
         .await?;
     Ok(())
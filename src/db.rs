@@ -1,7 +1,16 @@
+use std::{
+    collections::HashSet,
+    sync::{Mutex, PoisonError},
+};
+
 use anyhow::{Context, Result};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use entities::{
     prelude::*,
-    sea_orm_active_enums::{ImageKind, LocationFilter},
+    sea_orm_active_enums::{
+        DeactivationReason, EventKind, FlagReason, ImageKind,
+        LikeAttachmentKind, LocationFilter, NotificationKind, ReviewStatus,
+    },
     *,
 };
 use migration::{Migrator, MigratorTrait};
@@ -9,21 +18,74 @@ use sea_orm::{Database as SeaDatabase, DatabaseConnection, *};
 use sea_query::*;
 use tracing::{instrument, log::LevelFilter};
 
+use crate::{
+    config::{Config, MatchingSettings, Ranking},
+    recommendations::RecommendationsSettings,
+    text::Lang,
+};
+
 pub struct Database {
     conn: DatabaseConnection,
+    matching: MatchingSettings,
+    recommendations: RecommendationsSettings,
+    /// Only approved profiles are recommended
+    pre_moderation: bool,
+    /// Users deactivated by the bot, so updates from other users don't
+    /// touch the database in [`Self::reactivate_user`]
+    deactivated: Mutex<HashSet<i64>>,
 }
 
-impl Database {
-    pub async fn new() -> Result<Self> {
-        let db_url = std::env::var("DATABASE_URL")?;
+/// Unanswered likes received by the user of the outer query
+const PENDING_LIKES_EXPR: &str =
+    "(SELECT COUNT(*) FROM datings AS d WHERE d.partner_id = users.id AND \
+     d.initiator_reaction AND d.partner_reaction IS NULL AND NOT d.expired \
+     AND d.partner_msg_id IS NOT NULL AND d.initiator_id NOT IN (SELECT id \
+     FROM users WHERE shadow_banned_at IS NOT NULL))";
+
+/// Number of times the user of the outer query was recommended during `days`
+fn views_expr(days: Option<u32>) -> String {
+    let period = days.map_or_else(String::new, |days| {
+        format!(" AND d.time > CURRENT_TIMESTAMP - interval '{days} days'")
+    });
+    format!(
+        "(SELECT COUNT(*) FROM datings AS d WHERE d.partner_id = \
+         users.id{period})"
+    )
+}
 
-        let mut conn_options = ConnectOptions::new(db_url);
+impl Database {
+    pub async fn new(config: &Config) -> Result<Self> {
+        let mut conn_options = ConnectOptions::new(config.database_url.clone());
         conn_options.sqlx_logging_level(LevelFilter::Debug);
         conn_options.sqlx_logging(true);
 
         let conn = SeaDatabase::connect(conn_options).await?;
         Migrator::up(&conn, None).await?;
-        Ok(Self { conn })
+        let deactivated = Users::find()
+            .filter(users::Column::DeactivationReason.is_not_null())
+            .select_only()
+            .column(users::Column::Id)
+            .into_tuple()
+            .all(&conn)
+            .await?;
+        Ok(Self {
+            conn,
+            matching: config.matching.clone(),
+            recommendations: config.recommendations.clone(),
+            pre_moderation: config.moderation.review_chat.is_some(),
+            deactivated: Mutex::new(deactivated.into_iter().collect()),
+        })
+    }
+
+    /// Open and idle connections of the pool
+    pub fn pool_usage(&self) -> (u32, usize) {
+        let pool = self.conn.get_postgres_connection_pool();
+        (pool.size(), pool.num_idle())
+    }
+
+    pub async fn ping(&self) -> Result<()> {
+        self.conn.execute_unprepared("SELECT 1").await?;
+        Ok(())
     }
 
     #[instrument(level = "debug", skip(self))]
@@ -43,28 +105,6 @@ impl Database {
         Ok(())
     }
 
-    #[instrument(level = "debug", skip(self))]
-    pub async fn get_images(
-        &self,
-        user_id: i64,
-    ) -> Result<Vec<(String, ImageKind)>> {
-        #[derive(FromQueryResult)]
-        struct ImageTelegramId {
-            telegram_id: String,
-            kind: ImageKind,
-        }
-        Ok(images::Entity::find()
-            .filter(images::Column::UserId.eq(user_id))
-            .select_only()
-            .columns([images::Column::TelegramId, images::Column::Kind])
-            .into_model::<ImageTelegramId>()
-            .all(&self.conn)
-            .await?
-            .into_iter()
-            .map(|m| (m.telegram_id, m.kind))
-            .collect())
-    }
-
     #[instrument(level = "debug", skip(self))]
     pub async fn create_image(
         &self,
@@ -72,26 +112,202 @@ impl Database {
         tg_id: String,
         kind: ImageKind,
     ) -> Result<()> {
+        let position = Images::find()
+            .filter(images::Column::UserId.eq(user_id))
+            .count(&self.conn)
+            .await?;
         let image = entities::images::ActiveModel {
             user_id: ActiveValue::Set(user_id),
             telegram_id: ActiveValue::Set(tg_id),
             kind: ActiveValue::Set(kind),
+            position: ActiveValue::Set(position.try_into()?),
             ..Default::default()
         };
         Images::insert(image).exec(&self.conn).await?;
         Ok(())
     }
 
+    /// Users except `user_id` with photos whose perceptual hashes differ
+    /// from `phash` in at most `max_distance` bits
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_similar_photo_owners(
+        &self,
+        user_id: i64,
+        phash: i64,
+        max_distance: u32,
+    ) -> Result<Vec<i64>> {
+        Ok(Images::find()
+            .filter(images::Column::UserId.ne(user_id))
+            .filter(Expr::cust_with_values(
+                "bit_count((phash # $1)::bit(64)) <= $2",
+                [phash, max_distance.into()],
+            ))
+            .select_only()
+            .column(images::Column::UserId)
+            .distinct()
+            .into_tuple()
+            .all(&self.conn)
+            .await?)
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub async fn set_image_phash(&self, id: i32, phash: i64) -> Result<()> {
+        Images::update_many()
+            .filter(images::Column::Id.eq(id))
+            .col_expr(images::Column::Phash, Expr::value(phash))
+            .exec(&self.conn)
+            .await?;
+        Ok(())
+    }
+
+    /// User's images in the order they are shown in the profile
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_user_images(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<images::Model>> {
+        Ok(Images::find()
+            .filter(images::Column::UserId.eq(user_id))
+            .order_by_asc(images::Column::Position)
+            .order_by_asc(images::Column::Id)
+            .all(&self.conn)
+            .await?)
+    }
+
+    /// Images which are not saved to the blob storage yet
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_unarchived_images(
+        &self,
+        limit: u64,
+        skip: &HashSet<i32>,
+    ) -> Result<Vec<images::Model>> {
+        Ok(Images::find()
+            .filter(images::Column::Checksum.is_null())
+            .filter(images::Column::Id.is_not_in(skip.iter().copied()))
+            .order_by_asc(images::Column::Id)
+            .limit(limit)
+            .all(&self.conn)
+            .await?)
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub async fn set_image_checksum(
+        &self,
+        id: i32,
+        checksum: String,
+    ) -> Result<()> {
+        Images::update_many()
+            .filter(images::Column::Id.eq(id))
+            .col_expr(images::Column::Checksum, Expr::value(checksum))
+            .exec(&self.conn)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub async fn set_image_telegram_id(
+        &self,
+        id: i32,
+        telegram_id: String,
+    ) -> Result<()> {
+        Images::update_many()
+            .filter(images::Column::Id.eq(id))
+            .col_expr(images::Column::TelegramId, Expr::value(telegram_id))
+            .exec(&self.conn)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub async fn delete_image(&self, user_id: i64, id: i32) -> Result<()> {
+        Images::delete_many()
+            .filter(images::Column::Id.eq(id))
+            .filter(images::Column::UserId.eq(user_id))
+            .exec(&self.conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Set positions of user's images to their indexes in `ids`
+    #[instrument(level = "debug", skip(self))]
+    pub async fn set_images_order(
+        &self,
+        user_id: i64,
+        ids: Vec<i32>,
+    ) -> Result<()> {
+        let txn = self.conn.begin().await?;
+        for (position, id) in ids.into_iter().enumerate() {
+            let position: i16 = position.try_into()?;
+            Images::update_many()
+                .filter(images::Column::Id.eq(id))
+                .filter(images::Column::UserId.eq(user_id))
+                .col_expr(images::Column::Position, Expr::value(position))
+                .exec(&txn)
+                .await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
     #[instrument(level = "debug", skip(self))]
     pub async fn create_state(&self, id: i64) -> Result<()> {
         if States::find_by_id(id).one(&self.conn).await?.is_none() {
-            let state =
-                entities::states::ActiveModel { id: ActiveValue::Set(id) };
+            let state = entities::states::ActiveModel {
+                id: ActiveValue::Set(id),
+                ..Default::default()
+            };
             States::insert(state).exec(&self.conn).await?;
         }
         Ok(())
     }
 
+    /// Language chosen by the user or detected from the last seen
+    /// `language_code`
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_language(&self, id: i64) -> Result<Lang> {
+        let state = States::find_by_id(id).one(&self.conn).await?;
+        Ok(state.map_or_else(Lang::default, |s| state_language(&s)))
+    }
+
+    /// Remember user's `language_code` and return user's language
+    #[instrument(level = "debug", skip(self))]
+    pub async fn resolve_language(
+        &self,
+        id: i64,
+        code: Option<&str>,
+    ) -> Result<Lang> {
+        let Some(state) = States::find_by_id(id).one(&self.conn).await? else {
+            return Ok(Lang::from_code(code));
+        };
+        if state.language_code.as_deref() == code {
+            return Ok(state_language(&state));
+        }
+
+        let mut state: states::ActiveModel = state.into();
+        state.language_code = ActiveValue::Set(code.map(str::to_owned));
+        let state = state.update(&self.conn).await?;
+        Ok(state_language(&state))
+    }
+
+    /// Override detected language, `None` to detect it again
+    #[instrument(level = "debug", skip(self))]
+    pub async fn set_language(
+        &self,
+        id: i64,
+        lang: Option<Lang>,
+    ) -> Result<()> {
+        self.create_state(id).await?;
+        States::update_many()
+            .filter(states::Column::Id.eq(id))
+            .col_expr(
+                states::Column::Language,
+                Expr::value(lang.map(|l| l.code().to_owned())),
+            )
+            .exec(&self.conn)
+            .await?;
+        Ok(())
+    }
+
     #[instrument(level = "debug", skip(self))]
     pub async fn clean_images(&self, user_id: i64) -> Result<()> {
         Images::delete_many()
@@ -127,10 +343,76 @@ impl Database {
         Ok(())
     }
 
+    /// Deactivate user's profile because bot can't message them anymore
+    #[instrument(level = "debug", skip(self))]
+    pub async fn deactivate_user(
+        &self,
+        id: i64,
+        reason: DeactivationReason,
+    ) -> Result<()> {
+        let details = reason.to_value();
+        Users::update_many()
+            .col_expr(users::Column::Active, Expr::value(false))
+            .col_expr(users::Column::DeactivationReason, Expr::value(reason))
+            .col_expr(
+                users::Column::DeactivatedAt,
+                Expr::current_timestamp().into(),
+            )
+            .filter(users::Column::Id.eq(id))
+            .exec(&self.conn)
+            .await?;
+        self.deactivated
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(id);
+        self.log_event(id, EventKind::ProfileDisabled, None, Some(details))
+            .await
+    }
+
+    /// Reactivate user's profile if it was deactivated by the bot, returns
+    /// whether profile was reactivated
+    #[instrument(level = "debug", skip(self))]
+    pub async fn reactivate_user(&self, id: i64) -> Result<bool> {
+        let deactivated = self
+            .deactivated
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&id);
+        if !deactivated {
+            return Ok(false);
+        }
+        let res = Users::update_many()
+            .col_expr(users::Column::Active, Expr::value(true))
+            .col_expr(
+                users::Column::DeactivationReason,
+                Expr::value(Option::<DeactivationReason>::None),
+            )
+            .col_expr(
+                users::Column::DeactivatedAt,
+                Expr::value(Option::<sea_orm::prelude::DateTime>::None),
+            )
+            .filter(users::Column::Id.eq(id))
+            .filter(users::Column::DeactivationReason.is_not_null())
+            .exec(&self.conn)
+            .await?;
+        self.deactivated
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&id);
+        let reactivated = res.rows_affected > 0;
+        if reactivated {
+            let details = Some("reactivated".to_owned());
+            self.log_event(id, EventKind::ProfileEnabled, None, details)
+                .await?;
+        }
+        Ok(reactivated)
+    }
+
     #[instrument(level = "debug", skip(self))]
     pub async fn get_partner(
         &self,
         user_id: i64,
+        ranking: Ranking,
     ) -> Result<Option<(datings::Model, users::Model)>> {
         // Load dating initiator
         let user = Users::find_by_id(user_id)
@@ -142,6 +424,12 @@ impl Database {
 
         // TODO: fix this
         let user_id_clone = user.id;
+        let reshow_after =
+            format!("interval '{} hours'", self.matching.reshow_after_hours);
+        let reshow_liked_after = format!(
+            "interval '{} days'",
+            self.matching.reshow_liked_after_days
+        );
 
         let last_unresponded_dating = Datings::find()
             .filter(datings::Column::InitiatorId.eq(user_id))
@@ -162,9 +450,14 @@ impl Database {
             .filter(users::Column::Id.ne(user_id))
             // Only recommend activated profiles
             .filter(users::Column::Active.eq(true))
+            // Shadow banned users aren't shown to anyone
+            .filter(users::Column::ShadowBannedAt.is_null())
             // Only recommend active users
             .filter(users::Column::LastActivity.into_expr().gt(
-                Expr::current_timestamp().sub(Expr::cust("interval '14 days'")),
+                Expr::current_timestamp().sub(Expr::cust(&format!(
+                    "interval '{} days'",
+                    self.matching.active_days
+                ))),
             ))
             // Respect users's graduation delta preference
             .filter(users::Column::GraduationYear.between(
@@ -283,25 +576,82 @@ impl Database {
                                 Condition::any()
                                     .add(datings::Column::Time.into_expr().gt(
                                         Expr::current_timestamp().sub(
-                                            Expr::cust("interval '4 hours'"),
+                                            Expr::cust(&reshow_after),
                                         ),
                                     ))
                                     .add(datings::Column::Time.into_expr().gt(
                                         Expr::current_timestamp().sub(
-                                            Expr::cust("interval '7 days'"),
+                                            Expr::cust(&reshow_liked_after),
                                         ),
                                     ).and(
                                         datings::Column::InitiatorReaction
                                             .eq(true)
+                                    ).and(
+                                        // Expired likes are like skips
+                                        datings::Column::Expired.eq(false)
                                     )),
                             )
                     })
                     .into(),
             )
             .group_by(users::Column::Id)
-            .having(datings::Column::Id.count().eq(0))
+            .having(datings::Column::Id.count().eq(0));
+
+        if self.pre_moderation {
+            partner_query = partner_query
+                .filter(users::Column::ReviewStatus.eq(ReviewStatus::Approved));
+        }
+
+        let matching = &self.matching;
+        if matching.new_profile_views > 0 {
+            // New profiles are guaranteed to be shown several times
+            partner_query = partner_query.order_by_desc(Expr::cust(&format!(
+                "users.created > CURRENT_TIMESTAMP - interval '{} days' AND \
+                 {} < {}",
+                matching.new_profile_days,
+                views_expr(None),
+                matching.new_profile_views
+            )));
+        }
+        if ranking == Ranking::RecentlyActive {
+            partner_query =
+                partner_query.order_by_desc(users::Column::LastActivity);
+        }
+        // Frequently shown profiles and ones with many unanswered likes are
+        // shown less, so others get attention too
+        let dampening = format!(
+            "(1 + {} * {} + {} * {})",
+            matching.view_penalty,
+            views_expr(Some(matching.exposure_days)),
+            matching.pending_like_penalty,
+            PENDING_LIKES_EXPR
+        );
+        let weight = self.recommendations.weight;
+        if weight > 0.0 {
+            // Scores of collaborative filtering give candidates a head start
+            // in the random order
+            partner_query = partner_query
+                .join_rev(
+                    JoinType::LeftJoin,
+                    partner_scores::Entity::belongs_to(users::Entity)
+                        .from(partner_scores::Column::PartnerId)
+                        .to(users::Column::Id)
+                        .on_condition(move |_left, _right| {
+                            partner_scores::Column::UserId
+                                .eq(user_id_clone)
+                                .into_condition()
+                        })
+                        .into(),
+                )
+                .order_by_desc(Expr::cust(&format!(
+                    "(COALESCE(MAX(partner_scores.score), 0) * {weight} + \
+                     RANDOM()) / {dampening}"
+                )));
+        } else {
             // Get random partner
-            .order_by_desc(SimpleExpr::from(Func::random()));
+            partner_query = partner_query
+                .order_by_desc(Expr::cust(&format!("RANDOM() / {dampening}")));
+        }
 
         // Respect user's subject preference
         if user.subjects_filter != 0 {
@@ -380,28 +730,51 @@ impl Database {
     #[instrument(level = "debug", skip(self))]
     pub async fn set_dating_initiator_reaction(
         &self,
-        dating: i32,
+        dating: &datings::Model,
         reaction: bool,
     ) -> Result<()> {
+        let txn = self.conn.begin().await?;
         Datings::update_many()
-            .filter(datings::Column::Id.eq(dating))
+            .filter(datings::Column::Id.eq(dating.id))
             .col_expr(datings::Column::InitiatorReaction, Expr::value(reaction))
-            .exec(&self.conn)
+            .exec(&txn)
             .await?;
+        let details = if reaction { "like" } else { "dislike" };
+        Self::record_event(
+            &txn,
+            dating.initiator_id,
+            EventKind::Reaction,
+            Some(dating.id),
+            Some(details.to_owned()),
+        )
+        .await?;
+        txn.commit().await?;
         Ok(())
     }
 
     #[instrument(level = "debug", skip(self))]
     pub async fn set_dating_partner_reaction(
         &self,
-        dating: i32,
+        dating: &datings::Model,
         reaction: bool,
     ) -> Result<()> {
+        let txn = self.conn.begin().await?;
         Datings::update_many()
-            .filter(datings::Column::Id.eq(dating))
+            .filter(datings::Column::Id.eq(dating.id))
             .col_expr(datings::Column::PartnerReaction, Expr::value(reaction))
-            .exec(&self.conn)
+            .exec(&txn)
             .await?;
+        let details =
+            if reaction { "response_like" } else { "response_dislike" };
+        Self::record_event(
+            &txn,
+            dating.partner_id,
+            EventKind::Reaction,
+            Some(dating.id),
+            Some(details.to_owned()),
+        )
+        .await?;
+        txn.commit().await?;
         Ok(())
     }
 
@@ -418,4 +791,935 @@ impl Database {
             .await?;
         Ok(())
     }
+
+    /// Save the message with the like sent to the partner
+    #[instrument(level = "debug", skip(self))]
+    pub async fn set_like_delivered(
+        &self,
+        dating: &datings::Model,
+        msg: i32,
+    ) -> Result<()> {
+        let txn = self.conn.begin().await?;
+        Datings::update_many()
+            .filter(datings::Column::Id.eq(dating.id))
+            .col_expr(datings::Column::PartnerMsgId, Expr::value(msg))
+            .exec(&txn)
+            .await?;
+        Self::record_event(
+            &txn,
+            dating.partner_id,
+            EventKind::LikeDelivered,
+            Some(dating.id),
+            None,
+        )
+        .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Save user's consent to reveal contacts to the dating partner, `None`
+    /// if the user has already agreed
+    #[instrument(level = "debug", skip(self))]
+    pub async fn reveal_contact(
+        &self,
+        dating: i32,
+        user: i64,
+    ) -> Result<Option<datings::Model>> {
+        let txn = self.conn.begin().await?;
+        // The lock lets only one of the partners see the mutual consent
+        let dating = Datings::find_by_id(dating)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .context("dating not found")?;
+        let mut active: datings::ActiveModel = dating.clone().into();
+        if dating.initiator_id == user {
+            if dating.initiator_reveal {
+                return Ok(None);
+            }
+            active.initiator_reveal = ActiveValue::Set(true);
+        } else if dating.partner_id == user {
+            if dating.partner_reveal {
+                return Ok(None);
+            }
+            active.partner_reveal = ActiveValue::Set(true);
+        } else {
+            anyhow::bail!("user is not a participant of the dating");
+        }
+        let dating = active.update(&txn).await?;
+        txn.commit().await?;
+        Ok(Some(dating))
+    }
+
+    /// Delivered likes which are still waiting for partner's reaction after
+    /// `hours`. Likes of shadow banned users are never shown to partners.
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_pending_likes(
+        &self,
+        hours: u32,
+        reminded: Option<bool>,
+    ) -> Result<Vec<datings::Model>> {
+        let mut query = Datings::find()
+            .filter(datings::Column::InitiatorReaction.eq(true))
+            .filter(datings::Column::PartnerReaction.is_null())
+            .filter(datings::Column::Expired.eq(false))
+            .filter(datings::Column::PartnerMsgId.is_not_null())
+            .filter(
+                datings::Column::InitiatorId.not_in_subquery(
+                    Query::select()
+                        .column(users::Column::Id)
+                        .from(users::Entity)
+                        .and_where(users::Column::ShadowBannedAt.is_not_null())
+                        .to_owned(),
+                ),
+            )
+            .filter(
+                datings::Column::Time.into_expr().lt(Expr::current_timestamp()
+                    .sub(Expr::cust(&format!("interval '{hours} hours'")))),
+            );
+        if let Some(r) = reminded {
+            query = query.filter(datings::Column::Reminded.eq(r));
+        }
+        Ok(query.all(&self.conn).await?)
+    }
+
+    /// Mark likes reminded and enqueue reminder for their partner
+    #[instrument(level = "debug", skip(self))]
+    pub async fn remind_about_likes(
+        &self,
+        partner: i64,
+        datings: Vec<i32>,
+    ) -> Result<()> {
+        let txn = self.conn.begin().await?;
+        Datings::update_many()
+            .filter(datings::Column::Id.is_in(datings))
+            .col_expr(datings::Column::Reminded, Expr::value(true))
+            .exec(&txn)
+            .await?;
+        Self::enqueue_notification(
+            &txn,
+            partner,
+            NotificationKind::LikeReminder,
+            None,
+        )
+        .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Tell the chat partner that the other side has left the chat
+    #[instrument(level = "debug", skip(self))]
+    pub async fn notify_chat_left(
+        &self,
+        partner: i64,
+        dating_id: i32,
+    ) -> Result<()> {
+        Self::enqueue_notification(
+            &self.conn,
+            partner,
+            NotificationKind::ChatLeft,
+            Some(dating_id),
+        )
+        .await
+    }
+
+    /// Mark like expired and optionally notify its initiator
+    #[instrument(level = "debug", skip(self))]
+    pub async fn expire_like(
+        &self,
+        dating: &datings::Model,
+        notify: bool,
+    ) -> Result<()> {
+        let txn = self.conn.begin().await?;
+        Datings::update_many()
+            .filter(datings::Column::Id.eq(dating.id))
+            .col_expr(datings::Column::Expired, Expr::value(true))
+            .exec(&txn)
+            .await?;
+        if notify {
+            Self::enqueue_notification(
+                &txn,
+                dating.initiator_id,
+                NotificationKind::LikeExpired,
+                Some(dating.id),
+            )
+            .await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Active profiles with the most unanswered likes and views as
+    /// `(id, name, views, pending likes)`
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_exposure(
+        &self,
+        limit: u64,
+    ) -> Result<Vec<(i64, String, i64, i64)>> {
+        let views = views_expr(Some(self.matching.exposure_days));
+        Ok(Users::find()
+            .filter(users::Column::Active.eq(true))
+            .select_only()
+            .columns([users::Column::Id, users::Column::Name])
+            .column_as(Expr::cust(&views), "views")
+            .column_as(Expr::cust(PENDING_LIKES_EXPR), "pending")
+            .order_by_desc(Expr::cust(PENDING_LIKES_EXPR))
+            .order_by_desc(Expr::cust(&views))
+            .limit(limit)
+            .into_tuple()
+            .all(&self.conn)
+            .await?)
+    }
+
+    /// Reactions as `(user, partner, liked)`, responses to likes included
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_reactions(&self) -> Result<Vec<(i64, i64, bool)>> {
+        let datings: Vec<(i64, i64, Option<bool>, Option<bool>)> =
+            Datings::find()
+                .filter(datings::Column::InitiatorReaction.is_not_null())
+                .select_only()
+                .columns([
+                    datings::Column::InitiatorId,
+                    datings::Column::PartnerId,
+                    datings::Column::InitiatorReaction,
+                    datings::Column::PartnerReaction,
+                ])
+                .into_tuple()
+                .all(&self.conn)
+                .await?;
+        Ok(datings
+            .into_iter()
+            .flat_map(|(initiator, partner, initiator_r, partner_r)| {
+                [
+                    initiator_r.map(|r| (initiator, partner, r)),
+                    partner_r.map(|r| (partner, initiator, r)),
+                ]
+            })
+            .flatten()
+            .collect())
+    }
+
+    /// Replace all scores of collaborative filtering
+    #[instrument(level = "debug", skip_all)]
+    pub async fn set_partner_scores(
+        &self,
+        scores: Vec<partner_scores::Model>,
+    ) -> Result<()> {
+        let txn = self.conn.begin().await?;
+        PartnerScores::delete_many().exec(&txn).await?;
+        // Postgres limits the number of parameters of a query
+        for chunk in scores.chunks(10_000) {
+            let models = chunk.iter().cloned().map(|s| s.into_active_model());
+            PartnerScores::insert_many(models).exec(&txn).await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Save initiator's like and enqueue notification for the partner
+    #[instrument(level = "debug", skip(self))]
+    pub async fn like(
+        &self,
+        dating: &datings::Model,
+        msg: Option<String>,
+        attachment: Option<(LikeAttachmentKind, String)>,
+    ) -> Result<()> {
+        let details = if msg.is_some() || attachment.is_some() {
+            "like_with_message"
+        } else {
+            "like"
+        };
+        let (attachment_kind, attachment) = attachment.unzip();
+        let txn = self.conn.begin().await?;
+        datings::ActiveModel {
+            id: ActiveValue::Unchanged(dating.id),
+            initiator_reaction: ActiveValue::Set(Some(true)),
+            like_message: ActiveValue::Set(msg),
+            like_attachment_kind: ActiveValue::Set(attachment_kind),
+            like_attachment: ActiveValue::Set(attachment),
+            ..Default::default()
+        }
+        .update(&txn)
+        .await?;
+        Self::record_event(
+            &txn,
+            dating.initiator_id,
+            EventKind::Reaction,
+            Some(dating.id),
+            Some(details.to_owned()),
+        )
+        .await?;
+        Self::enqueue_notification(
+            &txn,
+            dating.partner_id,
+            NotificationKind::Like,
+            Some(dating.id),
+        )
+        .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Save partner's like and enqueue notification for the initiator
+    #[instrument(level = "debug", skip(self))]
+    pub async fn mutual_like(&self, dating: &datings::Model) -> Result<()> {
+        let txn = self.conn.begin().await?;
+        Datings::update_many()
+            .filter(datings::Column::Id.eq(dating.id))
+            .col_expr(datings::Column::PartnerReaction, Expr::value(true))
+            .exec(&txn)
+            .await?;
+        Self::record_event(
+            &txn,
+            dating.partner_id,
+            EventKind::Reaction,
+            Some(dating.id),
+            Some("response_like".to_owned()),
+        )
+        .await?;
+        for user in [dating.initiator_id, dating.partner_id] {
+            Self::record_event(
+                &txn,
+                user,
+                EventKind::Match,
+                Some(dating.id),
+                None,
+            )
+            .await?;
+        }
+        Self::enqueue_notification(
+            &txn,
+            dating.initiator_id,
+            NotificationKind::MutualLike,
+            Some(dating.id),
+        )
+        .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Append the event to the analytics log
+    #[instrument(level = "debug", skip(self))]
+    pub async fn log_event(
+        &self,
+        user_id: i64,
+        kind: EventKind,
+        dating_id: Option<i32>,
+        details: Option<String>,
+    ) -> Result<()> {
+        Self::record_event(&self.conn, user_id, kind, dating_id, details).await
+    }
+
+    async fn record_event<C: ConnectionTrait>(
+        conn: &C,
+        user_id: i64,
+        kind: EventKind,
+        dating_id: Option<i32>,
+        details: Option<String>,
+    ) -> Result<()> {
+        let event = events::ActiveModel {
+            user_id: ActiveValue::Set(user_id),
+            kind: ActiveValue::Set(kind),
+            dating_id: ActiveValue::Set(dating_id),
+            details: ActiveValue::Set(details),
+            ..Default::default()
+        };
+        Events::insert(event).exec(conn).await?;
+        Ok(())
+    }
+
+    /// Time and kind of the user's reactions during the last `hours`, oldest
+    /// first
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_recent_reactions(
+        &self,
+        user_id: i64,
+        hours: u32,
+    ) -> Result<Vec<(NaiveDateTime, Option<String>)>> {
+        Ok(Events::find()
+            .filter(events::Column::UserId.eq(user_id))
+            .filter(events::Column::Kind.eq(EventKind::Reaction))
+            .filter(
+                events::Column::Created
+                    .into_expr()
+                    .gt(Expr::current_timestamp()
+                        .sub(Expr::cust(&format!("interval '{hours} hours'")))),
+            )
+            .order_by_asc(events::Column::Id)
+            .select_only()
+            .columns([events::Column::Created, events::Column::Details])
+            .into_tuple()
+            .all(&self.conn)
+            .await?)
+    }
+
+    /// Time until the oldest of the last `quota` reactions of `kinds` is
+    /// older than `hours`, `None` if there are fewer such reactions. It's
+    /// computed with the clock of the database which saved the reactions.
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_quota_reset(
+        &self,
+        user_id: i64,
+        kinds: &[&str],
+        quota: u32,
+        hours: u32,
+    ) -> Result<Option<chrono::Duration>> {
+        let secs: Option<i64> = Events::find()
+            .filter(events::Column::UserId.eq(user_id))
+            .filter(events::Column::Kind.eq(EventKind::Reaction))
+            .filter(events::Column::Details.is_in(kinds.iter().copied()))
+            .filter(
+                events::Column::Created
+                    .into_expr()
+                    .gt(Expr::current_timestamp()
+                        .sub(Expr::cust(&format!("interval '{hours} hours'")))),
+            )
+            .order_by_desc(events::Column::Id)
+            .offset(u64::from(quota.saturating_sub(1)))
+            .select_only()
+            .column_as(
+                Expr::cust(&format!(
+                    "CAST(EXTRACT(EPOCH FROM created + interval '{hours} \
+                     hours' - CURRENT_TIMESTAMP) AS bigint)"
+                )),
+                "reset",
+            )
+            .into_tuple()
+            .one(&self.conn)
+            .await?;
+        Ok(secs.map(|s| chrono::Duration::seconds(s.max(0))))
+    }
+
+    /// Texts of the last `limit` likes sent by the user, newest first
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_like_messages(
+        &self,
+        user_id: i64,
+        limit: u64,
+    ) -> Result<Vec<String>> {
+        Ok(Datings::find()
+            .filter(datings::Column::InitiatorId.eq(user_id))
+            .filter(datings::Column::LikeMessage.is_not_null())
+            .order_by_desc(datings::Column::Id)
+            .limit(limit)
+            .select_only()
+            .column(datings::Column::LikeMessage)
+            .into_tuple()
+            .all(&self.conn)
+            .await?)
+    }
+
+    /// Number of the user's events of the kind
+    #[instrument(level = "debug", skip(self))]
+    pub async fn count_events(
+        &self,
+        user_id: i64,
+        kind: EventKind,
+    ) -> Result<u64> {
+        Ok(Events::find()
+            .filter(events::Column::UserId.eq(user_id))
+            .filter(events::Column::Kind.eq(kind))
+            .count(&self.conn)
+            .await?)
+    }
+
+    /// Record a step of the profile creation wizard, `variant` tells which
+    /// version of the wizard the user went through
+    #[instrument(level = "debug", skip(self))]
+    pub async fn log_step(
+        &self,
+        user_id: i64,
+        kind: EventKind,
+        step: &str,
+        variant: &str,
+    ) -> Result<()> {
+        let event = events::ActiveModel {
+            user_id: ActiveValue::Set(user_id),
+            kind: ActiveValue::Set(kind),
+            details: ActiveValue::Set(Some(step.to_owned())),
+            variant: ActiveValue::Set(Some(variant.to_owned())),
+            ..Default::default()
+        };
+        Events::insert(event).exec(&self.conn).await?;
+        Ok(())
+    }
+
+    /// Record the arm of the experiment unless the user is already assigned
+    #[instrument(level = "debug", skip(self))]
+    pub async fn assign_experiment(
+        &self,
+        user_id: i64,
+        experiment: &str,
+        arm: &str,
+    ) -> Result<()> {
+        let event = events::ActiveModel {
+            user_id: ActiveValue::Set(user_id),
+            kind: ActiveValue::Set(EventKind::ExperimentAssigned),
+            details: ActiveValue::Set(Some(experiment.to_owned())),
+            variant: ActiveValue::Set(Some(arm.to_owned())),
+            ..Default::default()
+        };
+        // The partial unique index keeps the first assignment
+        Events::insert(event)
+            .on_conflict(
+                OnConflict::columns([
+                    events::Column::UserId,
+                    events::Column::Details,
+                ])
+                .target_and_where(
+                    events::Column::Kind.eq(EventKind::ExperimentAssigned),
+                )
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(&self.conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Assignments to the experiment and outcomes of the assigned users
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_experiment_events(
+        &self,
+        experiment: &str,
+    ) -> Result<Vec<events::Model>> {
+        let assigned = Condition::all()
+            .add(events::Column::Kind.eq(EventKind::ExperimentAssigned))
+            .add(events::Column::Details.eq(experiment));
+        Ok(Events::find()
+            .filter(
+                Condition::any().add(assigned.clone()).add(
+                    Condition::all()
+                        .add(events::Column::Kind.is_in([
+                            EventKind::RecommendationShown,
+                            EventKind::Reaction,
+                            EventKind::Match,
+                        ]))
+                        .add(
+                            events::Column::UserId.in_subquery(
+                                Query::select()
+                                    .column(events::Column::UserId)
+                                    .from(events::Entity)
+                                    .cond_where(assigned)
+                                    .to_owned(),
+                            ),
+                        ),
+                ),
+            )
+            .order_by_asc(events::Column::Id)
+            .all(&self.conn)
+            .await?)
+    }
+
+    /// Wizard step events since the start of the day
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_step_events(
+        &self,
+        since: NaiveDate,
+    ) -> Result<Vec<events::Model>> {
+        Ok(Events::find()
+            .filter(
+                events::Column::Kind
+                    .is_in([EventKind::StepEntered, EventKind::StepCompleted]),
+            )
+            .filter(events::Column::Created.gte(since.and_time(NaiveTime::MIN)))
+            .order_by_asc(events::Column::Id)
+            .all(&self.conn)
+            .await?)
+    }
+
+    /// Events which happened from `from` till the end of `to`
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_events(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<events::Model>> {
+        Ok(Events::find()
+            .filter(events::Column::Created.gte(from.and_time(NaiveTime::MIN)))
+            .filter(
+                events::Column::Created.lt(to
+                    .succ_opt()
+                    .context("date is out of range")?
+                    .and_time(NaiveTime::MIN)),
+            )
+            .order_by_asc(events::Column::Id)
+            .all(&self.conn)
+            .await?)
+    }
+
+    async fn enqueue_notification<C: ConnectionTrait>(
+        conn: &C,
+        user_id: i64,
+        kind: NotificationKind,
+        dating_id: Option<i32>,
+    ) -> Result<()> {
+        let notification = notifications::ActiveModel {
+            user_id: ActiveValue::Set(user_id),
+            kind: ActiveValue::Set(kind),
+            dating_id: ActiveValue::Set(dating_id),
+            ..Default::default()
+        };
+        Notifications::insert(notification).exec(conn).await?;
+        Ok(())
+    }
+
+    /// Undelivered notifications which should be sent right now
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_due_notifications(
+        &self,
+        limit: u64,
+    ) -> Result<Vec<notifications::Model>> {
+        Ok(Notifications::find()
+            .filter(notifications::Column::Delivered.is_null())
+            .filter(notifications::Column::Failed.is_null())
+            .filter(
+                notifications::Column::NextAttempt
+                    .into_expr()
+                    .lte(Expr::current_timestamp()),
+            )
+            .order_by_asc(notifications::Column::Id)
+            .limit(limit)
+            .all(&self.conn)
+            .await?)
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub async fn set_notification_delivered(&self, id: i32) -> Result<()> {
+        Notifications::update_many()
+            .filter(notifications::Column::Id.eq(id))
+            .col_expr(
+                notifications::Column::Delivered,
+                Expr::current_timestamp().into(),
+            )
+            .exec(&self.conn)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub async fn postpone_notification(
+        &self,
+        id: i32,
+        attempts: i16,
+        delay_secs: u64,
+        error: String,
+    ) -> Result<()> {
+        Notifications::update_many()
+            .filter(notifications::Column::Id.eq(id))
+            .col_expr(notifications::Column::Attempts, Expr::value(attempts))
+            .col_expr(
+                notifications::Column::NextAttempt,
+                Expr::current_timestamp().add(Expr::cust(&format!(
+                    "interval '{delay_secs} seconds'"
+                ))),
+            )
+            .col_expr(notifications::Column::LastError, Expr::value(error))
+            .exec(&self.conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Save that the parts of the notification before `step` are sent
+    #[instrument(level = "debug", skip(self))]
+    pub async fn set_notification_step(
+        &self,
+        id: i32,
+        step: i16,
+    ) -> Result<()> {
+        Notifications::update_many()
+            .filter(notifications::Column::Id.eq(id))
+            .col_expr(notifications::Column::Step, Expr::value(step))
+            .exec(&self.conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Move notification to the dead letters
+    #[instrument(level = "debug", skip(self))]
+    pub async fn fail_notification(
+        &self,
+        id: i32,
+        attempts: i16,
+        error: String,
+    ) -> Result<()> {
+        Notifications::update_many()
+            .filter(notifications::Column::Id.eq(id))
+            .col_expr(notifications::Column::Attempts, Expr::value(attempts))
+            .col_expr(
+                notifications::Column::Failed,
+                Expr::current_timestamp().into(),
+            )
+            .col_expr(notifications::Column::LastError, Expr::value(error))
+            .exec(&self.conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Add the user to the moderation queue
+    #[instrument(level = "debug", skip(self))]
+    pub async fn create_flag(
+        &self,
+        user_id: i64,
+        reason: FlagReason,
+        details: String,
+    ) -> Result<()> {
+        let flag = flags::ActiveModel {
+            user_id: ActiveValue::Set(user_id),
+            reason: ActiveValue::Set(reason),
+            details: ActiveValue::Set(details),
+            ..Default::default()
+        };
+        Flags::insert(flag).exec(&self.conn).await?;
+        Ok(())
+    }
+
+    /// Whether the user is already in the moderation queue for the reason
+    #[instrument(level = "debug", skip(self))]
+    pub async fn has_unresolved_flag(
+        &self,
+        user_id: i64,
+        reason: FlagReason,
+    ) -> Result<bool> {
+        Ok(Flags::find()
+            .filter(flags::Column::UserId.eq(user_id))
+            .filter(flags::Column::Reason.eq(reason))
+            .filter(flags::Column::Resolved.is_null())
+            .count(&self.conn)
+            .await?
+            > 0)
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_unresolved_flags(
+        &self,
+        reason: FlagReason,
+    ) -> Result<Vec<(flags::Model, Option<users::Model>)>> {
+        Ok(Flags::find()
+            .filter(flags::Column::Resolved.is_null())
+            .filter(flags::Column::Reason.eq(reason))
+            .order_by_asc(flags::Column::Created)
+            .find_also_related(Users)
+            .all(&self.conn)
+            .await?)
+    }
+
+    /// Hide the user from others and add them to the moderation queue,
+    /// returns `false` if the user is already shadow banned
+    #[instrument(level = "debug", skip(self))]
+    pub async fn shadow_ban(
+        &self,
+        user_id: i64,
+        details: String,
+    ) -> Result<bool> {
+        let txn = self.conn.begin().await?;
+        let res = Users::update_many()
+            .col_expr(
+                users::Column::ShadowBannedAt,
+                Expr::current_timestamp().into(),
+            )
+            .filter(users::Column::Id.eq(user_id))
+            .filter(users::Column::ShadowBannedAt.is_null())
+            .exec(&txn)
+            .await?;
+        let banned = res.rows_affected > 0;
+        if banned {
+            flags::ActiveModel {
+                user_id: ActiveValue::Set(user_id),
+                reason: ActiveValue::Set(FlagReason::Spam),
+                details: ActiveValue::Set(details),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+        txn.commit().await?;
+        Ok(banned)
+    }
+
+    /// Show the user to others again and close their spam flags, returns
+    /// `false` if the user isn't shadow banned
+    #[instrument(level = "debug", skip(self))]
+    pub async fn lift_shadow_ban(&self, user_id: i64) -> Result<bool> {
+        let txn = self.conn.begin().await?;
+        let res = Users::update_many()
+            .col_expr(
+                users::Column::ShadowBannedAt,
+                Expr::value(Option::<sea_orm::prelude::DateTime>::None),
+            )
+            .filter(users::Column::Id.eq(user_id))
+            .filter(users::Column::ShadowBannedAt.is_not_null())
+            .exec(&txn)
+            .await?;
+        Flags::update_many()
+            .filter(flags::Column::UserId.eq(user_id))
+            .filter(flags::Column::Reason.eq(FlagReason::Spam))
+            .filter(flags::Column::Resolved.is_null())
+            .col_expr(flags::Column::Resolved, Expr::current_timestamp().into())
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(res.rows_affected > 0)
+    }
+
+    /// Time since the profile was created by the clock of the database
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_account_age(
+        &self,
+        user_id: i64,
+    ) -> Result<chrono::Duration> {
+        let secs: i64 = Users::find_by_id(user_id)
+            .select_only()
+            .column_as(
+                Expr::cust(
+                    "CAST(EXTRACT(EPOCH FROM CURRENT_TIMESTAMP - created) AS \
+                     bigint)",
+                ),
+                "age",
+            )
+            .into_tuple()
+            .one(&self.conn)
+            .await?
+            .context("user not found")?;
+        Ok(chrono::Duration::seconds(secs))
+    }
+
+    /// Current date of the database, which stamps the events
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_today(&self) -> Result<NaiveDate> {
+        let row = self
+            .conn
+            .query_one(Statement::from_string(
+                self.conn.get_database_backend(),
+                "SELECT CURRENT_DATE AS today".to_owned(),
+            ))
+            .await?
+            .context("no current date")?;
+        Ok(row.try_get("", "today")?)
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub async fn is_shadow_banned(&self, user_id: i64) -> Result<bool> {
+        Ok(Users::find_by_id(user_id)
+            .filter(users::Column::ShadowBannedAt.is_not_null())
+            .count(&self.conn)
+            .await?
+            > 0)
+    }
+
+    /// Hide the profile until it's reviewed, returns the version of the
+    /// profile which is reviewed, older reviews are outdated
+    #[instrument(level = "debug", skip(self))]
+    pub async fn hold_for_review(&self, user_id: i64) -> Result<i32> {
+        let txn = self.conn.begin().await?;
+        Users::update_many()
+            .col_expr(
+                users::Column::ReviewStatus,
+                Expr::value(ReviewStatus::Pending),
+            )
+            .col_expr(
+                users::Column::ReviewVersion,
+                users::Column::ReviewVersion.into_expr().add(1),
+            )
+            .filter(users::Column::Id.eq(user_id))
+            .exec(&txn)
+            .await?;
+        let user = Users::find_by_id(user_id)
+            .one(&txn)
+            .await?
+            .context("user to review not found")?;
+        txn.commit().await?;
+        Ok(user.review_version)
+    }
+
+    /// Save the moderator's decision, returns `false` if the profile was
+    /// changed or already reviewed
+    #[instrument(level = "debug", skip(self))]
+    pub async fn set_review_status(
+        &self,
+        user_id: i64,
+        version: i32,
+        approved: bool,
+    ) -> Result<bool> {
+        let status = if approved {
+            ReviewStatus::Approved
+        } else {
+            ReviewStatus::Rejected
+        };
+        let res = Users::update_many()
+            .col_expr(users::Column::ReviewStatus, Expr::value(status))
+            .filter(users::Column::Id.eq(user_id))
+            .filter(users::Column::ReviewVersion.eq(version))
+            .filter(users::Column::ReviewStatus.eq(ReviewStatus::Pending))
+            .exec(&self.conn)
+            .await?;
+        Ok(res.rows_affected > 0)
+    }
+
+    /// Returns `false` if there is no unresolved flag with such id. Spam
+    /// flags are closed with the ban by [`Self::lift_shadow_ban`] only.
+    #[instrument(level = "debug", skip(self))]
+    pub async fn resolve_flag(&self, id: i32) -> Result<bool> {
+        let res = Flags::update_many()
+            .filter(flags::Column::Id.eq(id))
+            .filter(flags::Column::Reason.ne(FlagReason::Spam))
+            .filter(flags::Column::Resolved.is_null())
+            .col_expr(flags::Column::Resolved, Expr::current_timestamp().into())
+            .exec(&self.conn)
+            .await?;
+        Ok(res.rows_affected > 0)
+    }
+
+    /// Texts changed by admins
+    #[instrument(level = "debug", skip(self))]
+    pub async fn get_texts(&self) -> Result<Vec<texts::Model>> {
+        Ok(Texts::find().all(&self.conn).await?)
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub async fn set_text(
+        &self,
+        key: &str,
+        lang: Lang,
+        value: String,
+        admin: i64,
+    ) -> Result<()> {
+        let text = texts::ActiveModel {
+            key: ActiveValue::Set(key.to_owned()),
+            lang: ActiveValue::Set(lang.code().to_owned()),
+            value: ActiveValue::Set(value),
+            updated_by: ActiveValue::Set(admin),
+            // The default is the time of the database, also on conflict
+            updated: ActiveValue::NotSet,
+        };
+        Texts::insert(text)
+            .on_conflict(
+                OnConflict::columns([texts::Column::Key, texts::Column::Lang])
+                    .update_columns([
+                        texts::Column::Value,
+                        texts::Column::UpdatedBy,
+                        texts::Column::Updated,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&self.conn)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub async fn delete_text(&self, key: &str, lang: Lang) -> Result<()> {
+        Texts::delete_by_id((key.to_owned(), lang.code().to_owned()))
+            .exec(&self.conn)
+            .await?;
+        Ok(())
+    }
+}
+
+fn state_language(state: &states::Model) -> Lang {
+    state
+        .language
+        .as_deref()
+        .and_then(|l| l.parse().ok())
+        .unwrap_or_else(|| Lang::from_code(state.language_code.as_deref()))
 }
@@ -0,0 +1,338 @@
+//! A/B experiments set in `[[experiments]]` of the config
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use anyhow::{ensure, Context};
+use entities::sea_orm_active_enums::EventKind;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::Ranking,
+    db::Database,
+    funnel::percent,
+    text::{self, Lang, Text},
+};
+
+/// Texts which can differ between arms. Labels of buttons are compared with
+/// the user's input, so only prompts of the profile wizard can be tested.
+pub const TEXTS: &[Text] = &[
+    text::REQUEST_NAME,
+    text::REQUEST_GENDER,
+    text::REQUEST_GENDER_FILTER,
+    text::REQUEST_GRADE,
+    text::EDIT_SUBJECTS,
+    text::EDIT_PARTNER_SUBJECTS,
+    text::REQUEST_SET_DATING_PURPOSE,
+    text::REQUEST_CITY,
+    text::EDIT_LOCATION_FILTER,
+    text::EDIT_ABOUT,
+    text::REQUEST_SET_PHOTOS,
+];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Experiments(Vec<Experiment>);
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Experiment {
+    /// Also salts the bucketing, so renaming reshuffles the users
+    pub name: String,
+    pub arms: Vec<Arm>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Arm {
+    pub name: String,
+    /// Share of the users relative to other arms
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    /// Replaces `matching.ranking`
+    #[serde(default)]
+    pub ranking: Option<Ranking>,
+    /// Replaced texts by key and language, see [`TEXTS`]
+    #[serde(default)]
+    pub texts: HashMap<String, HashMap<String, String>>,
+}
+
+const fn default_weight() -> u32 {
+    1
+}
+
+/// Where users meet experiments, they're assigned to them there
+#[derive(Debug, Clone, Copy)]
+pub enum Exposure {
+    /// Searching a partner
+    Matching,
+    /// Starting the profile wizard
+    Onboarding,
+}
+
+impl Experiment {
+    /// Arm of the user, it's the same on every call and every instance
+    pub fn arm(&self, user: i64) -> Option<&Arm> {
+        let total: u64 = self.arms.iter().map(|a| u64::from(a.weight)).sum();
+        if total == 0 {
+            return None;
+        }
+        let hash = Sha256::digest(format!("{}:{user}", self.name));
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&hash[..8]);
+        let mut bucket = u64::from_be_bytes(bytes) % total;
+        self.arms.iter().find(|arm| {
+            let weight = u64::from(arm.weight);
+            if bucket < weight {
+                return true;
+            }
+            bucket -= weight;
+            false
+        })
+    }
+
+    fn is_exposed(&self, exposure: Exposure) -> bool {
+        self.arms.iter().any(|arm| match exposure {
+            Exposure::Matching => arm.ranking.is_some(),
+            Exposure::Onboarding => !arm.texts.is_empty(),
+        })
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        ensure!(!self.name.is_empty(), "experiment name is empty");
+        ensure!(self.arms.len() >= 2, "experiment must have several arms");
+        let mut names = HashSet::new();
+        for arm in &self.arms {
+            ensure!(names.insert(&arm.name), "arm {} is repeated", arm.name);
+            ensure!(arm.weight > 0, "weight of arm {} is zero", arm.name);
+            for (key, values) in &arm.texts {
+                let text = TEXTS
+                    .iter()
+                    .find(|t| t.key == key)
+                    .with_context(|| format!("text {key} can't be tested"))?;
+                for (lang, value) in values {
+                    let lang: Lang = lang.parse().map_err(|()| {
+                        anyhow::anyhow!("unknown language {lang} of {key}")
+                    })?;
+                    ensure!(
+                        value.matches("{}").count() == text.placeholders(lang),
+                        "wrong number of {{}} in {key}"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Experiments {
+    pub fn iter(&self) -> impl Iterator<Item = &Experiment> {
+        self.0.iter()
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Experiment> {
+        self.0.iter().find(|e| e.name == name)
+    }
+
+    /// Ranking of partners for the user
+    pub fn ranking(&self, user: i64, default: Ranking) -> Ranking {
+        self.0.iter().find_map(|e| e.arm(user)?.ranking).unwrap_or(default)
+    }
+
+    /// Text for the user, see [`TEXTS`]
+    pub fn text(&self, text: Text, lang: Lang, user: i64) -> Cow<'static, str> {
+        self.0
+            .iter()
+            .find_map(|e| e.arm(user)?.texts.get(text.key)?.get(lang.code()))
+            .map_or_else(|| text.get(lang), |t| Cow::Owned(t.clone()))
+    }
+
+    /// Variant of the profile wizard saved with its steps: the configured
+    /// label followed by the user's arms of experiments with the wizard
+    /// texts, so `/funnel` compares the arms
+    pub fn wizard_variant(&self, user: i64, default: &str) -> String {
+        let arms = self
+            .0
+            .iter()
+            .filter(|e| e.is_exposed(Exposure::Onboarding))
+            .filter_map(|e| Some(format!("{}:{}", e.name, e.arm(user)?.name)));
+        std::iter::once(default.to_owned())
+            .chain(arms)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Save arms of the experiments which the user meets
+    pub async fn record(
+        &self,
+        db: &Database,
+        user: i64,
+        exposure: Exposure,
+    ) -> anyhow::Result<()> {
+        for experiment in self.0.iter().filter(|e| e.is_exposed(exposure)) {
+            if let Some(arm) = experiment.arm(user) {
+                db.assign_experiment(user, &experiment.name, &arm.name).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut names = HashSet::new();
+        let mut ranking = None;
+        let mut texts = HashMap::new();
+        for experiment in &self.0 {
+            let name = &experiment.name;
+            experiment
+                .validate()
+                .with_context(|| format!("invalid experiment {name}"))?;
+            ensure!(names.insert(name), "experiment {name} is repeated");
+            // Overlapping experiments would spoil each other's results
+            if experiment.is_exposed(Exposure::Matching) {
+                if let Some(other) = ranking.replace(name) {
+                    anyhow::bail!("{other} and {name} both change ranking");
+                }
+            }
+            let keys: HashSet<_> =
+                experiment.arms.iter().flat_map(|a| a.texts.keys()).collect();
+            for key in keys {
+                if let Some(other) = texts.insert(key, name) {
+                    anyhow::bail!("{other} and {name} both change {key}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct ArmStats {
+    users: usize,
+    shown: usize,
+    likes: usize,
+    matches: usize,
+}
+
+/// Compare like and match rates of the arms
+pub async fn report(
+    db: &Database,
+    experiment: &Experiment,
+) -> anyhow::Result<String> {
+    let events = db.get_experiment_events(&experiment.name).await?;
+
+    let mut arms: Vec<(String, ArmStats)> = experiment
+        .arms
+        .iter()
+        .map(|a| (a.name.clone(), ArmStats::default()))
+        .collect();
+    let mut assigned = HashMap::new();
+    for event in &events {
+        if event.kind != EventKind::ExperimentAssigned {
+            continue;
+        }
+        // Arms removed from the config are still reported
+        let arm = event.variant.clone().unwrap_or_default();
+        let index = arms.iter().position(|(name, _)| *name == arm);
+        let index = index.unwrap_or_else(|| {
+            arms.push((arm, ArmStats::default()));
+            arms.len() - 1
+        });
+        arms[index].1.users += 1;
+        assigned.insert(event.user_id, (index, event.created));
+    }
+
+    for event in &events {
+        let Some(&(index, since)) = assigned.get(&event.user_id) else {
+            continue;
+        };
+        if event.created < since {
+            continue;
+        }
+        let stats = &mut arms[index].1;
+        match (&event.kind, event.details.as_deref()) {
+            (EventKind::RecommendationShown, _) => stats.shown += 1,
+            (EventKind::Reaction, Some("like" | "like_with_message")) => {
+                stats.likes += 1;
+            }
+            (EventKind::Match, _) => stats.matches += 1,
+            _ => {}
+        }
+    }
+
+    let mut lines = vec![format!(
+        "Эксперимент {}, лайки и мэтчи в % от показанных анкет",
+        experiment.name
+    )];
+    for (name, stats) in arms {
+        lines.push(format!(
+            "{name}: {} польз., показано {}, лайков {} ({}), мэтчей {} ({})",
+            stats.users,
+            stats.shown,
+            stats.likes,
+            percent(stats.likes, stats.shown),
+            stats.matches,
+            percent(stats.matches, stats.shown),
+        ));
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn experiment(name: &str, weights: &[u32]) -> Experiment {
+        Experiment {
+            name: name.to_owned(),
+            arms: weights
+                .iter()
+                .enumerate()
+                .map(|(i, &weight)| Arm {
+                    name: i.to_string(),
+                    weight,
+                    ranking: None,
+                    texts: HashMap::new(),
+                })
+                .collect(),
+        }
+    }
+
+    fn arm(experiment: &Experiment, user: i64) -> &str {
+        &experiment.arm(user).unwrap().name
+    }
+
+    #[test]
+    fn arm_is_stable() {
+        let first = experiment("test", &[1, 1]);
+        let second = experiment("test", &[1, 1]);
+        for user in 0..100 {
+            assert_eq!(arm(&first, user), arm(&second, user));
+        }
+    }
+
+    #[test]
+    fn arms_follow_weights() {
+        let experiment = experiment("test", &[1, 3]);
+        let users = 10_000;
+        let second = (0..users).filter(|&u| arm(&experiment, u) == "1").count();
+        assert!((7_000..8_000).contains(&second), "{second} of {users}");
+    }
+
+    #[test]
+    fn name_salts_buckets() {
+        let first = experiment("first", &[1, 1]);
+        let second = experiment("second", &[1, 1]);
+        let moved =
+            (0..1_000).filter(|&u| arm(&first, u) != arm(&second, u)).count();
+        assert!((400..600).contains(&moved), "{moved} users moved");
+    }
+
+    #[test]
+    fn no_weight_no_arm() {
+        assert!(experiment("test", &[]).arm(1).is_none());
+        assert!(experiment("test", &[0, 0]).arm(1).is_none());
+    }
+}
@@ -0,0 +1,145 @@
+//! Conversion of the profile creation wizard, see
+//! [`crate::handle::wizard_step`]
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use entities::{events, sea_orm_active_enums::EventKind};
+use itertools::Itertools;
+
+use crate::db::Database;
+
+/// Steps of the wizard in their order
+pub const STEPS: [&str; 11] = [
+    "name",
+    "gender",
+    "gender_filter",
+    "graduation_year",
+    "subjects",
+    "subjects_filter",
+    "dating_purpose",
+    "city",
+    "location_filter",
+    "about",
+    "photos",
+];
+
+/// Users who started the wizard during the same week with the same variant
+#[derive(Debug, Default)]
+struct Cohort {
+    started: usize,
+    steps: [StepStats; STEPS.len()],
+}
+
+#[derive(Debug, Default)]
+struct StepStats {
+    entered: usize,
+    /// Time spent by each user who completed the step
+    durations: Vec<Duration>,
+}
+
+/// Report for the cohorts of the last `weeks`, one message per cohort
+pub async fn report(db: &Database, weeks: u32) -> anyhow::Result<Vec<String>> {
+    // Events are stamped with the database clock, so weeks follow it too
+    let today = db.get_today().await?;
+    let since = week_start(today) - Duration::weeks((weeks - 1).into());
+    let events = db.get_step_events(since).await?;
+
+    let mut cohorts: BTreeMap<(NaiveDate, String), Cohort> = BTreeMap::new();
+    let by_user = events.iter().into_group_map_by(|e| e.user_id);
+    for user_events in by_user.values() {
+        // Users who started before the period are skipped
+        let Some(start) = user_events.iter().find(|e| {
+            e.kind == EventKind::StepEntered
+                && e.details.as_deref() == Some(STEPS[0])
+        }) else {
+            continue;
+        };
+        let variant = start.variant.clone().unwrap_or_default();
+        let cohort = cohorts
+            .entry((week_start(start.created.date()), variant))
+            .or_default();
+        cohort.started += 1;
+
+        for (step, stats) in STEPS.iter().zip(&mut cohort.steps) {
+            let Some(entered) =
+                find(user_events, &EventKind::StepEntered, step, start.created)
+            else {
+                continue;
+            };
+            stats.entered += 1;
+            if let Some(completed) =
+                find(user_events, &EventKind::StepCompleted, step, entered)
+            {
+                stats.durations.push(completed - entered);
+            }
+        }
+    }
+
+    Ok(cohorts
+        .into_iter()
+        .map(|((week, variant), cohort)| format_cohort(week, &variant, cohort))
+        .collect())
+}
+
+/// Time of the first event of the step since `since`
+fn find(
+    events: &[&events::Model],
+    kind: &EventKind,
+    step: &str,
+    since: NaiveDateTime,
+) -> Option<NaiveDateTime> {
+    events
+        .iter()
+        .find(|e| {
+            e.kind == *kind
+                && e.details.as_deref() == Some(step)
+                && e.created >= since
+        })
+        .map(|e| e.created)
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday().into())
+}
+
+fn format_cohort(week: NaiveDate, variant: &str, cohort: Cohort) -> String {
+    let finished = cohort.steps.last().map_or(0, |s| s.durations.len());
+    let mut lines = vec![format!(
+        "Неделя с {}, вариант {variant}: начали {}, заполнили анкету {} ({})",
+        week.format("%d.%m.%Y"),
+        cohort.started,
+        finished,
+        percent(finished, cohort.started),
+    )];
+    for (step, mut stats) in STEPS.into_iter().zip(cohort.steps) {
+        let completed = stats.durations.len();
+        stats.durations.sort_unstable();
+        let median = stats
+            .durations
+            .get(completed / 2)
+            .map_or_else(|| "—".to_owned(), |d| format_duration(*d));
+        lines.push(format!(
+            "{step}: {} → {completed} ({}), медиана {median}",
+            stats.entered,
+            percent(completed, stats.entered),
+        ));
+    }
+    lines.join("\n")
+}
+
+pub fn percent(part: usize, total: usize) -> String {
+    if total == 0 {
+        return "—".to_owned();
+    }
+    format!("{}%", part * 100 / total)
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.num_seconds();
+    match secs {
+        ..=59 => format!("{secs} с"),
+        60..=3599 => format!("{} мин {} с", secs / 60, secs % 60),
+        _ => format!("{} ч {} мин", secs / 3600, secs % 3600 / 60),
+    }
+}
@@ -2,7 +2,9 @@ use std::{mem, sync::Arc};
 
 use anyhow::{bail, ensure, Context};
 use db::Database;
-use entities::sea_orm_active_enums::{Gender, ImageKind, LocationFilter};
+use entities::sea_orm_active_enums::{
+    EventKind, Gender, ImageKind, LikeAttachmentKind, LocationFilter,
+};
 use teloxide::{
     // net::Download,
     prelude::*,
@@ -10,14 +12,17 @@ use teloxide::{
         Chat, InlineKeyboardButton, InlineKeyboardMarkup, KeyboardButton,
         KeyboardMarkup, KeyboardRemove,
     },
-    utils::command::BotCommands,
 };
 use tracing::instrument;
 
 use crate::{
-    callbacks::{Callback, RateCode, UpdateBitflags},
+    callbacks::{Callback, PhotoAction, RateCode, UpdateBitflags},
     cities::{self, City},
-    db, text,
+    datings::LikeDraft,
+    db,
+    experiments::Exposure,
+    moderation::{moderate_text, Field},
+    text::{self, Lang},
     types::{DatingPurpose, Grade, GraduationYear, Subjects},
     utils, Bot, EditProfile, MyDialogue, State,
 };
@@ -32,6 +37,8 @@ enum HandleError {
     Length,
     #[error("попробуйте ещё раз")]
     Retry,
+    #[error("текст не прошёл модерацию")]
+    Rejected,
     #[error("ignore an error")]
     Ignore,
     #[error("wrong callback code")]
@@ -39,13 +46,13 @@ enum HandleError {
 }
 
 macro_rules! make_macros {
-    ($bot:ident, $msg:ident, $state:ident, $chat:ident) => {
+    ($bot:ident, $msg:ident, $state:ident, $chat:ident, $lang:ident) => {
         // Why macros? Because async closures are unstable,
         // the only difference is "!"
         macro_rules! upd_print {
             ($e:expr) => {
                 let e = $e;
-                crate::handle::print_state(&e, $bot, $chat).await?;
+                crate::handle::print_state(&e, $bot, $chat, $lang).await?;
                 *$state = e;
             };
         }
@@ -84,12 +91,18 @@ async fn handle_error(
     bot: &Bot,
     state: &State,
     chat: &Chat,
+    lang: Lang,
 ) -> anyhow::Result<()> {
     use HandleError::*;
     match e.downcast_ref::<HandleError>() {
         Some(h) => match h {
             NeedText | WrongText | Length | Retry => {
-                print_state(state, bot, chat).await?;
+                print_state(state, bot, chat, lang).await?;
+            }
+            Rejected => {
+                bot.send_message(chat.id, text::TEXT_REJECTED.get(lang))
+                    .await?;
+                print_state(state, bot, chat, lang).await?;
             }
             Ignore => {}
             WrongCode => return Err(e),
@@ -106,11 +119,16 @@ pub async fn handle_message(
     dialogue: MyDialogue,
     mut state: State,
     msg: Message,
+    lang: Lang,
 ) -> anyhow::Result<()> {
-    if let Err(e) = try_handle_message(&db, &bot, &mut state, &msg).await {
-        handle_error(e, &bot, &state, &msg.chat).await?;
+    let step = wizard_step(&state);
+    if let Err(e) = try_handle_message(&db, &bot, &mut state, &msg, lang).await
+    {
+        handle_error(e, &bot, &state, &msg.chat, lang).await?;
     }
+    let new_step = wizard_step(&state);
     dialogue.update(state).await?;
+    log_wizard_step(&db, &bot, msg.chat.id.0, step, new_step).await;
     Ok(())
 }
 
@@ -121,15 +139,87 @@ pub async fn handle_callback(
     dialogue: MyDialogue,
     mut state: State,
     q: CallbackQuery,
+    lang: Lang,
 ) -> anyhow::Result<()> {
     let msg = q.message.as_ref().context("callback message is None")?;
     let data = q.data.as_deref().context("callback data is None")?;
+    let step = wizard_step(&state);
     if let Err(e) =
-        try_handle_callback(&db, &bot, &mut state, msg, data, &q).await
+        try_handle_callback(&db, &bot, &mut state, msg, data, &q, lang).await
     {
-        handle_error(e, &bot, &state, &msg.chat).await?;
+        handle_error(e, &bot, &state, &msg.chat, lang).await?;
     }
+    let new_step = wizard_step(&state);
     dialogue.update(state).await?;
+    log_wizard_step(&db, &bot, msg.chat.id.0, step, new_step).await;
+    Ok(())
+}
+
+/// Step of the profile creation wizard, `None` if the profile isn't being
+/// created, see [`crate::funnel::STEPS`]
+pub const fn wizard_step(state: &State) -> Option<&'static str> {
+    use State::*;
+
+    let (step, p) = match state {
+        SetName(p) => ("name", p),
+        SetGender(p) => ("gender", p),
+        SetGenderFilter(p) => ("gender_filter", p),
+        SetGraduationYear(p) => ("graduation_year", p),
+        SetSubjects(p) => ("subjects", p),
+        SetSubjectsFilter(p) => ("subjects_filter", p),
+        SetDatingPurpose(p) => ("dating_purpose", p),
+        SetCity(p) => ("city", p),
+        SetLocationFilter(p) => ("location_filter", p),
+        SetAbout(p) => ("about", p),
+        SetPhotos(p) | ManagePhotos(p) => ("photos", p),
+        _ => return None,
+    };
+    if p.create_new {
+        Some(step)
+    } else {
+        None
+    }
+}
+
+/// Record leaving the wizard step `old` and entering the step `new`, the
+/// analytics don't interrupt the wizard
+pub async fn log_wizard_step(
+    db: &Database,
+    bot: &Bot,
+    user: i64,
+    old: Option<&'static str>,
+    new: Option<&'static str>,
+) {
+    if let Err(e) = try_log_wizard_step(db, bot, user, old, new).await {
+        tracing::warn!("can't log wizard step of {user}: {e}");
+        sentry_anyhow::capture_anyhow(
+            &e.context("error while logging wizard step"),
+        );
+    }
+}
+
+async fn try_log_wizard_step(
+    db: &Database,
+    bot: &Bot,
+    user: i64,
+    old: Option<&'static str>,
+    new: Option<&'static str>,
+) -> anyhow::Result<()> {
+    if old == new {
+        return Ok(());
+    }
+    let config = bot.config();
+    let variant =
+        config.experiments.wizard_variant(user, &config.profile.wizard_variant);
+    if let Some(old) = old {
+        db.log_step(user, EventKind::StepCompleted, old, &variant).await?;
+    }
+    if let Some(new) = new {
+        if new == crate::funnel::STEPS[0] {
+            config.experiments.record(db, user, Exposure::Onboarding).await?;
+        }
+        db.log_step(user, EventKind::StepEntered, new, &variant).await?;
+    }
     Ok(())
 }
 
@@ -138,6 +228,7 @@ pub async fn print_state(
     state: &State,
     bot: &Bot,
     chat: &Chat,
+    lang: Lang,
 ) -> anyhow::Result<()> {
     use State::*;
 
@@ -145,26 +236,31 @@ pub async fn print_state(
 
     match state {
         // edit profile
-        SetName(_) => set_name(bot, chat).await?,
-        SetGender(_) => set_gender(bot, chat).await?,
-        SetGenderFilter(_) => set_gender_filter(bot, chat).await?,
-        SetGraduationYear(_) => set_grade(bot, chat).await?,
-        SetSubjects(p) => set_subjects(bot, chat, p).await?,
+        SetName(_) => set_name(bot, chat, lang).await?,
+        SetGender(_) => set_gender(bot, chat, lang).await?,
+        SetGenderFilter(_) => set_gender_filter(bot, chat, lang).await?,
+        SetGraduationYear(_) => set_grade(bot, chat, lang).await?,
+        SetSubjects(p) => set_subjects(bot, chat, p, lang).await?,
         SetSubjectsFilter(p) => {
-            set_subjects_filter(bot, chat, p).await?;
+            set_subjects_filter(bot, chat, p, lang).await?;
         }
-        SetDatingPurpose(p) => set_dating_purpose(bot, chat, p).await?,
-        SetCity(_) => set_city(bot, chat).await?,
+        SetDatingPurpose(p) => set_dating_purpose(bot, chat, p, lang).await?,
+        SetCity(_) => set_city(bot, chat, lang).await?,
         SetLocationFilter(p) => {
-            set_location_filter(bot, chat, p).await?;
+            set_location_filter(bot, chat, p, lang).await?;
         }
-        SetAbout(_) => set_about(bot, chat).await?,
-        SetPhotos(_) => set_photos(bot, chat).await?,
+        SetAbout(_) => set_about(bot, chat, lang).await?,
+        SetPhotos(p) => set_photos(bot, chat, p.create_new, lang).await?,
+        ManagePhotos(_) => manage_photos(bot, chat, lang).await?,
         // others
-        LikeWithMessage { .. } => {
-            crate::datings::request_like_msg(bot, chat).await?;
+        LikeWithMessage { draft: None, .. } => {
+            crate::datings::request_like_msg(bot, chat, lang).await?;
+        }
+        LikeWithMessage { draft: Some(draft), .. } => {
+            crate::datings::preview_like(bot, chat, draft, lang).await?;
         }
-        Edit => edit_profile(bot, chat).await?,
+        Chatting { name, .. } => chatting(bot, chat, name, lang).await?,
+        Edit => edit_profile(bot, chat, lang).await?,
         // invalid states
         Start => {}
     };
@@ -176,18 +272,22 @@ async fn try_handle_message(
     bot: &Bot,
     state: &mut State,
     msg: &Message,
+    lang: Lang,
 ) -> anyhow::Result<()> {
     let chat = &msg.chat;
     let t = msg.text();
 
-    make_macros!(bot, msg, state, chat);
+    make_macros!(bot, msg, state, chat, lang);
 
     use State::*;
     match state {
         SetName(p) => {
             let t = t.ok_or(HandleError::NeedText)?;
             ensure!((3..=16).contains(&t.chars().count()), HandleError::Length);
-            p.name = Some(t.to_owned());
+            let name = moderate_text(db, bot, chat.id.0, Field::Name, t)
+                .await?
+                .ok_or(HandleError::Rejected)?;
+            p.name = Some(name);
             upd_print!(if p.create_new {
                 SetGender(mem::take(p))
             } else {
@@ -196,11 +296,11 @@ async fn try_handle_message(
         }
         SetGender(p) => {
             let t = t.ok_or(HandleError::NeedText)?;
-            let gender = match t {
-                text::GENDER_MALE => Gender::Male,
-                text::GENDER_FEMALE => Gender::Female,
-                _ => bail!(HandleError::WrongText),
-            };
+            let gender = text::choose(lang, t, [
+                (text::GENDER_MALE, Gender::Male),
+                (text::GENDER_FEMALE, Gender::Female),
+            ])
+            .ok_or(HandleError::WrongText)?;
             p.gender = Some(gender);
             upd_print!(if p.create_new {
                 SetGenderFilter(mem::take(p))
@@ -210,12 +310,12 @@ async fn try_handle_message(
         }
         SetGenderFilter(p) => {
             let t = t.ok_or(HandleError::NeedText)?;
-            let gender = match t {
-                text::GENDER_FILTER_MALE => Some(Gender::Male),
-                text::GENDER_FILTER_FEMALE => Some(Gender::Female),
-                text::GENDER_FILTER_ANY => None,
-                _ => bail!(HandleError::WrongText),
-            };
+            let gender = text::choose(lang, t, [
+                (text::GENDER_FILTER_MALE, Some(Gender::Male)),
+                (text::GENDER_FILTER_FEMALE, Some(Gender::Female)),
+                (text::GENDER_FILTER_ANY, None),
+            ])
+            .ok_or(HandleError::WrongText)?;
             p.gender_filter = Some(gender);
             upd_print!(if p.create_new {
                 SetGraduationYear(mem::take(p))
@@ -240,14 +340,14 @@ async fn try_handle_message(
             let t = t.ok_or(HandleError::NeedText)?;
 
             match t {
-                "Верно" if p.city.is_some() => {
+                _ if t == text::CITY_CORRECT.get(lang) && p.city.is_some() => {
                     upd_print!(SetLocationFilter(mem::take(p)));
                 }
-                "Не указывать" => {
+                _ if t == text::SKIP_CITY.get(lang) => {
                     p.city = Some(None);
                     p.location_filter = Some(LocationFilter::SameCountry);
 
-                    send!(text::NO_CITY, remove);
+                    send!(text::NO_CITY.get(lang), remove);
                     upd_print!(if p.create_new {
                         SetAbout(mem::take(p))
                     } else {
@@ -258,16 +358,20 @@ async fn try_handle_message(
                     if let Ok(city) = city.parse::<City>() {
                         p.city = Some(city.into());
                         send!(
-                            format!("Ваш город - {city}?"),
+                            text::CONFIRM_CITY.fill(lang, &[&city.name(lang)]),
                             markup[[
-                                KeyboardButton::new("Верно"),
-                                KeyboardButton::new("Не указывать"),
+                                KeyboardButton::new(
+                                    text::CITY_CORRECT.get(lang)
+                                ),
+                                KeyboardButton::new(text::SKIP_CITY.get(lang)),
                             ]]
                         );
                     } else {
                         send!(
-                            text::CANT_FIND_CITY,
-                            markup[[KeyboardButton::new("Не указывать")]]
+                            text::CANT_FIND_CITY.get(lang),
+                            markup[[KeyboardButton::new(
+                                text::SKIP_CITY.get(lang)
+                            )]]
                         );
                     }
                 }
@@ -283,18 +387,12 @@ async fn try_handle_message(
             //     _ => bail!(HandleError::WrongText),
             // };
 
-            // TODO: fix this mostrosity
-            let filter = if t == "Вся Россия" {
+            let filter = if t == text::LOCATION_COUNTRY.get(lang) {
                 LocationFilter::SameCountry
-            } else if cities::county_exists(
-                &t.chars()
-                    .rev()
-                    .skip(3)
-                    .collect::<Vec<_>>()
-                    .into_iter()
-                    .rev()
-                    .collect::<String>(),
-            ) {
+            } else if text::COUNTY
+                .strip(lang, t)
+                .is_some_and(cities::county_exists)
+            {
                 LocationFilter::SameCounty
             } else if cities::subject_exists(t) {
                 LocationFilter::SameSubject
@@ -317,9 +415,21 @@ async fn try_handle_message(
                 (1..=1024).contains(&t.chars().count()),
                 HandleError::Length
             );
-            p.about = Some(t.to_owned());
+            let entities = msg.entities().unwrap_or_default();
+            let about = crate::html::from_entities(t, entities);
+            let about = moderate_text(db, bot, chat.id.0, Field::About, &about)
+                .await?
+                .ok_or(HandleError::Rejected)?;
+            p.about = Some(about);
             // FIXME: HACK: create user before SetPhotos
             db.create_or_update_user(p.clone()).await?;
+            crate::spam::check(db, bot, chat.id.0).await?;
+            // New profiles are submitted when the photos are added
+            if p.create_new {
+                crate::review::hold(db, bot, chat.id.0).await?;
+            } else {
+                crate::review::submit(db, bot, chat.id.0, lang).await?;
+            }
             upd_print!(if p.create_new {
                 SetPhotos(mem::take(p))
             } else {
@@ -327,13 +437,16 @@ async fn try_handle_message(
             });
         }
         SetPhotos(p) => match t {
-            Some("Без фото") => {
+            // Photos added to the existing ones are saved instead
+            Some(t) if p.create_new && t == text::NO_PHOTOS.get(lang) => {
                 db.clean_images(chat.id.0).await?;
-                crate::datings::send_profile(bot, db, p.id).await?;
+                crate::datings::send_profile(bot, db, p.id, lang).await?;
+                crate::review::submit(db, bot, p.id, lang).await?;
                 upd_print!(Start);
             }
-            Some("Сохранить") => {
-                crate::datings::send_profile(bot, db, p.id).await?;
+            Some(t) if t == text::SAVE_PHOTOS.get(lang) => {
+                crate::datings::send_profile(bot, db, p.id, lang).await?;
+                submit_photos(db, bot, p, lang).await?;
                 upd_print!(Start);
             }
             _ => {
@@ -341,14 +454,17 @@ async fn try_handle_message(
                 // TODO: reset photos button
                 if p.photos_count == 0 {
                     db.clean_images(msg.chat.id.0).await?;
-                } else if p.photos_count >= 10 {
+                } else if p.photos_count >= bot.config().profile.max_media {
                     send!(
-                        "Невозможно добавить более 10 фото/видео",
-                        markup[[KeyboardButton::new("Сохранить")]]
+                        text::TOO_MANY_PHOTOS.get(lang),
+                        markup[[KeyboardButton::new(
+                            text::SAVE_PHOTOS.get(lang)
+                        )]]
                     );
                     return Ok(());
                 };
 
+                // Photos are compared with others when they're archived
                 if let Some([.., photo]) = msg.photo() {
                     let file = bot.get_file(&photo.file.id).await?;
                     db.create_image(chat.id.0, file.meta.id, ImageKind::Image)
@@ -357,53 +473,140 @@ async fn try_handle_message(
                     let file = bot.get_file(&video.file.id).await?;
                     db.create_image(chat.id.0, file.meta.id, ImageKind::Video)
                         .await?;
+                } else if let Some(voice) = msg.voice() {
+                    ensure!(
+                        voice.duration <= crate::datings::MAX_MEDIA_DURATION,
+                        HandleError::Length
+                    );
+                    let file = bot.get_file(&voice.file.id).await?;
+                    db.create_image(chat.id.0, file.meta.id, ImageKind::Voice)
+                        .await?;
+                } else if let Some(note) = msg.video_note() {
+                    ensure!(
+                        note.duration <= crate::datings::MAX_MEDIA_DURATION,
+                        HandleError::Length
+                    );
+                    let file = bot.get_file(&note.file.id).await?;
+                    db.create_image(
+                        chat.id.0,
+                        file.meta.id,
+                        ImageKind::VideoNote,
+                    )
+                    .await?;
                 } else {
                     bail!(HandleError::WrongText);
                 };
+                change_photos(db, bot, p).await?;
 
                 p.photos_count += 1;
 
                 send!(
-                    format!(
-                        "Добавлено {}/10 фото/видео. Добавить ещё?",
-                        p.photos_count
-                    ),
-                    markup[[KeyboardButton::new("Сохранить")]]
+                    text::PHOTOS_ADDED.fill(lang, &[&p.photos_count]),
+                    markup[[KeyboardButton::new(text::SAVE_PHOTOS.get(lang))]]
                 );
             }
         },
+        ManagePhotos(p) => match t {
+            Some(t) if t == text::ADD_PHOTOS.get(lang) => {
+                p.photos_count =
+                    db.get_user_images(chat.id.0).await?.len().try_into()?;
+                upd_print!(SetPhotos(mem::take(p)));
+            }
+            Some(t) if t == text::PHOTOS_DONE.get(lang) => {
+                crate::datings::send_profile(bot, db, p.id, lang).await?;
+                submit_photos(db, bot, p, lang).await?;
+                upd_print!(Start);
+            }
+            _ => bail!(HandleError::WrongText),
+        },
         // TODO: confirm profile change State
         Start => {
-            bot.send_message(
-                chat.id,
-                crate::Command::descriptions().to_string(),
-            )
-            .await?;
+            send!(text::HELP.get(lang));
         }
-        LikeWithMessage { dating } => {
-            let t = t.ok_or(HandleError::NeedText)?;
-
-            let msg_to_send = if t == "Отмена"
-                || t.chars().next().context("empty string")? == '/'
-            {
-                db.set_dating_initiator_reaction(dating.id, false).await?;
-                "Отправка лайка отменена"
-            } else {
-                db.set_dating_initiator_reaction(dating.id, true).await?;
-                crate::datings::send_like(db, bot, dating, Some(t.to_owned()))
-                    .await?;
-                "Лайк отправлен!"
+        LikeWithMessage { dating, draft } => {
+            let choice = t.and_then(|t| {
+                // Commands which aren't handled cancel the like
+                if t.starts_with('/') {
+                    return Some(false);
+                }
+                text::choose(lang, t, [
+                    (text::CANCEL, false),
+                    (text::SEND, true),
+                ])
+            });
+            let msg_to_send = match (choice, draft.take()) {
+                (Some(false), _) => {
+                    db.set_dating_initiator_reaction(dating, false).await?;
+                    text::LIKE_CANCELLED
+                }
+                (Some(true), Some(d)) => {
+                    db.like(dating, d.message, d.attachment).await?;
+                    bot.metrics().likes.inc();
+                    crate::spam::check(db, bot, chat.id.0).await?;
+                    text::LIKE_SENT
+                }
+                _ => {
+                    let Some(mut draft) = like_draft(msg, lang)? else {
+                        send!(text::LIKE_MEDIA_TOO_LARGE.get(lang));
+                        return Ok(());
+                    };
+                    if let Some(message) = &draft.message {
+                        // Contacts may be masked below, the spam check
+                        // counts them by these events
+                        if crate::moderation::has_contact(message) {
+                            db.log_event(
+                                chat.id.0,
+                                EventKind::LikeContact,
+                                Some(dating.id),
+                                None,
+                            )
+                            .await?;
+                        }
+                        let field = Field::LikeMessage;
+                        draft.message = Some(
+                            moderate_text(db, bot, chat.id.0, field, message)
+                                .await?
+                                .ok_or(HandleError::Rejected)?,
+                        );
+                    }
+                    upd_print!(LikeWithMessage {
+                        dating: dating.clone(),
+                        draft: Some(draft),
+                    });
+                    return Ok(());
+                }
             };
 
-            send!(msg_to_send, remove);
+            send!(msg_to_send.get(lang), remove);
             crate::datings::send_recommendation(
                 bot,
                 db,
                 ChatId(dating.initiator_id),
+                lang,
             )
             .await?;
             upd_print!(Start);
         }
+        Chatting { dating_id, partner, .. } => {
+            if t.is_some_and(|t| t.starts_with('/')) {
+                bail!(HandleError::WrongText);
+            }
+            let supported = msg.text().is_some()
+                || msg.photo().is_some()
+                || msg.video().is_some()
+                || msg.voice().is_some()
+                || msg.video_note().is_some()
+                || msg.sticker().is_some()
+                || msg.animation().is_some();
+            if !supported {
+                send!(text::CHAT_UNSUPPORTED.get(lang));
+                return Ok(());
+            }
+            crate::relay::relay_message(
+                bot, db, *dating_id, *partner, msg, lang,
+            )
+            .await?;
+        }
 
         // explicit ignore (for now)
         SetSubjects(_) | SetSubjectsFilter(_) | SetDatingPurpose(_) | Edit => {}
@@ -411,6 +614,77 @@ async fn try_handle_message(
     Ok(())
 }
 
+/// Hide the edited profile when its photos are changed for the first time,
+/// it's submitted by [`submit_photos`]
+async fn change_photos(
+    db: &Database,
+    bot: &Bot,
+    p: &mut EditProfile,
+) -> anyhow::Result<()> {
+    if !p.create_new && !p.photos_changed {
+        crate::review::hold(db, bot, p.id).await?;
+    }
+    p.photos_changed = true;
+    Ok(())
+}
+
+/// Submit the profile for review when the user leaves the photos, unless
+/// nothing was changed
+pub async fn submit_photos(
+    db: &Database,
+    bot: &Bot,
+    p: &EditProfile,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    if p.create_new || p.photos_changed {
+        crate::review::submit(db, bot, p.id, lang).await?;
+    }
+    Ok(())
+}
+
+/// Make a like message from any supported message, `None` if it's too large
+fn like_draft(msg: &Message, lang: Lang) -> anyhow::Result<Option<LikeDraft>> {
+    use crate::datings::{
+        MAX_LIKE_FILE_SIZE, MAX_LIKE_MESSAGE_LEN, MAX_MEDIA_DURATION,
+    };
+
+    let text = msg.text().or_else(|| msg.caption());
+    if let Some(t) = text {
+        // Labels of the buttons, e.g. Send without a draft, aren't messages
+        let label =
+            text::choose(lang, t, [(text::SEND, ()), (text::CANCEL, ())]);
+        ensure!(label.is_none(), HandleError::WrongText);
+        ensure!(t.chars().count() <= MAX_LIKE_MESSAGE_LEN, HandleError::Length);
+    }
+
+    let (attachment, size, duration) = if let Some([.., photo]) = msg.photo() {
+        let attachment = (LikeAttachmentKind::Photo, photo.file.id.clone());
+        (Some(attachment), photo.file.size, 0)
+    } else if let Some(voice) = msg.voice() {
+        let attachment = (LikeAttachmentKind::Voice, voice.file.id.clone());
+        (Some(attachment), voice.file.size, voice.duration)
+    } else if let Some(note) = msg.video_note() {
+        let attachment = (LikeAttachmentKind::VideoNote, note.file.id.clone());
+        (Some(attachment), note.file.size, note.duration)
+    } else if let Some(sticker) = msg.sticker() {
+        let attachment = (LikeAttachmentKind::Sticker, sticker.file.id.clone());
+        (Some(attachment), sticker.file.size, 0)
+    } else if msg.text().is_some() {
+        (None, 0, 0)
+    } else {
+        bail!(HandleError::WrongText);
+    };
+
+    if size > MAX_LIKE_FILE_SIZE || duration > MAX_MEDIA_DURATION {
+        return Ok(None);
+    }
+
+    let entities =
+        msg.entities().or_else(|| msg.caption_entities()).unwrap_or_default();
+    let message = text.map(|t| crate::html::from_entities(t, entities));
+    Ok(Some(LikeDraft { message, attachment }))
+}
+
 async fn try_handle_callback(
     db: &Database,
     bot: &Bot,
@@ -418,17 +692,28 @@ async fn try_handle_callback(
     msg: &Message,
     data: &str,
     q: &CallbackQuery,
+    lang: Lang,
 ) -> anyhow::Result<()> {
     let chat = &msg.chat;
-    make_macros!(bot, msg, state, chat);
+    make_macros!(bot, msg, state, chat, lang);
 
     let callback: Callback = data.parse()?;
 
     use State::*;
 
-    if matches!(callback, Callback::Dating { .. }) && *state != Start {
+    let alert = match callback {
+        Callback::Dating { .. } if *state != Start => Some(text::LEAVE_EDITING),
+        Callback::Chat { .. } if !matches!(state, Start | Chatting { .. }) => {
+            Some(text::LEAVE_EDITING)
+        }
+        Callback::Photo { .. } if !matches!(state, ManagePhotos(_)) => {
+            Some(text::OPEN_PHOTOS_FROM_EDIT)
+        }
+        _ => None,
+    };
+    if let Some(alert) = alert {
         bot.answer_callback_query(&q.id)
-            .text("Сначала выйдите из режима редактирования!")
+            .text(alert.get(lang))
             .show_alert(true)
             .await?;
         return Ok(());
@@ -452,11 +737,10 @@ async fn try_handle_callback(
                     remove_buttons!();
 
                     let subjects_str = if current_subjects.is_empty() {
-                        "Вы ничего не ботаете.".to_owned()
+                        text::SUBJECTS_USER_NONE.get(lang).into_owned()
                     } else {
-                        format!(
-                            "Предметы, которые вы ботаете: {current_subjects}.",
-                        )
+                        text::SUBJECTS_USER_SELECTED
+                            .fill(lang, &[&current_subjects.names(lang)])
                     };
                     bot.edit_message_text(msg.chat.id, msg.id, subjects_str)
                         .await?;
@@ -471,6 +755,7 @@ async fn try_handle_callback(
                         .reply_markup(utils::make_subjects_keyboard(
                             new_subjects,
                             &utils::SubjectsKeyboardType::User,
+                            lang,
                         ))
                         .await?;
 
@@ -495,12 +780,10 @@ async fn try_handle_callback(
                     remove_buttons!();
 
                     let subjects_filter_str = if current_filter.is_empty() {
-                        "Не важно, что ботает другой человек.".to_owned()
+                        text::SUBJECTS_PARTNER_NONE.get(lang).into_owned()
                     } else {
-                        format!(
-                            "Предметы, хотя бы один из которых должен ботать \
-                             тот, кого вы ищете: {current_filter}.",
-                        )
+                        text::SUBJECTS_PARTNER_SELECTED
+                            .fill(lang, &[&current_filter.names(lang)])
                     };
                     bot.edit_message_text(
                         msg.chat.id,
@@ -520,6 +803,7 @@ async fn try_handle_callback(
                         .reply_markup(utils::make_subjects_keyboard(
                             changed_subjects_filter,
                             &utils::SubjectsKeyboardType::User,
+                            lang,
                         ))
                         .await?;
 
@@ -549,7 +833,8 @@ async fn try_handle_callback(
                     bot.edit_message_text(
                         msg.chat.id,
                         msg.id,
-                        format!("Вас интересует: {current_purpose}.",),
+                        text::PURPOSE_SELECTED
+                            .fill(lang, &[&current_purpose.names(lang)]),
                     )
                     .await?;
 
@@ -566,6 +851,7 @@ async fn try_handle_callback(
                     bot.edit_message_reply_markup(msg.chat.id, msg.id)
                         .reply_markup(utils::make_dating_purpose_keyboard(
                             new_purpose,
+                            lang,
                         ))
                         .await?;
 
@@ -573,6 +859,68 @@ async fn try_handle_callback(
                 }
             }
         }
+        ManagePhotos(p) => {
+            let Callback::Photo { image_id, action } = callback else {
+                bail!("wrong callback type")
+            };
+
+            let mut images: Vec<_> = db
+                .get_user_images(chat.id.0)
+                .await?
+                .into_iter()
+                .map(|i| i.id)
+                .collect();
+            let index = images
+                .iter()
+                .position(|&i| i == image_id)
+                .context("image not found")?;
+
+            let position = match action {
+                PhotoAction::Delete => {
+                    db.delete_image(chat.id.0, image_id).await?;
+                    images.remove(index);
+                    bot.delete_message(chat.id, msg.id).await?;
+                    p.photo_messages.retain(|&m| m != msg.id);
+                    None
+                }
+                PhotoAction::Up => {
+                    let new = index.saturating_sub(1);
+                    images.swap(index, new);
+                    Some(new)
+                }
+                PhotoAction::Down => {
+                    let new = (index + 1).min(images.len() - 1);
+                    images.swap(index, new);
+                    Some(new)
+                }
+                PhotoAction::Cover => {
+                    images.remove(index);
+                    images.insert(0, image_id);
+                    Some(0)
+                }
+            };
+            db.set_images_order(chat.id.0, images).await?;
+            change_photos(db, bot, p).await?;
+
+            // Send the manager again to show the photos in the new order
+            if position.is_some_and(|p| p != index) {
+                for message in mem::take(&mut p.photo_messages) {
+                    if let Err(e) = bot.delete_message(chat.id, message).await {
+                        tracing::warn!("can't delete photo manager: {e}");
+                    }
+                }
+                p.photo_messages = crate::datings::send_photo_manager(
+                    bot, db, chat.id.0, lang,
+                )
+                .await?;
+            }
+
+            let notice = position.map_or_else(
+                || text::PHOTO_DELETED.get(lang).into_owned(),
+                |p| text::PHOTO_MOVED.fill(lang, &[&(p + 1)]),
+            );
+            bot.answer_callback_query(&q.id).text(notice).await?;
+        }
         Edit => {
             // TODO: edit should work in Start state
             // ensure!(code == Callback::Edit, HandleError::WrongCode);
@@ -580,16 +928,22 @@ async fn try_handle_callback(
             // FIXME: check if user exists
             let user =
                 db.get_user(msg.chat.id.0).await?.context("user not found")?;
-            let p = EditProfile::from_model(user); // FIXME: why?
+            let mut p = EditProfile::from_model(user); // FIXME: why?
 
             remove_buttons!();
-            let state = match data {
-                "Имя" => SetName(p),
-                "Предметы" => SetSubjects(p),
-                "О себе" => SetAbout(p),
-                "Город" => SetCity(p),
-                "Фото" => SetPhotos(p),
-                "Отмена" => Start,
+            let state = match data.strip_prefix('e').unwrap_or(data) {
+                "name" => SetName(p),
+                "subjects" => SetSubjects(p),
+                "about" => SetAbout(p),
+                "city" => SetCity(p),
+                "photos" => {
+                    p.photo_messages = crate::datings::send_photo_manager(
+                        bot, db, chat.id.0, lang,
+                    )
+                    .await?;
+                    ManagePhotos(p)
+                }
+                "cancel" => Start,
                 _ => bail!("unknown edit data"),
             };
             upd_print!(state);
@@ -606,40 +960,61 @@ async fn try_handle_callback(
                                 dating.initiator_reaction.is_none(),
                                 "user abuses dislikes"
                             );
-                            db.set_dating_initiator_reaction(dating_id, false)
+                            db.set_dating_initiator_reaction(&dating, false)
                                 .await?;
                             crate::datings::send_recommendation(
                                 bot,
                                 db,
                                 ChatId(dating.initiator_id),
+                                lang,
                             )
                             .await?;
                         }
                         RateCode::LikeWithMsg => {
-                            remove_buttons!();
                             ensure!(
                                 dating.initiator_reaction.is_none(),
                                 "user abuses msglikes"
                             );
-                            upd_print!(State::LikeWithMessage { dating });
+                            // Buttons are kept to like when the quota resets
+                            if let Some(text) = crate::limits::like_limit(
+                                db, bot, chat.id.0, true, lang,
+                            )
+                            .await?
+                            {
+                                send!(text);
+                                return Ok(());
+                            }
+                            remove_buttons!();
+                            upd_print!(State::LikeWithMessage {
+                                dating,
+                                draft: None
+                            });
                         }
                         RateCode::Like => {
-                            remove_buttons!();
                             ensure!(
                                 dating.initiator_reaction.is_none(),
                                 "user abuses likes"
                             );
+                            if let Some(text) = crate::limits::like_limit(
+                                db, bot, chat.id.0, false, lang,
+                            )
+                            .await?
+                            {
+                                send!(text);
+                                return Ok(());
+                            }
+                            remove_buttons!();
 
-                            db.set_dating_initiator_reaction(dating_id, true)
-                                .await?;
+                            db.like(&dating, None, None).await?;
+                            bot.metrics().likes.inc();
+                            crate::spam::check(db, bot, chat.id.0).await?;
                             crate::datings::send_recommendation(
                                 bot,
                                 db,
                                 ChatId(dating.initiator_id),
+                                lang,
                             )
                             .await?;
-                            crate::datings::send_like(db, bot, &dating, None)
-                                .await?;
                         }
                         RateCode::ResponseDislike => {
                             remove_buttons!();
@@ -647,7 +1022,8 @@ async fn try_handle_callback(
                                 dating.partner_reaction.is_none(),
                                 "partner abuses dislikes"
                             );
-                            db.set_dating_partner_reaction(dating_id, false)
+                            ensure!(!dating.expired, "like is expired");
+                            db.set_dating_partner_reaction(&dating, false)
                                 .await?;
                         }
                         RateCode::ResponseLike => {
@@ -655,23 +1031,17 @@ async fn try_handle_callback(
                                 dating.partner_reaction.is_none(),
                                 "partner abuses likes"
                             );
-
-                            let initiator = db
-                                .get_user(dating.initiator_id)
-                                .await?
-                                .context("dating initiator not found")?;
+                            ensure!(!dating.expired, "like is expired");
 
                             let markup = InlineKeyboardMarkup::new([[
-                                InlineKeyboardButton::url(
-                                    "Открыть чат",
-                                    crate::utils::user_url(bot, initiator.id)
-                                        .await?
-                                        .context("can't get url")?,
+                                InlineKeyboardButton::callback(
+                                    text::START_CHAT.get(lang),
+                                    Callback::Chat { dating_id }.to_string(),
                                 ),
                             ]]);
 
-                            crate::datings::mutual_like(bot, db, &dating)
-                                .await?;
+                            db.mutual_like(&dating).await?;
+                            bot.metrics().mutual_likes.inc();
                             // TODO: check if error works
                             bot.edit_message_reply_markup(msg.chat.id, msg.id)
                                 .reply_markup(markup)
@@ -683,14 +1053,25 @@ async fn try_handle_callback(
                         }
                     }
                 }
+                Callback::Chat { dating_id } => {
+                    upd_print!(
+                        crate::relay::enter(db, chat.id.0, dating_id).await?
+                    );
+                }
                 Callback::CreateProfile => {
-                    crate::start_profile_creation(state, msg, bot).await?;
+                    crate::start_profile_creation(state, msg, bot, lang)
+                        .await?;
                 }
                 Callback::FindPartner => {
                     remove_buttons!();
                     // TODO: refactor this
-                    crate::datings::send_recommendation(bot, db, msg.chat.id)
-                        .await?;
+                    crate::datings::send_recommendation(
+                        bot,
+                        db,
+                        msg.chat.id,
+                        lang,
+                    )
+                    .await?;
                 }
                 _ => bail!("wrong callback type"),
             }
@@ -705,6 +1086,12 @@ async fn try_handle_callback(
         | SetAbout(_)
         | SetPhotos(_)
         | LikeWithMessage { .. } => {}
+        Chatting { .. } => {
+            let Callback::Chat { dating_id } = callback else {
+                bail!("wrong callback type")
+            };
+            upd_print!(crate::relay::enter(db, chat.id.0, dating_id).await?);
+        }
     }
 
     Ok(())
@@ -0,0 +1,138 @@
+//! Building messages for Telegram's HTML parse mode
+
+use teloxide::types::{MessageEntity, MessageEntityKind};
+pub use teloxide::utils::html::{bold, escape};
+
+/// Convert user's text to HTML keeping its formatting
+pub fn from_entities(text: &str, entities: &[MessageEntity]) -> String {
+    let mut entities: Vec<_> =
+        entities.iter().filter_map(|e| Some((e, tags(&e.kind)?))).collect();
+    // Outer entities go first, so they're closed last
+    entities.sort_by_key(|(e, _)| (e.offset, std::cmp::Reverse(e.length)));
+    let mut entities = entities.into_iter().peekable();
+
+    let mut result = String::with_capacity(text.len());
+    let mut open: Vec<(usize, &str)> = Vec::new();
+    // Offsets of the entities are measured in UTF-16 code units
+    let mut offset = 0;
+    for c in text.chars() {
+        close_tags(&mut result, &mut open, offset);
+        while let Some((e, (start, end))) =
+            entities.next_if(|(e, _)| e.offset <= offset)
+        {
+            result.push_str(&start);
+            open.push((e.offset + e.length, end));
+        }
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            c => result.push(c),
+        }
+        offset += c.len_utf16();
+    }
+    close_tags(&mut result, &mut open, usize::MAX);
+    result
+}
+
+/// Close tags of the entities ending before `offset`
+fn close_tags(
+    result: &mut String,
+    open: &mut Vec<(usize, &str)>,
+    offset: usize,
+) {
+    while let Some(&(end, tag)) = open.last() {
+        if end > offset {
+            break;
+        }
+        result.push_str(tag);
+        open.pop();
+    }
+}
+
+/// Opening and closing tags of the entity, `None` for entities which are
+/// detected by Telegram or can't be sent by bots
+fn tags(kind: &MessageEntityKind) -> Option<(String, &'static str)> {
+    use MessageEntityKind::*;
+
+    Some(match kind {
+        Bold => ("<b>".to_owned(), "</b>"),
+        Italic => ("<i>".to_owned(), "</i>"),
+        Underline => ("<u>".to_owned(), "</u>"),
+        Strikethrough => ("<s>".to_owned(), "</s>"),
+        Spoiler => ("<tg-spoiler>".to_owned(), "</tg-spoiler>"),
+        Code => ("<code>".to_owned(), "</code>"),
+        Pre { language: Some(language) } => (
+            format!("<pre><code class=\"language-{}\">", escape(language)),
+            "</code></pre>",
+        ),
+        Pre { language: None } => ("<pre>".to_owned(), "</pre>"),
+        TextLink { url } => {
+            (format!("<a href=\"{}\">", escape(url.as_str())), "</a>")
+        }
+        TextMention { user } => {
+            (format!("<a href=\"tg://user?id={}\">", user.id), "</a>")
+        }
+        Mention
+        | Hashtag
+        | Cashtag
+        | BotCommand
+        | Url
+        | Email
+        | PhoneNumber
+        | CustomEmoji { .. } => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use teloxide::types::MessageEntity;
+
+    use super::*;
+
+    #[test]
+    fn escaping() {
+        assert_eq!(
+            from_entities("a < b & c > d", &[]),
+            "a &lt; b &amp; c &gt; d"
+        );
+    }
+
+    #[test]
+    fn nested_entities() {
+        let entities =
+            [MessageEntity::italic(5, 6), MessageEntity::bold(0, 11)];
+        assert_eq!(
+            from_entities("bold italic", &entities),
+            "<b>bold <i>italic</i></b>"
+        );
+        let entities = [MessageEntity::bold(0, 4), MessageEntity::code(5, 3)];
+        assert_eq!(
+            from_entities("bold <a> end", &entities),
+            "<b>bold</b> <code>&lt;a&gt;</code> end"
+        );
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        // The emoji takes two UTF-16 code units
+        let entities = [MessageEntity::bold(3, 2)];
+        assert_eq!(from_entities("😀 hi", &entities), "😀 <b>hi</b>");
+        let entities = [MessageEntity::italic(0, 2)];
+        assert_eq!(from_entities("😀 hi", &entities), "<i>😀</i> hi");
+        let entities = [MessageEntity::bold(0, 4)];
+        assert_eq!(from_entities("😀😀!", &entities), "<b>😀😀</b>!");
+    }
+
+    #[test]
+    fn links_and_ignored_entities() {
+        let url = "https://example.com/?a=1&b=2".parse().unwrap();
+        let entities = [MessageEntity::text_link(url, 0, 4)];
+        assert_eq!(
+            from_entities("link", &entities),
+            "<a href=\"https://example.com/?a=1&amp;b=2\">link</a>"
+        );
+        let entities = [MessageEntity::new(MessageEntityKind::Url, 0, 5)];
+        assert_eq!(from_entities("a.com", &entities), "a.com");
+    }
+}
@@ -0,0 +1,256 @@
+//! Daily quotas of likes and the rate limit of updates
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+    time::Instant,
+};
+
+use chrono::Duration;
+use entities::sea_orm_active_enums::FlagReason;
+use serde::Deserialize;
+use teloxide::prelude::*;
+
+use crate::{
+    db::Database,
+    text::{self, Lang},
+    Bot,
+};
+
+/// Likes are counted during this number of hours
+const QUOTA_HOURS: u32 = 24;
+/// Reactions counted as likes
+const LIKE_KINDS: &[&str] = &["like", "like_with_message"];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsSettings {
+    /// Likes including ones with a message, 0 disables the quota
+    pub likes_per_day: u32,
+    /// Likes with a message or an attachment, 0 disables the quota
+    pub message_likes_per_day: u32,
+    /// Sustained rate of messages and button presses, 0 disables the limit
+    pub updates_per_minute: u32,
+    /// Updates which can be sent at once after a pause
+    pub burst: u32,
+    /// Users who used up the quota of likes and liked at least this share
+    /// of recommendations are flagged
+    pub suspicious_like_ratio: f64,
+}
+
+impl Default for LimitsSettings {
+    fn default() -> Self {
+        Self {
+            likes_per_day: 50,
+            message_likes_per_day: 10,
+            updates_per_minute: 30,
+            burst: 10,
+            suspicious_like_ratio: 0.9,
+        }
+    }
+}
+
+/// Message for the user who can't like now, `None` if the like is allowed
+pub async fn like_limit(
+    db: &Database,
+    bot: &Bot,
+    user: i64,
+    with_message: bool,
+    lang: Lang,
+) -> anyhow::Result<Option<String>> {
+    let settings = &bot.config().limits;
+    if let Some(reset) =
+        quota_reset(db, user, LIKE_KINDS, settings.likes_per_day).await?
+    {
+        let reactions = db.get_recent_reactions(user, QUOTA_HOURS).await?;
+        let likes = reactions
+            .iter()
+            .filter(|(_, kind)| {
+                kind.as_deref().is_some_and(|k| LIKE_KINDS.contains(&k))
+            })
+            .count();
+        let rated = reactions
+            .iter()
+            .filter(|(_, kind)| {
+                matches!(
+                    kind.as_deref(),
+                    Some("like" | "like_with_message" | "dislike")
+                )
+            })
+            .count();
+        // Liking almost everyone is what spammers do
+        if like_ratio(likes, rated) >= settings.suspicious_like_ratio {
+            flag(
+                db,
+                user,
+                format!("лайкнул {likes} из {rated} анкет за сутки"),
+            )
+            .await?;
+        }
+        return Ok(Some(fill_reset(text::LIKES_LIMIT, reset, lang)));
+    }
+
+    if with_message {
+        if let Some(reset) = quota_reset(
+            db,
+            user,
+            &["like_with_message"],
+            settings.message_likes_per_day,
+        )
+        .await?
+        {
+            return Ok(Some(fill_reset(
+                text::MESSAGE_LIKES_LIMIT,
+                reset,
+                lang,
+            )));
+        }
+    }
+    Ok(None)
+}
+
+/// Time until one of the reactions leaves the quota period, `None` if
+/// fewer than `quota` of them are in it
+async fn quota_reset(
+    db: &Database,
+    user: i64,
+    kinds: &[&str],
+    quota: u32,
+) -> anyhow::Result<Option<Duration>> {
+    if quota == 0 {
+        return Ok(None);
+    }
+    db.get_quota_reset(user, kinds, quota, QUOTA_HOURS).await
+}
+
+fn like_ratio(likes: usize, rated: usize) -> f64 {
+    let count = |n: usize| f64::from(u32::try_from(n).unwrap_or(u32::MAX));
+    if rated == 0 {
+        return 0.0;
+    }
+    count(likes) / count(rated)
+}
+
+fn fill_reset(text: text::Text, reset: Duration, lang: Lang) -> String {
+    // Round up, so the user isn't told to wait 0 minutes
+    let minutes = (reset.num_seconds() + 59) / 60;
+    text.fill(lang, &[&(minutes / 60), &(minutes % 60)])
+}
+
+/// Add the user to the moderation queue unless they're already there
+async fn flag(db: &Database, user: i64, details: String) -> anyhow::Result<()> {
+    if !db.has_unresolved_flag(user, FlagReason::SuspiciousActivity).await? {
+        tracing::info!("user {user} is flagged: {details}");
+        db.create_flag(user, FlagReason::SuspiciousActivity, details).await?;
+    }
+    Ok(())
+}
+
+/// Token bucket of every user
+pub struct RateLimiter {
+    /// Tokens added per second
+    rate: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<i64, Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    /// Whether the user was told about the limit since the last allowed
+    /// update
+    warned: bool,
+}
+
+enum Verdict {
+    Allowed,
+    Limited { first: bool },
+}
+
+/// Buckets are cleaned when there are more of them
+const MAX_BUCKETS: usize = 10_000;
+
+impl RateLimiter {
+    pub fn new(settings: &LimitsSettings) -> Self {
+        Self {
+            rate: f64::from(settings.updates_per_minute) / 60.0,
+            burst: f64::from(settings.burst.max(1)),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn take(&self, user: i64) -> Verdict {
+        if self.rate <= 0.0 {
+            return Verdict::Allowed;
+        }
+        let now = Instant::now();
+        let mut buckets =
+            self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        if buckets.len() > MAX_BUCKETS {
+            // Full buckets are the same as missing ones
+            let full_after = self.burst / self.rate;
+            buckets.retain(|_, b| {
+                now.duration_since(b.updated).as_secs_f64() < full_after
+            });
+        }
+
+        let verdict = buckets
+            .entry(user)
+            .or_insert(Bucket {
+                tokens: self.burst,
+                updated: now,
+                warned: false,
+            })
+            .take(now, self.rate, self.burst);
+        drop(buckets);
+        verdict
+    }
+}
+
+impl Bucket {
+    fn take(&mut self, now: Instant, rate: f64, burst: f64) -> Verdict {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = elapsed.mul_add(rate, self.tokens).min(burst);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            self.warned = false;
+            Verdict::Allowed
+        } else {
+            let first = !self.warned;
+            self.warned = true;
+            Verdict::Limited { first }
+        }
+    }
+}
+
+/// Filter of updates of users who send them too often, it tells the user
+/// about the limit once and flags them
+pub async fn allow(
+    limiter: Arc<RateLimiter>,
+    db: Arc<Database>,
+    bot: Bot,
+    upd: Update,
+    lang: Lang,
+) -> bool {
+    let Some(user) = upd.user() else { return true };
+    let user = ChatId::from(user.id);
+    match limiter.take(user.0) {
+        Verdict::Allowed => true,
+        Verdict::Limited { first: false } => false,
+        Verdict::Limited { first: true } => {
+            if let Err(e) =
+                bot.send_message(user, text::TOO_MANY_REQUESTS.get(lang)).await
+            {
+                tracing::warn!("can't warn about the rate limit: {e}");
+            }
+            let details = "слишком часто отправляет сообщения".to_owned();
+            if let Err(e) = flag(&db, user.0, details).await {
+                sentry_anyhow::capture_anyhow(
+                    &e.context("error while flagging rate limited user"),
+                );
+            }
+            false
+        }
+    }
+}
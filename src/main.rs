@@ -11,34 +11,64 @@
     clippy::cast_possible_truncation
 )]
 
-use std::{str::FromStr, sync::Arc};
+use std::{
+    str::FromStr,
+    sync::{atomic::Ordering, Arc},
+};
 
+use anyhow::Context;
 use db::Database;
-use entities::sea_orm_active_enums::{Gender, LocationFilter};
+use entities::sea_orm_active_enums::{EventKind, Gender, LocationFilter};
 use sentry_tracing::EventFilter;
 use teloxide::{
-    adaptors::{throttle::Limits, Throttle},
     dispatching::dialogue::InMemStorage,
     error_handlers::ErrorHandler,
+    net,
     prelude::*,
-    types::{InlineKeyboardButton, InlineKeyboardMarkup},
+    types::{
+        InlineKeyboardButton, InlineKeyboardMarkup, KeyboardRemove, MessageId,
+    },
     utils::command::BotCommands,
     RequestError,
 };
 use tracing::*;
 use tracing_subscriber::prelude::*;
 
+mod admin;
+mod bot;
 mod callbacks;
+#[cfg(feature = "card")]
+mod card;
 mod cities;
+mod config;
 mod datings;
 mod db;
+mod experiments;
+mod funnel;
 mod handle;
+mod html;
+mod limits;
+mod metrics;
+mod moderation;
+mod outbox;
+mod recommendations;
+mod relay;
 mod request;
+mod review;
+mod scheduler;
+mod spam;
+mod storage;
 mod text;
 mod types;
 mod utils;
-
-type Bot = Throttle<teloxide::Bot>;
+#[cfg(feature = "webhook")]
+mod webhook;
+
+use admin::{AdminCommand, TextDrafts};
+use bot::Bot;
+use config::Config;
+use metrics::Metrics;
+use text::Lang;
 type MyDialogue = Dialogue<State, InMemStorage<State>>;
 
 #[derive(thiserror::Error, Debug)]
@@ -73,12 +103,13 @@ impl ErrorHandler<anyhow::Error> for AppErrorHandler {
 async fn main() -> anyhow::Result<()> {
     std::env::set_var("RUST_BACKTRACE", "1"); // FIXME: HACK
 
+    let config = Arc::new(Config::load()?);
+
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::fmt::layer().with_filter(
                 tracing_subscriber::filter::LevelFilter::from_str(
-                    &std::env::var("RUST_LOG")
-                        .unwrap_or_else(|_| String::from("info")),
+                    &config.log_level,
                 )
                 .unwrap_or(tracing_subscriber::filter::LevelFilter::INFO),
             ),
@@ -91,33 +122,53 @@ async fn main() -> anyhow::Result<()> {
         .try_init()
         .unwrap();
 
-    let _sentry_guard = match std::env::var("SENTRY_DSN") {
-        Ok(d) => {
-            let guard = sentry::init((d, sentry::ClientOptions {
-                release: sentry::release_name!(),
-                default_integrations: true,
-                attach_stacktrace: true,
-                traces_sample_rate: 1.0,
-                enable_profiling: true,
-                profiles_sample_rate: 1.0,
-                ..Default::default()
-            }));
-            Some(guard)
-        }
-        Err(e) => {
-            warn!("can't get SENTRY_DSN: {:?}", e);
-            None
-        }
-    };
+    if config.sentry_dsn.is_none() {
+        warn!("sentry_dsn is not set");
+    }
+    let _sentry_guard = config.sentry_dsn.as_deref().map(|d| {
+        sentry::init((d, sentry::ClientOptions {
+            release: sentry::release_name!(),
+            default_integrations: true,
+            attach_stacktrace: true,
+            traces_sample_rate: 1.0,
+            enable_profiling: true,
+            profiles_sample_rate: 1.0,
+            ..Default::default()
+        }))
+    });
+
+    let database = Arc::new(db::Database::new(&config).await?);
+    admin::load_texts(&database).await?;
 
     tracing::info!("Starting bot...");
-    let bot = teloxide::Bot::from_env().throttle(Limits {
-        messages_per_sec_chat: 2,
-        messages_per_min_chat: 120,
-        ..Default::default()
-    });
+    let metrics = Arc::new(Metrics::new()?);
+    if let Some(settings) = &config.monitoring {
+        metrics::serve(settings, metrics.clone(), database.clone())?;
+    }
+
+    let bot = Bot::new(
+        teloxide::Bot::with_client(&config.token, net::client_from_env())
+            .throttle((&config.throttle).into()),
+        database.clone(),
+        Arc::new(storage::Storage::new(&config.storage)?),
+        config.clone(),
+        metrics.clone(),
+    );
+
+    // The menu is a convenience, the bot works without it
+    if let Err(e) = set_commands(&bot).await {
+        warn!("can't set commands: {e}");
+    }
+
+    #[cfg(feature = "webhook")]
+    let webhook_listener = match &config.webhook {
+        Some(settings) => Some(webhook::listener(&bot, settings).await?),
+        None => None,
+    };
 
     let handler = dptree::entry()
+        .inspect_async(reactivate_user)
+        .map_async(user_language)
         .enter_dialogue::<Update, InMemStorage<State>, State>()
         // .branch(
         //     dptree::filter_map(|update: Update| {
@@ -127,33 +178,146 @@ async fn main() -> anyhow::Result<()> {
         // )
         .branch(
             Update::filter_message()
+                .branch(
+                    dptree::filter(|config: Arc<Config>, msg: Message| {
+                        config.admins.contains(&msg.chat.id.0)
+                    })
+                    .filter_command::<AdminCommand>()
+                    .inspect(count_update("admin_command"))
+                    .endpoint(admin::answer),
+                )
+                .chain(dptree::filter_async(limits::allow))
                 .branch(
                     dptree::entry()
                         .filter_command::<Command>()
+                        .inspect(count_update("command"))
                         .endpoint(answer),
                 )
-                .branch(dptree::endpoint(handle::handle_message)),
+                .branch(
+                    dptree::inspect(count_update("message"))
+                        .endpoint(handle::handle_message),
+                ),
         )
         .branch(
             Update::filter_callback_query()
-                .branch(dptree::endpoint(handle::handle_callback)),
+                .branch(
+                    dptree::filter(|config: Arc<Config>, q: CallbackQuery| {
+                        let chat = q.message.map(|m| m.chat.id.0);
+                        chat.is_some() && chat == config.moderation.review_chat
+                    })
+                    .inspect(count_update("review"))
+                    .endpoint(review::handle_callback),
+                )
+                .chain(dptree::filter_async(limits::allow))
+                .branch(
+                    dptree::inspect(count_update("callback"))
+                        .endpoint(handle::handle_callback),
+                ),
         );
 
-    let database = db::Database::new().await?;
-
-    Dispatcher::builder(bot, handler)
+    tokio::spawn(outbox::run(bot.clone(), database.clone()));
+    tokio::spawn(storage::run(bot.clone(), database.clone()));
+    tokio::spawn(scheduler::run(
+        bot.clone(),
+        database.clone(),
+        config.likes.clone(),
+    ));
+    tokio::spawn(recommendations::run(
+        database.clone(),
+        config.recommendations.clone(),
+    ));
+
+    let rate_limiter = Arc::new(limits::RateLimiter::new(&config.limits));
+    let mut dispatcher = Dispatcher::builder(bot.inner().clone(), handler)
         .dependencies(dptree::deps![
             InMemStorage::<State>::new(),
-            Arc::new(database)
+            database,
+            bot,
+            config,
+            Arc::new(TextDrafts::default()),
+            rate_limiter
         ])
         .error_handler(AppErrorHandler::new())
         .enable_ctrlc_handler()
-        .build()
-        .dispatch()
-        .await;
+        .build();
+
+    metrics.dispatching.store(true, Ordering::Relaxed);
+    #[cfg(feature = "webhook")]
+    if let Some(listener) = webhook_listener {
+        dispatcher
+            .dispatch_with_listener(
+                listener,
+                LoggingErrorHandler::with_custom_text("webhook error"),
+            )
+            .await;
+        metrics.dispatching.store(false, Ordering::Relaxed);
+        return Ok(());
+    }
+
+    dispatcher.dispatch().await;
+    metrics.dispatching.store(false, Ordering::Relaxed);
     Ok(())
 }
 
+/// Show the commands in the menu in the language of the interface
+async fn set_commands(bot: &Bot) -> anyhow::Result<()> {
+    for lang in Lang::ALL {
+        let commands = Command::bot_commands()
+            .into_iter()
+            .map(|mut command| {
+                let name = command.command.trim_start_matches('/');
+                let key = format!("COMMAND_{}", name.to_uppercase());
+                let text = text::find(&key)
+                    .with_context(|| format!("no description of /{name}"))?;
+                command.description = text.get(lang).into_owned();
+                Ok(command)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        for code in lang.codes() {
+            bot.set_my_commands(commands.clone()).language_code(*code).await?;
+        }
+        // Users with other languages get English, see `Lang::from_code`
+        if lang == Lang::En {
+            bot.set_my_commands(commands).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Count updates processed by the handler
+fn count_update(handler: &'static str) -> impl Fn(Bot) + Clone {
+    move |bot: Bot| bot.metrics().update(handler)
+}
+
+/// Language of the user who sent the update
+async fn user_language(db: Arc<Database>, upd: Update) -> Lang {
+    let Some(user) = upd.user() else { return Lang::default() };
+    let code = user.language_code.as_deref();
+    match db.resolve_language(ChatId::from(user.id).0, code).await {
+        Ok(lang) => lang,
+        Err(e) => {
+            sentry_anyhow::capture_anyhow(
+                &e.context("error while resolving user language"),
+            );
+            Lang::from_code(code)
+        }
+    }
+}
+
+/// Reactivate profile of the user deactivated because of blocking the bot
+async fn reactivate_user(db: Arc<Database>, upd: Update) {
+    let Some(user) = upd.user() else { return };
+    match db.reactivate_user(ChatId::from(user.id).0).await {
+        Ok(true) => info!("user {} reactivated", user.id),
+        Ok(false) => {}
+        Err(e) => {
+            sentry_anyhow::capture_anyhow(
+                &e.context("error while reactivating user"),
+            );
+        }
+    }
+}
+
 macro_rules! make_profile {
     ($($element:ident: $ty:ty),* $(,)?) => {
         #[derive(Clone, Default, Debug, PartialEq, Eq)]
@@ -161,6 +325,11 @@ macro_rules! make_profile {
             id: i64,
             create_new: bool,
             photos_count: u8,
+            /// Photos were changed during the edit, so the profile is hidden
+            /// until it's reviewed again
+            photos_changed: bool,
+            /// Messages of the photo manager in the order of the photos
+            photo_messages: Vec<MessageId>,
             $($element: Option<$ty>),*
         }
         impl EditProfile {
@@ -169,6 +338,8 @@ macro_rules! make_profile {
                     id,
                     create_new: true,
                     photos_count: 0,
+                    photos_changed: false,
+                    photo_messages: Vec::new(),
                     ..Default::default()
                 }
             }
@@ -178,6 +349,8 @@ macro_rules! make_profile {
                     id: m.id,
                     create_new: false,
                     photos_count: 0,
+                    photos_changed: false,
+                    photo_messages: Vec::new(),
                     $($element: Some(m.$element)),*
                 }
             }
@@ -187,6 +360,12 @@ macro_rules! make_profile {
                 entities::users::ActiveModel {
                     id: ActiveValue::Unchanged(self.id),
                     last_activity: ActiveValue::NotSet,
+                    deactivation_reason: ActiveValue::NotSet,
+                    deactivated_at: ActiveValue::NotSet,
+                    created: ActiveValue::NotSet,
+                    shadow_banned_at: ActiveValue::NotSet,
+                    review_status: ActiveValue::NotSet,
+                    review_version: ActiveValue::NotSet,
                     $($element: self.$element
                         .map_or(ActiveValue::NotSet, |p| ActiveValue::Set(p))),*
                 }
@@ -227,9 +406,17 @@ pub enum State {
     SetLocationFilter(EditProfile),
     SetAbout(EditProfile),
     SetPhotos(EditProfile),
-    /// Waiting for the message for the like
+    ManagePhotos(EditProfile),
+    /// Waiting for the message for the like, previewing it if it's received
     LikeWithMessage {
         dating: entities::datings::Model,
+        draft: Option<datings::LikeDraft>,
+    },
+    /// Relaying messages to the partner of mutual dating
+    Chatting {
+        dating_id: i32,
+        partner: i64,
+        name: String,
     },
     Edit,
 }
@@ -245,10 +432,16 @@ enum Command {
     Edit,
     #[command(description = "найти партнёра")]
     Date,
+    #[command(description = "выйти из чата")]
+    Leave,
+    #[command(description = "обменяться контактами с собеседником")]
+    Reveal,
     #[command(description = "включить анкету")]
     Enable,
     #[command(description = "выключить анкету")]
     Disable,
+    #[command(description = "выбрать язык")]
+    Language(String),
     #[command(description = "приветственное сообщение")]
     Start,
     #[command(description = "помощь по командам")]
@@ -259,9 +452,10 @@ pub async fn start_profile_creation(
     state: &mut State,
     msg: &Message,
     bot: &Bot,
+    lang: Lang,
 ) -> anyhow::Result<()> {
     let chat = &msg.chat;
-    handle::make_macros!(bot, msg, state, chat);
+    handle::make_macros!(bot, msg, state, chat, lang);
 
     // if !utils::check_user_subscribed_channel(bot, msg.chat.id.0).await? {
     //     let keyboard = vec![vec![InlineKeyboardButton::callback(
@@ -298,25 +492,18 @@ pub async fn start_profile_creation(
     remove_buttons!();
     if !utils::check_user_subscribed_channel(bot, msg.chat.id.0).await? {
         send!(
-            text::SUBSCRIBE_TEXT,
+            text::SUBSCRIBE_TEXT.get(lang),
             inline[[InlineKeyboardButton::callback(
-                "Я подписался на канал",
+                text::SUBSCRIBED.get(lang),
                 "✍",
             )]]
         );
         return Ok(());
     };
 
-    if utils::user_url(bot, msg.chat.id.0).await?.is_none() {
-        send!(
-            text::PLEASE_ALLOW_FORWARDING,
-            inline[[InlineKeyboardButton::callback("Я сделал юзернейм", "✍",)]]
-        );
-    } else {
-        send!(text::PROFILE_CREATION_STARTED);
-        let profile = EditProfile::new(msg.chat.id.0);
-        upd_print!(State::SetName(profile));
-    }
+    send!(text::PROFILE_CREATION_STARTED.get(lang));
+    let profile = EditProfile::new(msg.chat.id.0);
+    upd_print!(State::SetName(profile));
 
     Ok(())
 }
@@ -329,6 +516,7 @@ async fn answer(
     state: State,
     msg: Message,
     cmd: Command,
+    lang: Lang,
 ) -> anyhow::Result<()> {
     async fn inner(
         db: Arc<Database>,
@@ -337,51 +525,114 @@ async fn answer(
         mut state: State,
         msg: Message,
         cmd: Command,
+        lang: Lang,
     ) -> anyhow::Result<()> {
         match cmd {
             Command::Create => {
-                start_profile_creation(&mut state, &msg, &bot).await?;
+                start_profile_creation(&mut state, &msg, &bot, lang).await?;
+                let step = handle::wizard_step(&state);
                 dialogue.update(state).await?;
+                // Restarting the wizard isn't a transition between its steps
+                handle::log_wizard_step(&db, &bot, msg.chat.id.0, None, step)
+                    .await;
             }
             Command::Edit => {
                 if db.get_user(msg.chat.id.0).await?.is_none() {
-                    bot.send_message(msg.chat.id, text::PLEASE_CREATE_PROFILE)
-                        .await?;
+                    bot.send_message(
+                        msg.chat.id,
+                        text::PLEASE_CREATE_PROFILE.get(lang),
+                    )
+                    .await?;
                     return Ok(());
                 }
 
-                request::edit_profile(&bot, &msg.chat).await?;
+                // The photos can't be left on hold by switching to the menu
+                if let State::SetPhotos(p) | State::ManagePhotos(p) = &state {
+                    handle::submit_photos(&db, &bot, p, lang).await?;
+                }
+                request::edit_profile(&bot, &msg.chat, lang).await?;
                 dialogue.update(State::Edit).await?;
             }
             Command::Help => {
-                bot.send_message(
-                    msg.chat.id,
-                    Command::descriptions().to_string(),
-                )
-                .await?;
+                bot.send_message(msg.chat.id, text::HELP.get(lang)).await?;
+            }
+            Command::Language(code) => {
+                let choice = match code.trim() {
+                    "auto" => Some(None),
+                    code => code.parse().ok().map(Some),
+                };
+                let Some(choice) = choice else {
+                    bot.send_message(
+                        msg.chat.id,
+                        text::LANGUAGE_USAGE.get(lang),
+                    )
+                    .await?;
+                    return Ok(());
+                };
+
+                db.set_language(msg.chat.id.0, choice).await?;
+                let lang = db.get_language(msg.chat.id.0).await?;
+                bot.send_message(msg.chat.id, text::LANGUAGE_CHANGED.get(lang))
+                    .reply_markup(KeyboardRemove::new())
+                    .await?;
+                handle::print_state(&state, &bot, &msg.chat, lang).await?;
             }
             Command::Date => {
                 if db.get_user(msg.chat.id.0).await?.is_none() {
-                    bot.send_message(msg.chat.id, text::PLEASE_CREATE_PROFILE)
-                        .await?;
+                    bot.send_message(
+                        msg.chat.id,
+                        text::PLEASE_CREATE_PROFILE.get(lang),
+                    )
+                    .await?;
                     return Ok(());
                 }
 
-                datings::send_recommendation(&bot, &db, msg.chat.id).await?;
+                datings::send_recommendation(&bot, &db, msg.chat.id, lang)
+                    .await?;
+            }
+            Command::Leave => {
+                if let State::Chatting { dating_id, partner, .. } = state {
+                    db.notify_chat_left(partner, dating_id).await?;
+                    bot.send_message(msg.chat.id, text::CHAT_LEFT.get(lang))
+                        .reply_markup(KeyboardRemove::new())
+                        .await?;
+                    dialogue.update(State::Start).await?;
+                } else {
+                    bot.send_message(msg.chat.id, text::NOT_IN_CHAT.get(lang))
+                        .await?;
+                }
+            }
+            Command::Reveal => {
+                let State::Chatting { dating_id, .. } = state else {
+                    bot.send_message(msg.chat.id, text::NOT_IN_CHAT.get(lang))
+                        .await?;
+                    return Ok(());
+                };
+                relay::reveal(&bot, &db, msg.chat.id.0, dating_id, lang)
+                    .await?;
             }
             Command::Profile => {
                 if db.get_user(msg.chat.id.0).await?.is_none() {
-                    bot.send_message(msg.chat.id, text::PLEASE_CREATE_PROFILE)
-                        .await?;
+                    bot.send_message(
+                        msg.chat.id,
+                        text::PLEASE_CREATE_PROFILE.get(lang),
+                    )
+                    .await?;
                     return Ok(());
                 }
 
-                datings::send_profile(&bot, &db, msg.chat.id.0).await?;
+                datings::send_profile(&bot, &db, msg.chat.id.0, lang).await?;
+                #[cfg(feature = "card")]
+                datings::send_profile_card(&bot, &db, msg.chat.id.0, lang)
+                    .await?;
             }
             Command::Enable => {
                 if db.get_user(msg.chat.id.0).await?.is_none() {
-                    bot.send_message(msg.chat.id, text::PLEASE_CREATE_PROFILE)
-                        .await?;
+                    bot.send_message(
+                        msg.chat.id,
+                        text::PLEASE_CREATE_PROFILE.get(lang),
+                    )
+                    .await?;
                     return Ok(());
                 }
 
@@ -390,12 +641,23 @@ async fn answer(
                     ..EditProfile::new(msg.chat.id.0)
                 })
                 .await?;
-                bot.send_message(msg.chat.id, text::PROFILE_ENABLED).await?;
+                db.log_event(
+                    msg.chat.id.0,
+                    EventKind::ProfileEnabled,
+                    None,
+                    Some("command".to_owned()),
+                )
+                .await?;
+                bot.send_message(msg.chat.id, text::PROFILE_ENABLED.get(lang))
+                    .await?;
             }
             Command::Disable => {
                 if db.get_user(msg.chat.id.0).await?.is_none() {
-                    bot.send_message(msg.chat.id, text::PLEASE_CREATE_PROFILE)
-                        .await?;
+                    bot.send_message(
+                        msg.chat.id,
+                        text::PLEASE_CREATE_PROFILE.get(lang),
+                    )
+                    .await?;
                     return Ok(());
                 }
 
@@ -404,18 +666,26 @@ async fn answer(
                     ..EditProfile::new(msg.chat.id.0)
                 })
                 .await?;
-                bot.send_message(msg.chat.id, text::PROFILE_DISABLED).await?;
+                db.log_event(
+                    msg.chat.id.0,
+                    EventKind::ProfileDisabled,
+                    None,
+                    Some("command".to_owned()),
+                )
+                .await?;
+                bot.send_message(msg.chat.id, text::PROFILE_DISABLED.get(lang))
+                    .await?;
             }
             Command::Start => {
                 db.create_state(msg.chat.id.0).await?;
 
                 let keyboard = vec![vec![InlineKeyboardButton::callback(
-                    "Заполнить анкету ✍",
+                    text::CREATE_PROFILE.get(lang),
                     "✍",
                 )]];
                 let keyboard_markup = InlineKeyboardMarkup::new(keyboard);
 
-                bot.send_message(msg.chat.id, text::START)
+                bot.send_message(msg.chat.id, text::START.get(lang))
                     .reply_markup(keyboard_markup)
                     .await?;
             }
@@ -425,7 +695,7 @@ async fn answer(
     }
     // FIXME: remove this
     if let Err(e) =
-        inner(db, bot.clone(), dialogue, state, msg.clone(), cmd).await
+        inner(db, bot.clone(), dialogue, state, msg.clone(), cmd, lang).await
     {
         bot.send_message(
             msg.chat.id,
@@ -0,0 +1,194 @@
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge,
+    Opts, Registry, TextEncoder,
+};
+use serde::Deserialize;
+use teloxide::{ApiError, DownloadError, RequestError};
+
+use crate::db::Database;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MonitoringSettings {
+    /// Address serving `/metrics` and `/healthz`
+    pub address: SocketAddr,
+}
+
+/// Prometheus metrics of the bot
+pub struct Metrics {
+    registry: Registry,
+    /// Updates by handler which processed them
+    pub updates: IntCounterVec,
+    pub get_partner_duration: Histogram,
+    /// `get_partner` calls by result, `found` or `empty`
+    pub get_partner: IntCounterVec,
+    pub likes: IntCounter,
+    pub mutual_likes: IntCounter,
+    /// Failed requests and downloads by error kind
+    telegram_errors: IntCounterVec,
+    /// Requests and downloads which are sent but not answered yet. It's not
+    /// the depth of the throttle queue: requests waiting there are counted
+    /// with the ones which Telegram is processing.
+    pub telegram_requests_in_flight: IntGauge,
+    db_connections: IntGauge,
+    db_idle_connections: IntGauge,
+    /// Whether the dispatcher is running, checked by `/healthz`
+    pub dispatching: AtomicBool,
+}
+
+impl Metrics {
+    pub fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new();
+        let metrics = Self {
+            updates: IntCounterVec::new(
+                Opts::new("bot_updates_total", "Processed updates"),
+                &["handler"],
+            )?,
+            get_partner_duration: Histogram::with_opts(
+                HistogramOpts::new(
+                    "bot_get_partner_duration_seconds",
+                    "Time of searching a partner",
+                )
+                .buckets(vec![0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]),
+            )?,
+            get_partner: IntCounterVec::new(
+                Opts::new("bot_get_partner_total", "Searches of a partner"),
+                &["result"],
+            )?,
+            likes: IntCounter::new("bot_likes_total", "Sent likes")?,
+            mutual_likes: IntCounter::new(
+                "bot_mutual_likes_total",
+                "Mutual likes",
+            )?,
+            telegram_errors: IntCounterVec::new(
+                Opts::new("bot_telegram_errors_total", "Telegram API errors"),
+                &["kind"],
+            )?,
+            telegram_requests_in_flight: IntGauge::new(
+                "bot_telegram_requests_in_flight",
+                "Telegram requests and downloads waiting for a response, \
+                 including throttled ones",
+            )?,
+            db_connections: IntGauge::new(
+                "bot_db_connections",
+                "Open database connections",
+            )?,
+            db_idle_connections: IntGauge::new(
+                "bot_db_idle_connections",
+                "Idle database connections",
+            )?,
+            dispatching: AtomicBool::new(false),
+            registry,
+        };
+
+        let r = &metrics.registry;
+        r.register(Box::new(metrics.updates.clone()))?;
+        r.register(Box::new(metrics.get_partner_duration.clone()))?;
+        r.register(Box::new(metrics.get_partner.clone()))?;
+        r.register(Box::new(metrics.likes.clone()))?;
+        r.register(Box::new(metrics.mutual_likes.clone()))?;
+        r.register(Box::new(metrics.telegram_errors.clone()))?;
+        r.register(Box::new(metrics.telegram_requests_in_flight.clone()))?;
+        r.register(Box::new(metrics.db_connections.clone()))?;
+        r.register(Box::new(metrics.db_idle_connections.clone()))?;
+        Ok(metrics)
+    }
+
+    pub fn update(&self, handler: &str) {
+        self.updates.with_label_values(&[handler]).inc();
+    }
+
+    pub fn telegram_error(&self, error: &RequestError) {
+        let kind = match error {
+            // The text of unknown errors would make too many labels
+            RequestError::Api(ApiError::Unknown(_)) => "Unknown".to_owned(),
+            RequestError::Api(e) => format!("{e:?}"),
+            RequestError::MigrateToChatId(_) => "MigrateToChatId".to_owned(),
+            RequestError::RetryAfter(_) => "RetryAfter".to_owned(),
+            RequestError::Network(_) => "Network".to_owned(),
+            RequestError::InvalidJson { .. } => "InvalidJson".to_owned(),
+            RequestError::Io(_) => "Io".to_owned(),
+        };
+        self.telegram_errors.with_label_values(&[&kind]).inc();
+    }
+
+    pub fn download_error(&self, error: &DownloadError) {
+        let kind = match error {
+            DownloadError::Network(_) => "Network",
+            DownloadError::Io(_) => "Io",
+        };
+        self.telegram_errors.with_label_values(&[kind]).inc();
+    }
+}
+
+#[derive(Clone)]
+struct AppState {
+    metrics: Arc<Metrics>,
+    db: Arc<Database>,
+}
+
+/// Serve `/metrics` and `/healthz` in the background
+pub fn serve(
+    settings: &MonitoringSettings,
+    metrics: Arc<Metrics>,
+    db: Arc<Database>,
+) -> anyhow::Result<()> {
+    let app = Router::new()
+        .route("/metrics", get(metrics_handler))
+        .route("/healthz", get(health_handler))
+        .with_state(AppState { metrics, db });
+    // Bind before spawning, so a busy address stops the bot at startup
+    let server = axum::Server::try_bind(&settings.address)?
+        .serve(app.into_make_service());
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            sentry_anyhow::capture_anyhow(
+                &anyhow::Error::from(e).context("error while serving metrics"),
+            );
+        }
+    });
+    Ok(())
+}
+
+async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let (size, idle) = state.db.pool_usage();
+    state.metrics.db_connections.set(size.into());
+    state.metrics.db_idle_connections.set(idle.try_into().unwrap_or(i64::MAX));
+
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    if let Err(e) =
+        encoder.encode(&state.metrics.registry.gather(), &mut buffer)
+    {
+        tracing::error!("can't encode metrics: {e}");
+        return (StatusCode::INTERNAL_SERVER_ERROR, Vec::new()).into_response();
+    }
+    ([(header::CONTENT_TYPE, encoder.format_type().to_owned())], buffer)
+        .into_response()
+}
+
+async fn health_handler(State(state): State<AppState>) -> impl IntoResponse {
+    if !state.metrics.dispatching.load(Ordering::Relaxed) {
+        return (StatusCode::SERVICE_UNAVAILABLE, "dispatcher is stopped");
+    }
+    if let Err(e) = state.db.ping().await {
+        tracing::warn!("database health check failed: {e}");
+        return (StatusCode::SERVICE_UNAVAILABLE, "database is unavailable");
+    }
+    (StatusCode::OK, "ok")
+}
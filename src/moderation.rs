@@ -0,0 +1,541 @@
+use std::ops::Range;
+
+use anyhow::ensure;
+use entities::sea_orm_active_enums::FlagReason;
+use image::imageops::FilterType;
+use itertools::Itertools;
+use serde::Deserialize;
+
+use crate::{db::Database, Bot};
+
+/// Maximum Hamming distance between hashes of near-identical photos
+const MAX_HASH_DISTANCE: u32 = 6;
+
+/// Difference hash of the photo which survives resizing and recompression
+pub fn photo_hash(data: &[u8]) -> anyhow::Result<i64> {
+    let image = image::load_from_memory(data)?
+        .grayscale()
+        .resize_exact(9, 8, FilterType::Triangle)
+        .into_luma8();
+
+    let mut hash = 0i64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if image.get_pixel(x, y)[0] < image.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    Ok(hash)
+}
+
+/// Flag the user if the photo looks like a photo of another user
+pub async fn check_duplicate_photo(
+    db: &Database,
+    user: i64,
+    hash: i64,
+) -> anyhow::Result<()> {
+    let owners =
+        db.get_similar_photo_owners(user, hash, MAX_HASH_DISTANCE).await?;
+
+    if !owners.is_empty() {
+        tracing::info!("user {user} uploaded photo similar to {owners:?}");
+        db.create_flag(
+            user,
+            FlagReason::DuplicatePhoto,
+            format!("фото похоже на фото {}", owners.iter().join(", ")),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModerationSettings {
+    /// Words are found in any case, with letters replaced by similar ones and
+    /// with symbols between letters. A word matches whole words, unless it
+    /// ends with `*` which matches any ending.
+    pub banned_words: Vec<String>,
+    /// New and changed profiles are hidden until they're approved in this
+    /// chat, profiles aren't reviewed without it
+    pub review_chat: Option<i64>,
+    pub name: FieldPolicy,
+    pub about: FieldPolicy,
+    pub like_message: FieldPolicy,
+}
+
+impl Default for ModerationSettings {
+    fn default() -> Self {
+        Self {
+            banned_words: Vec::new(),
+            review_chat: None,
+            name: FieldPolicy {
+                words: Action::Reject,
+                contacts: Action::Reject,
+            },
+            about: FieldPolicy {
+                words: Action::Mask,
+                contacts: Action::Review,
+            },
+            like_message: FieldPolicy::default(),
+        }
+    }
+}
+
+impl ModerationSettings {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for word in &self.banned_words {
+            ensure!(
+                !BannedWord::new(word).runs.is_empty(),
+                "banned word {word:?} has no letters"
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FieldPolicy {
+    /// Action on banned words
+    pub words: Action,
+    /// Action on phone numbers, emails, usernames and links
+    pub contacts: Action,
+}
+
+impl Default for FieldPolicy {
+    fn default() -> Self {
+        Self { words: Action::Mask, contacts: Action::Mask }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Allow,
+    /// The user is asked to send another text
+    Reject,
+    /// Found fragments are replaced with `*`, hidden links are removed
+    Mask,
+    /// The text is kept and sent to admins, see `/flagged`
+    Review,
+}
+
+/// Texts written by users
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// Plain text
+    Name,
+    /// HTML, see [`crate::html::from_entities`]
+    About,
+    /// HTML, see [`crate::html::from_entities`]
+    LikeMessage,
+}
+
+impl Field {
+    const fn policy(self, settings: &ModerationSettings) -> FieldPolicy {
+        match self {
+            Self::Name => settings.name,
+            Self::About => settings.about,
+            Self::LikeMessage => settings.like_message,
+        }
+    }
+
+    const fn title(self) -> &'static str {
+        match self {
+            Self::Name => "имя",
+            Self::About => "описание",
+            Self::LikeMessage => "сообщение с лайком",
+        }
+    }
+}
+
+/// Apply the policy of the field to the text, `None` if it's rejected
+pub async fn moderate_text(
+    db: &Database,
+    bot: &Bot,
+    user: i64,
+    field: Field,
+    text: &str,
+) -> anyhow::Result<Option<String>> {
+    let settings = &bot.config().moderation;
+    let policy = field.policy(settings);
+    let html = field != Field::Name;
+
+    let visible = visible_chars(text, html);
+    let chars: Vec<char> = visible.iter().map(|(c, _)| *c).collect();
+    let words: Vec<_> =
+        settings.banned_words.iter().map(|w| BannedWord::new(w)).collect();
+    let found = [
+        (policy.words, find_words(&chars, &words), Vec::new()),
+        (
+            policy.contacts,
+            find_contacts(&chars),
+            if html { link_tags(text) } else { Vec::new() },
+        ),
+    ];
+
+    let mut masked = Vec::new();
+    let mut removed = Vec::new();
+    let mut fragments = Vec::new();
+    for (action, ranges, links) in found {
+        if ranges.is_empty() && links.is_empty() {
+            continue;
+        }
+        match action {
+            Action::Allow => {}
+            Action::Reject => {
+                tracing::info!("{} of user {user} is rejected", field.title());
+                return Ok(None);
+            }
+            Action::Mask => {
+                masked.extend(ranges);
+                removed.extend(links);
+            }
+            Action::Review => {
+                fragments.extend(
+                    ranges.into_iter().map(|r| chars[r].iter().collect()),
+                );
+                fragments.extend(links.into_iter().map(|r| text[r].to_owned()));
+            }
+        }
+    }
+
+    if !fragments.is_empty() {
+        let details = format!("{}: {}", field.title(), fragments.join(", "));
+        db.create_flag(user, FlagReason::Text, details).await?;
+    }
+    Ok(Some(mask(text, &visible, &masked, &removed)))
+}
+
+/// Whether the HTML has a link, an email, a username or a phone number
+pub fn has_contact(html: &str) -> bool {
+    let chars: Vec<char> =
+        visible_chars(html, true).into_iter().map(|(c, _)| c).collect();
+    !find_contacts(&chars).is_empty() || !link_tags(html).is_empty()
+}
+
+/// Characters shown to users and their bytes in the text
+fn visible_chars(text: &str, html: bool) -> Vec<(char, Range<usize>)> {
+    let mut result = Vec::new();
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        let mut end = i + c.len_utf8();
+        let mut shown = Some(c);
+        if html && c == '<' {
+            end = text[i..].find('>').map_or(text.len(), |e| i + e + 1);
+            shown = None;
+        } else if html && c == '&' {
+            if let Some((entity, decoded)) = ["&amp;", "&lt;", "&gt;", "&quot;"]
+                .into_iter()
+                .zip(['&', '<', '>', '"'])
+                .find(|(e, _)| text[i..].starts_with(e))
+            {
+                end = i + entity.len();
+                shown = Some(decoded);
+            }
+        }
+        if let Some(c) = shown {
+            result.push((c, i..end));
+        }
+        i = end;
+    }
+    result
+}
+
+/// Opening and closing tags of links, their addresses aren't visible
+fn link_tags(html: &str) -> Vec<Range<usize>> {
+    let mut result = Vec::new();
+    for (start, _) in html.match_indices("<a ") {
+        let end = html[start..].find('>').map_or(html.len(), |e| start + e + 1);
+        result.push(start..end);
+        if let Some(close) = html[end..].find("</a>") {
+            result.push(end + close..end + close + "</a>".len());
+        }
+    }
+    result
+}
+
+/// Replace masked characters with `*` and remove removed ranges
+fn mask(
+    text: &str,
+    visible: &[(char, Range<usize>)],
+    masked: &[Range<usize>],
+    removed: &[Range<usize>],
+) -> String {
+    let mut replaced: Vec<(Range<usize>, &str)> = masked
+        .iter()
+        .flat_map(|r| {
+            visible[r.clone()].iter().map(|(_, span)| (span.clone(), "*"))
+        })
+        .chain(removed.iter().map(|r| (r.clone(), "")))
+        .collect();
+    replaced.sort_by_key(|(r, _)| r.start);
+
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+    for (range, with) in replaced {
+        // Words and contacts can overlap
+        if range.start < i {
+            continue;
+        }
+        result.push_str(&text[i..range.start]);
+        result.push_str(with);
+        i = range.end;
+    }
+    result.push_str(&text[i..]);
+    result
+}
+
+/// Letter which looks like `c`, `None` for symbols between letters
+fn normalize(c: char) -> Option<char> {
+    let c = c.to_lowercase().next()?;
+    let c = match c {
+        'а' | '@' => 'a',
+        'в' => 'b',
+        'с' => 'c',
+        'е' | 'ё' => 'e',
+        'н' => 'h',
+        'к' => 'k',
+        'м' => 'm',
+        'о' | '0' => 'o',
+        'р' => 'p',
+        'т' => 't',
+        'х' => 'x',
+        'у' => 'y',
+        '1' | '!' | '|' => 'i',
+        '$' | '5' => 's',
+        '3' => 'з',
+        '6' => 'б',
+        c => c,
+    };
+    c.is_alphanumeric().then_some(c)
+}
+
+/// Normalized letters and the number of their repeats
+struct BannedWord {
+    runs: Vec<(char, usize)>,
+    prefix: bool,
+}
+
+impl BannedWord {
+    fn new(word: &str) -> Self {
+        let prefix = word.ends_with('*');
+        let mut runs: Vec<(char, usize)> = Vec::new();
+        for c in word.chars().filter_map(normalize) {
+            match runs.last_mut() {
+                Some((last, count)) if *last == c => *count += 1,
+                _ => runs.push((c, 1)),
+            }
+        }
+        Self { runs, prefix }
+    }
+}
+
+/// Visible characters of banned words. Repeated letters and symbols between
+/// letters are skipped, so "f.u.u.c.k" is found like "fuck".
+fn find_words(chars: &[char], words: &[BannedWord]) -> Vec<Range<usize>> {
+    struct Run {
+        letter: char,
+        count: usize,
+        start: usize,
+        end: usize,
+    }
+    let mut runs: Vec<Run> = Vec::new();
+    for (i, letter) in chars.iter().enumerate() {
+        let Some(letter) = normalize(*letter) else {
+            continue;
+        };
+        match runs.last_mut() {
+            Some(run) if run.letter == letter => {
+                run.count += 1;
+                run.end = i + 1;
+            }
+            _ => runs.push(Run { letter, count: 1, start: i, end: i + 1 }),
+        }
+    }
+
+    let boundary = |i: Option<usize>| {
+        !i.and_then(|i| chars.get(i)).is_some_and(|c| c.is_alphanumeric())
+    };
+    let mut result = Vec::new();
+    for word in words.iter().filter(|w| !w.runs.is_empty()) {
+        for window in runs.windows(word.runs.len()) {
+            let matches =
+                window.iter().zip(&word.runs).all(|(run, &(c, count))| {
+                    run.letter == c && run.count >= count
+                });
+            let (first, last) = (&window[0], &window[window.len() - 1]);
+            if matches
+                && boundary(first.start.checked_sub(1))
+                && (word.prefix || boundary(Some(last.end)))
+            {
+                // The ending of a prefix match is hidden too
+                let end = chars[last.end..]
+                    .iter()
+                    .position(|c| !c.is_alphanumeric())
+                    .map_or(chars.len(), |e| last.end + e);
+                result.push(first.start..end);
+            }
+        }
+    }
+    result
+}
+
+/// Top-level domains of links written without `http://`
+const DOMAINS: &[&str] = &[
+    "com", "ru", "net", "org", "me", "io", "gg", "su", "info", "рф", "link",
+    "ly", "xyz", "site", "online",
+];
+
+/// Visible characters of links, emails, usernames and phone numbers
+fn find_contacts(chars: &[char]) -> Vec<Range<usize>> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    for (i, c) in chars.iter().chain([&' ']).enumerate() {
+        if !c.is_whitespace() {
+            continue;
+        }
+        if start < i {
+            let token: String = chars[start..i].iter().collect();
+            if is_link(&token.to_lowercase()) || is_address(&token) {
+                result.push(start..i);
+            }
+        }
+        start = i + 1;
+    }
+    result.extend(find_phones(chars));
+    result
+}
+
+fn is_link(token: &str) -> bool {
+    if ["://", "www.", "t.me/", "telegram.me/", "wa.me/", "discord.gg/"]
+        .iter()
+        .any(|p| token.contains(p))
+    {
+        return true;
+    }
+    let host = token.split(['/', '?']).next().unwrap_or_default();
+    let host = host.trim_end_matches(|c: char| !c.is_alphanumeric());
+    host.rsplit_once('.').is_some_and(|(name, domain)| {
+        !name.is_empty() && DOMAINS.contains(&domain)
+    })
+}
+
+/// Whether the token is an email or a Telegram username
+fn is_address(token: &str) -> bool {
+    let Some((user, rest)) = token.split_once('@') else {
+        return false;
+    };
+    let name = |c: &char| c.is_ascii_alphanumeric() || *c == '_';
+    // Usernames have at least 5 characters
+    let username = rest.chars().take_while(name).count() >= 5;
+    let email = !user.is_empty()
+        && rest.rsplit_once('.').is_some_and(|(host, domain)| {
+            !host.is_empty() && domain.chars().any(|c| c.is_alphabetic())
+        });
+    username || email
+}
+
+/// Phone numbers start with `+`, `7` or `8` and have at least 10 digits
+/// split by spaces, dashes or parentheses
+fn find_phones(chars: &[char]) -> Vec<Range<usize>> {
+    let mut result = Vec::new();
+    let mut digits = 0;
+    let mut start = None;
+    let mut end = 0;
+    for (i, &c) in chars.iter().chain([&'.']).enumerate() {
+        if c.is_ascii_digit() {
+            if digits == 0 && start.is_none() && matches!(c, '7' | '8') {
+                start = Some(i);
+            }
+            digits += 1;
+            end = i + 1;
+        } else if c == '+' && digits == 0 {
+            start = Some(i);
+        } else if !matches!(c, ' ' | '-' | '(' | ')') {
+            if let Some(start) = start.filter(|_| digits >= 10) {
+                result.push(start..end);
+            }
+            digits = 0;
+            start = None;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mask banned words and contacts like the [`Action::Mask`] policy
+    fn masked(text: &str, html: bool, words: &[&str]) -> String {
+        let visible = visible_chars(text, html);
+        let chars: Vec<char> = visible.iter().map(|(c, _)| *c).collect();
+        let words: Vec<_> = words.iter().map(|w| BannedWord::new(w)).collect();
+        let mut ranges = find_words(&chars, &words);
+        ranges.extend(find_contacts(&chars));
+        let links = if html { link_tags(text) } else { Vec::new() };
+        mask(text, &visible, &ranges, &links)
+    }
+
+    #[test]
+    fn obfuscated_words() {
+        assert_eq!(masked("you f.u.u.c.k", false, &["fuck"]), "you *********");
+        assert_eq!(masked("FUUUCK off", false, &["fuck"]), "****** off");
+        // Cyrillic letters which look like Latin ones
+        assert_eq!(masked("nice сакe", false, &["cake"]), "nice ****");
+        assert_eq!(masked("c@ke", false, &["cake"]), "****");
+    }
+
+    #[test]
+    fn whole_and_prefix_words() {
+        assert_eq!(masked("cakes", false, &["cake"]), "cakes");
+        assert_eq!(masked("pancake", false, &["cake*"]), "pancake");
+        assert_eq!(masked("cakes!", false, &["cake*"]), "*****!");
+    }
+
+    #[test]
+    fn masking_html() {
+        assert_eq!(masked("a&amp;fuck", true, &["fuck"]), "a&amp;****");
+        assert_eq!(masked("fuck&lt;3", true, &["fuck"]), "****&lt;3");
+        assert_eq!(masked("<b>fu</b>ck ok", true, &["fuck"]), "<b>**</b>** ok");
+        assert_eq!(masked("&lt;fuck&gt;", true, &["fuck"]), "&lt;****&gt;");
+    }
+
+    #[test]
+    fn removing_links() {
+        assert_eq!(
+            masked("<a href=\"https://x.com\">site</a> hi", true, &[]),
+            "site hi"
+        );
+        assert_eq!(masked("go to t.me/chat", true, &[]), "go to *********");
+        assert!(has_contact("<a href=\"tg://user?id=1\">me</a>"));
+        assert!(!has_contact("<b>just text</b>"));
+    }
+
+    #[test]
+    fn contacts() {
+        assert!(has_contact("write @username"));
+        assert!(has_contact("mail a.b@example.org"));
+        assert!(has_contact("example.com/page"));
+        assert!(!has_contact("@me"));
+        assert!(!has_contact("end of sentence.Next"));
+    }
+
+    #[test]
+    fn phones() {
+        assert_eq!(
+            masked("call +7 (999) 123-45-67 now", false, &[]),
+            "call ****************** now"
+        );
+        assert_eq!(masked("8 999 123 45 67", false, &[]), "***************");
+        assert_eq!(
+            masked("born in 2003, 180 cm", false, &[]),
+            "born in 2003, 180 cm"
+        );
+        assert_eq!(masked("+7 999 12", false, &[]), "+7 999 12");
+    }
+}
@@ -0,0 +1,181 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use anyhow::Context;
+use entities::{notifications, sea_orm_active_enums::NotificationKind};
+use teloxide::{prelude::*, RequestError};
+use tracing::*;
+
+use crate::{datings, db::Database, text, AppError, Bot};
+
+/// Delay before the first retry, doubled on every attempt
+const BASE_RETRY_DELAY: u64 = 10;
+const MAX_RETRY_DELAY: u64 = 60 * 60;
+/// Notification is moved to the dead letters after this number of attempts
+const MAX_ATTEMPTS: i16 = 10;
+
+/// Deliver enqueued notifications to users
+pub async fn run(bot: Bot, db: Arc<Database>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(2));
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = deliver_due(&bot, &db).await {
+            sentry_anyhow::capture_anyhow(
+                &e.context("error while delivering notifications"),
+            );
+        }
+    }
+}
+
+async fn deliver_due(bot: &Bot, db: &Database) -> anyhow::Result<()> {
+    for notification in db.get_due_notifications(50).await? {
+        let result = deliver(bot, db, &notification).await;
+        process_result(db, &notification, result).await?;
+    }
+    Ok(())
+}
+
+#[instrument(level = "debug", skip(bot, db))]
+async fn deliver(
+    bot: &Bot,
+    db: &Database,
+    notification: &notifications::Model,
+) -> Result<(), AppError> {
+    let chat = ChatId(notification.user_id);
+    match notification.kind {
+        NotificationKind::Like => {
+            let dating = get_dating(db, notification).await?;
+            // Likes of shadow banned users are dropped as if delivered
+            if db.is_shadow_banned(dating.initiator_id).await? {
+                return Ok(());
+            }
+            let photos = datings::send_like_photos(
+                bot,
+                db,
+                dating.initiator_id,
+                dating.partner_id,
+            );
+            step(db, notification, 0, photos).await?;
+            let attachment = datings::send_dating_attachment(bot, &dating);
+            step(db, notification, 1, attachment).await?;
+            datings::send_like(db, bot, &dating).await?;
+        }
+        NotificationKind::MutualLike => {
+            let dating = get_dating(db, notification).await?;
+            if db.is_shadow_banned(dating.partner_id).await? {
+                return Ok(());
+            }
+            let photos = datings::send_like_photos(
+                bot,
+                db,
+                dating.partner_id,
+                dating.initiator_id,
+            );
+            step(db, notification, 0, photos).await?;
+            datings::mutual_like(bot, db, &dating).await?;
+        }
+        NotificationKind::LikeReminder
+        | NotificationKind::LikeExpired
+        | NotificationKind::ChatLeft => {
+            let notice = notice(&notification.kind)
+                .context("notification without text")?;
+            let lang = db.get_language(chat.0).await?;
+            bot.send_message(chat, notice.get(lang)).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Text of the notifications which are a single message
+const fn notice(kind: &NotificationKind) -> Option<text::Text> {
+    match kind {
+        NotificationKind::LikeReminder => Some(text::LIKE_REMINDER),
+        NotificationKind::LikeExpired => Some(text::LIKE_EXPIRED),
+        NotificationKind::ChatLeft => Some(text::CHAT_PARTNER_LEFT),
+        NotificationKind::Like | NotificationKind::MutualLike => None,
+    }
+}
+
+/// Send a part of the notification unless an earlier attempt did it, so
+/// retries don't repeat the sent parts
+async fn step(
+    db: &Database,
+    notification: &notifications::Model,
+    step: i16,
+    send: impl Future<Output = Result<(), AppError>>,
+) -> Result<(), AppError> {
+    if notification.step > step {
+        return Ok(());
+    }
+    send.await?;
+    db.set_notification_step(notification.id, step + 1).await?;
+    Ok(())
+}
+
+async fn get_dating(
+    db: &Database,
+    notification: &notifications::Model,
+) -> anyhow::Result<entities::datings::Model> {
+    db.get_dating(
+        notification.dating_id.context("notification without dating")?,
+    )
+    .await
+}
+
+async fn process_result(
+    db: &Database,
+    notification: &notifications::Model,
+    result: Result<(), AppError>,
+) -> anyhow::Result<()> {
+    let Err(error) = result else {
+        db.set_notification_delivered(notification.id).await?;
+        return Ok(());
+    };
+
+    let attempts = notification.attempts + 1;
+    let error_str = error.to_string();
+
+    match error {
+        // Blocked users were already deactivated by the bot adaptor
+        e if datings::is_unreachable(&e) => {
+            db.fail_notification(notification.id, attempts, error_str).await?;
+        }
+        _ if attempts >= MAX_ATTEMPTS => {
+            sentry_anyhow::capture_anyhow(
+                &anyhow::Error::from(error)
+                    .context("notification delivery attempts exceeded"),
+            );
+            db.fail_notification(notification.id, attempts, error_str).await?;
+        }
+        e => {
+            let mut delay = BASE_RETRY_DELAY
+                .saturating_mul(1 << (attempts - 1))
+                .min(MAX_RETRY_DELAY);
+            if let AppError::Telegram(RequestError::RetryAfter(d)) = e {
+                delay = delay.max(d.as_secs());
+            }
+            debug!("notification {} postponed for {delay}s", notification.id);
+            db.postpone_notification(
+                notification.id,
+                attempts,
+                delay,
+                error_str,
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partner_is_told_about_leaving() {
+        let key = notice(&NotificationKind::ChatLeft).map(|t| t.key);
+        assert_eq!(key, Some(text::CHAT_PARTNER_LEFT.key));
+        assert!(notice(&NotificationKind::Like).is_none());
+    }
+}
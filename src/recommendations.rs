@@ -0,0 +1,189 @@
+//! Collaborative filtering: users who liked the same people as you also
+//! liked these partners
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use entities::partner_scores;
+use serde::Deserialize;
+use tracing::*;
+
+use crate::db::Database;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecommendationsSettings {
+    /// Hours between rebuilds of the scores
+    pub rebuild_hours: u32,
+    /// Best candidates saved for every user
+    pub candidates: usize,
+    /// Weight of the score against the random order, 0 disables the scores
+    pub weight: f64,
+}
+
+impl Default for RecommendationsSettings {
+    fn default() -> Self {
+        Self { rebuild_hours: 6, candidates: 100, weight: 1.0 }
+    }
+}
+
+/// Periodically rebuild scores of the candidates
+pub async fn run(db: Arc<Database>, settings: RecommendationsSettings) {
+    if settings.weight <= 0.0 {
+        return;
+    }
+    let period = Duration::from_secs(u64::from(settings.rebuild_hours) * 3600);
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = rebuild(&db, &settings).await {
+            sentry_anyhow::capture_anyhow(
+                &e.context("error while rebuilding recommendations"),
+            );
+        }
+    }
+}
+
+#[instrument(level = "debug", skip(db))]
+async fn rebuild(
+    db: &Database,
+    settings: &RecommendationsSettings,
+) -> anyhow::Result<()> {
+    let reactions = db.get_reactions().await?;
+    let limit = settings.candidates;
+    let scores =
+        tokio::task::spawn_blocking(move || score(&reactions, limit)).await?;
+    info!(scores = scores.len(), "recommendations are rebuilt");
+    db.set_partner_scores(scores).await
+}
+
+/// Score candidates for every user who liked someone. Neighbours are users
+/// whose likes overlap with the user's ones, weighted by cosine similarity of
+/// the liked sets. Scores are scaled so the best candidate has 1.
+fn score(
+    reactions: &[(i64, i64, bool)],
+    limit: usize,
+) -> Vec<partner_scores::Model> {
+    let mut liked: HashMap<i64, HashSet<i64>> = HashMap::new();
+    let mut likers: HashMap<i64, HashSet<i64>> = HashMap::new();
+    // Partners who were already rated aren't candidates
+    let mut rated: HashMap<i64, HashSet<i64>> = HashMap::new();
+    for &(user, partner, like) in reactions {
+        rated.entry(user).or_default().insert(partner);
+        if like {
+            liked.entry(user).or_default().insert(partner);
+            likers.entry(partner).or_default().insert(user);
+        }
+    }
+
+    let mut result = Vec::new();
+    for (&user, user_liked) in &liked {
+        let mut common: HashMap<i64, u32> = HashMap::new();
+        for partner in user_liked {
+            for &neighbour in likers.get(partner).into_iter().flatten() {
+                if neighbour != user {
+                    *common.entry(neighbour).or_default() += 1;
+                }
+            }
+        }
+
+        let user_rated = rated.get(&user);
+        let mut candidates: HashMap<i64, f64> = HashMap::new();
+        for (neighbour, common) in common {
+            let Some(neighbour_liked) = liked.get(&neighbour) else {
+                continue;
+            };
+            let sizes = set_size(user_liked) * set_size(neighbour_liked);
+            let similarity = f64::from(common) / sizes.sqrt();
+            for &candidate in neighbour_liked {
+                if candidate != user
+                    && !user_rated.is_some_and(|r| r.contains(&candidate))
+                {
+                    *candidates.entry(candidate).or_default() += similarity;
+                }
+            }
+        }
+
+        let mut candidates: Vec<_> = candidates.into_iter().collect();
+        candidates.sort_unstable_by(|a, b| b.1.total_cmp(&a.1));
+        candidates.truncate(limit);
+        let Some(&(_, best)) = candidates.first() else {
+            continue;
+        };
+        result.extend(candidates.into_iter().map(|(partner_id, score)| {
+            partner_scores::Model {
+                user_id: user,
+                partner_id,
+                score: (score / best) as f32,
+            }
+        }));
+    }
+    result
+}
+
+fn set_size(set: &HashSet<i64>) -> f64 {
+    f64::from(u32::try_from(set.len()).unwrap_or(u32::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Partners of the user from the best one
+    fn scores_of(
+        scores: &[partner_scores::Model],
+        user: i64,
+    ) -> Vec<(i64, f32)> {
+        let mut result: Vec<_> = scores
+            .iter()
+            .filter(|s| s.user_id == user)
+            .map(|s| (s.partner_id, s.score))
+            .collect();
+        result.sort_unstable_by(|a, b| b.1.total_cmp(&a.1));
+        result
+    }
+
+    const REACTIONS: &[(i64, i64, bool)] = &[
+        (1, 10, true),
+        (1, 11, true),
+        (1, 14, false),
+        (2, 10, true),
+        (2, 11, true),
+        (2, 12, true),
+        (3, 10, true),
+        (3, 14, true),
+        (4, 10, true),
+        (5, 10, false),
+    ];
+
+    #[test]
+    fn rated_partners_are_skipped() {
+        let scores = score(REACTIONS, 10);
+        assert_eq!(scores_of(&scores, 1), [(12, 1.0)]);
+        // Users who haven't liked anyone have no neighbours
+        assert!(scores_of(&scores, 5).is_empty());
+        assert!(scores.iter().all(|s| s.user_id != s.partner_id));
+    }
+
+    #[test]
+    fn scores_are_weighted_by_similarity() {
+        let scores = scores_of(&score(REACTIONS, 10), 4);
+        let partners: Vec<_> = scores.iter().map(|&(p, _)| p).collect();
+        assert_eq!(partners, [11, 14, 12]);
+        assert!((scores[0].1 - 1.0).abs() < f32::EPSILON);
+        // 1/√2 of the neighbour 3 against 1/√2 + 1/√3 of 1 and 2
+        let expected = 0.5f32.sqrt() / (0.5f32.sqrt() + (1.0f32 / 3.0).sqrt());
+        assert!((scores[1].1 - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn candidates_are_limited() {
+        let scores = scores_of(&score(REACTIONS, 2), 4);
+        let partners: Vec<_> = scores.iter().map(|&(p, _)| p).collect();
+        assert_eq!(partners, [11, 14]);
+    }
+}
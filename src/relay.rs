@@ -0,0 +1,143 @@
+use anyhow::{ensure, Context};
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup},
+    RequestError,
+};
+
+use crate::{
+    callbacks::Callback,
+    db::Database,
+    text::{self, Lang},
+    Bot, State,
+};
+
+/// Start relay chat of `user` with the partner of mutual dating
+pub async fn enter(
+    db: &Database,
+    user: i64,
+    dating_id: i32,
+) -> anyhow::Result<State> {
+    let dating = db.get_dating(dating_id).await?;
+    ensure!(
+        dating.initiator_reaction == Some(true)
+            && dating.partner_reaction == Some(true),
+        "dating is not mutual"
+    );
+
+    let partner = if dating.initiator_id == user {
+        dating.partner_id
+    } else if dating.partner_id == user {
+        dating.initiator_id
+    } else {
+        anyhow::bail!("user is not a participant of the dating");
+    };
+    let name = db.get_user(partner).await?.context("partner not found")?.name;
+
+    Ok(State::Chatting { dating_id, partner, name })
+}
+
+/// Copy user's message to the chat partner
+pub async fn relay_message(
+    bot: &Bot,
+    db: &Database,
+    dating_id: i32,
+    partner: i64,
+    msg: &Message,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let sender =
+        db.get_user(msg.chat.id.0).await?.context("sender not found")?;
+    // Shadow banned users don't know that their messages are dropped
+    if sender.shadow_banned_at.is_some() {
+        return Ok(());
+    }
+    let partner_lang = db.get_language(partner).await?;
+
+    let markup = InlineKeyboardMarkup::new([[InlineKeyboardButton::callback(
+        text::REPLY_TO.fill(partner_lang, &[&sender.name]),
+        Callback::Chat { dating_id }.to_string(),
+    )]]);
+
+    match bot
+        .copy_message(ChatId(partner), msg.chat.id, msg.id)
+        .reply_markup(markup)
+        .await
+    {
+        Ok(_) => {}
+        Err(RequestError::Api(e)) => {
+            tracing::warn!("can't relay message to {partner}: {e}");
+            bot.send_message(msg.chat.id, text::CHAT_NOT_DELIVERED.get(lang))
+                .await?;
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    Ok(())
+}
+
+/// Save user's consent to reveal contacts and exchange them if it's mutual.
+/// Repeated consent only reminds the user of the state of the exchange.
+pub async fn reveal(
+    bot: &Bot,
+    db: &Database,
+    user: i64,
+    dating_id: i32,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let Some(dating) = db.reveal_contact(dating_id, user).await? else {
+        let dating = db.get_dating(dating_id).await?;
+        let notice = if dating.initiator_reveal && dating.partner_reveal {
+            text::CONTACT_ALREADY_REVEALED
+        } else {
+            text::REVEAL_ALREADY_REQUESTED
+        };
+        bot.send_message(ChatId(user), notice.get(lang)).await?;
+        return Ok(());
+    };
+    let partner = if dating.initiator_id == user {
+        dating.partner_id
+    } else {
+        dating.initiator_id
+    };
+
+    if !(dating.initiator_reveal && dating.partner_reveal) {
+        let partner_lang = db.get_language(partner).await?;
+        bot.send_message(ChatId(user), text::REVEAL_REQUESTED.get(lang))
+            .await?;
+        bot.send_message(
+            ChatId(partner),
+            text::REVEAL_PROPOSED.get(partner_lang),
+        )
+        .reply_markup(InlineKeyboardMarkup::new([[
+            InlineKeyboardButton::callback(
+                text::START_CHAT.get(partner_lang),
+                Callback::Chat { dating_id }.to_string(),
+            ),
+        ]]))
+        .await?;
+        return Ok(());
+    }
+
+    for (to, about) in [(user, partner), (partner, user)] {
+        let lang = db.get_language(to).await?;
+        match crate::utils::user_url(bot, about).await? {
+            Some(url) => {
+                bot.send_message(ChatId(to), text::CONTACT_REVEALED.get(lang))
+                    .reply_markup(InlineKeyboardMarkup::new([[
+                        InlineKeyboardButton::url(
+                            text::OPEN_CHAT.get(lang),
+                            url,
+                        ),
+                    ]]))
+                    .await?;
+            }
+            None => {
+                bot.send_message(ChatId(to), text::CONTACT_HIDDEN.get(lang))
+                    .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
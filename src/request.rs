@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use anyhow::Context;
 use itertools::Itertools;
 use teloxide::{
@@ -9,15 +11,22 @@ use teloxide::{
 };
 
 use crate::{
-    cities, text,
+    cities,
+    text::{self, Lang, Text},
     types::{DatingPurpose, Subjects},
     utils, Bot, EditProfile,
 };
 
+/// Prompt of the profile wizard, it can differ between experiment arms
+fn prompt(bot: &Bot, chat: &Chat, text: Text, lang: Lang) -> Cow<'static, str> {
+    bot.config().experiments.text(text, lang, chat.id.0)
+}
+
 pub async fn set_location_filter(
     bot: &Bot,
     chat: &Chat,
     p: &EditProfile,
+    lang: Lang,
 ) -> anyhow::Result<()> {
     let id = p
         .city
@@ -35,33 +44,43 @@ pub async fn set_location_filter(
 
     let keyboard = vec![
         vec![
-            KeyboardButton::new("Вся Россия".to_owned()),
-            KeyboardButton::new(format!(
-                "{} ФО",
-                cities::county_by_id(id).context("county not found")?
-            )),
+            KeyboardButton::new(text::LOCATION_COUNTRY.get(lang)),
+            KeyboardButton::new(text::COUNTY.fill(lang, &[
+                &cities::county_by_id(id).context("county not found")?,
+            ])),
         ],
         subject_city,
     ];
     let keyboard_markup = KeyboardMarkup::new(keyboard).resize_keyboard(true);
 
-    bot.send_message(chat.id, text::EDIT_LOCATION_FILTER)
-        .reply_markup(keyboard_markup)
-        .await?;
+    bot.send_message(
+        chat.id,
+        prompt(bot, chat, text::EDIT_LOCATION_FILTER, lang),
+    )
+    .reply_markup(keyboard_markup)
+    .await?;
 
     Ok(())
 }
 
-pub async fn set_city(bot: &Bot, chat: &Chat) -> anyhow::Result<()> {
-    let keyboard = vec![vec![KeyboardButton::new("Не указывать")]];
+pub async fn set_city(
+    bot: &Bot,
+    chat: &Chat,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let keyboard = vec![vec![KeyboardButton::new(text::SKIP_CITY.get(lang))]];
     let keyboard_markup = KeyboardMarkup::new(keyboard).resize_keyboard(true);
-    bot.send_message(chat.id, text::REQUEST_CITY)
+    bot.send_message(chat.id, prompt(bot, chat, text::REQUEST_CITY, lang))
         .reply_markup(keyboard_markup)
         .await?;
     Ok(())
 }
 
-pub async fn set_name(bot: &Bot, chat: &Chat) -> anyhow::Result<()> {
+pub async fn set_name(
+    bot: &Bot,
+    chat: &Chat,
+    lang: Lang,
+) -> anyhow::Result<()> {
     match &chat.kind {
         ChatKind::Public(_) => anyhow::bail!("chat isn't private"),
         ChatKind::Private(p) => match &p.first_name {
@@ -69,55 +88,77 @@ pub async fn set_name(bot: &Bot, chat: &Chat) -> anyhow::Result<()> {
                 let keyboard = vec![vec![KeyboardButton::new(n)]];
                 let keyboard_markup =
                     KeyboardMarkup::new(keyboard).resize_keyboard(true);
-                bot.send_message(chat.id, text::REQUEST_NAME)
-                    .reply_markup(keyboard_markup)
-                    .await?;
+                bot.send_message(
+                    chat.id,
+                    prompt(bot, chat, text::REQUEST_NAME, lang),
+                )
+                .reply_markup(keyboard_markup)
+                .await?;
                 Ok(())
             }
             None => {
-                bot.send_message(chat.id, text::REQUEST_NAME).await?;
+                bot.send_message(
+                    chat.id,
+                    prompt(bot, chat, text::REQUEST_NAME, lang),
+                )
+                .await?;
                 Ok(())
             }
         },
     }
 }
 
-pub async fn set_gender(bot: &Bot, chat: &Chat) -> anyhow::Result<()> {
+pub async fn set_gender(
+    bot: &Bot,
+    chat: &Chat,
+    lang: Lang,
+) -> anyhow::Result<()> {
     let keyboard = vec![vec![
-        KeyboardButton::new(text::GENDER_MALE),
-        KeyboardButton::new(text::GENDER_FEMALE),
+        KeyboardButton::new(text::GENDER_MALE.get(lang)),
+        KeyboardButton::new(text::GENDER_FEMALE.get(lang)),
     ]];
     let keyboard_markup = KeyboardMarkup::new(keyboard).resize_keyboard(true);
 
-    bot.send_message(chat.id, text::REQUEST_GENDER)
+    bot.send_message(chat.id, prompt(bot, chat, text::REQUEST_GENDER, lang))
         .reply_markup(keyboard_markup)
         .await?;
     Ok(())
 }
 
-pub async fn set_gender_filter(bot: &Bot, chat: &Chat) -> anyhow::Result<()> {
+pub async fn set_gender_filter(
+    bot: &Bot,
+    chat: &Chat,
+    lang: Lang,
+) -> anyhow::Result<()> {
     let keyboard = vec![
         vec![
-            KeyboardButton::new(text::GENDER_FILTER_MALE),
-            KeyboardButton::new(text::GENDER_FILTER_FEMALE),
+            KeyboardButton::new(text::GENDER_FILTER_MALE.get(lang)),
+            KeyboardButton::new(text::GENDER_FILTER_FEMALE.get(lang)),
         ],
-        vec![KeyboardButton::new(text::GENDER_FILTER_ANY)],
+        vec![KeyboardButton::new(text::GENDER_FILTER_ANY.get(lang))],
     ];
     let keyboard_markup = KeyboardMarkup::new(keyboard).resize_keyboard(true);
 
-    bot.send_message(chat.id, text::REQUEST_GENDER_FILTER)
-        .reply_markup(keyboard_markup)
-        .await?;
+    bot.send_message(
+        chat.id,
+        prompt(bot, chat, text::REQUEST_GENDER_FILTER, lang),
+    )
+    .reply_markup(keyboard_markup)
+    .await?;
     Ok(())
 }
 
-pub async fn set_grade(bot: &Bot, chat: &Chat) -> anyhow::Result<()> {
+pub async fn set_grade(
+    bot: &Bot,
+    chat: &Chat,
+    lang: Lang,
+) -> anyhow::Result<()> {
     // let keyboard =
     //     (6..=11).map(|n| KeyboardButton::new(n.to_string())).chunks(3);
     // let keyboard_markup =
     //     KeyboardMarkup::new(keyboard.into_iter()).resize_keyboard(true);
 
-    bot.send_message(chat.id, text::REQUEST_GRADE)
+    bot.send_message(chat.id, prompt(bot, chat, text::REQUEST_GRADE, lang))
         .reply_markup(KeyboardRemove::new())
         .await?;
     Ok(())
@@ -127,8 +168,9 @@ pub async fn set_subjects(
     bot: &Bot,
     chat: &Chat,
     p: &EditProfile,
+    lang: Lang,
 ) -> anyhow::Result<()> {
-    bot.send_message(chat.id, text::EDIT_SUBJECTS)
+    bot.send_message(chat.id, prompt(bot, chat, text::EDIT_SUBJECTS, lang))
         .reply_markup(utils::make_subjects_keyboard(
             match p.subjects {
                 Some(s) => Subjects::from_bits(s)
@@ -136,6 +178,7 @@ pub async fn set_subjects(
                 None => Subjects::default(),
             },
             &utils::SubjectsKeyboardType::User,
+            lang,
         ))
         .await?;
     Ok(())
@@ -145,15 +188,20 @@ pub async fn set_dating_purpose(
     bot: &Bot,
     chat: &Chat,
     p: &EditProfile,
+    lang: Lang,
 ) -> anyhow::Result<()> {
-    bot.send_message(chat.id, text::REQUEST_SET_DATING_PURPOSE)
-        .reply_markup(utils::make_dating_purpose_keyboard(
-            match p.dating_purpose {
-                Some(d) => DatingPurpose::try_from(d)?,
-                None => DatingPurpose::default(),
-            },
-        ))
-        .await?;
+    bot.send_message(
+        chat.id,
+        prompt(bot, chat, text::REQUEST_SET_DATING_PURPOSE, lang),
+    )
+    .reply_markup(utils::make_dating_purpose_keyboard(
+        match p.dating_purpose {
+            Some(d) => DatingPurpose::try_from(d)?,
+            None => DatingPurpose::default(),
+        },
+        lang,
+    ))
+    .await?;
     Ok(())
 }
 
@@ -161,47 +209,117 @@ pub async fn set_subjects_filter(
     bot: &Bot,
     chat: &Chat,
     p: &EditProfile,
+    lang: Lang,
 ) -> anyhow::Result<()> {
-    bot.send_message(chat.id, text::EDIT_PARTNER_SUBJECTS)
-        .reply_markup(utils::make_subjects_keyboard(
-            match p.subjects_filter {
-                Some(s) => Subjects::from_bits(s)
-                    .context("subjects filter must be created")?,
-                None => Subjects::default(),
-            },
-            &utils::SubjectsKeyboardType::Partner,
-        ))
-        .await?;
+    bot.send_message(
+        chat.id,
+        prompt(bot, chat, text::EDIT_PARTNER_SUBJECTS, lang),
+    )
+    .reply_markup(utils::make_subjects_keyboard(
+        match p.subjects_filter {
+            Some(s) => Subjects::from_bits(s)
+                .context("subjects filter must be created")?,
+            None => Subjects::default(),
+        },
+        &utils::SubjectsKeyboardType::Partner,
+        lang,
+    ))
+    .await?;
     Ok(())
 }
 
-pub async fn set_about(bot: &Bot, chat: &Chat) -> anyhow::Result<()> {
-    bot.send_message(chat.id, text::EDIT_ABOUT)
+pub async fn set_about(
+    bot: &Bot,
+    chat: &Chat,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    bot.send_message(chat.id, prompt(bot, chat, text::EDIT_ABOUT, lang))
         .reply_markup(KeyboardRemove::new())
         .await?;
     Ok(())
 }
 
-pub async fn set_photos(bot: &Bot, chat: &Chat) -> anyhow::Result<()> {
-    let keyboard = vec![vec![KeyboardButton::new("Без фото")]];
+/// New profiles can go without photos, photos added to the existing ones are
+/// saved instead
+pub async fn set_photos(
+    bot: &Bot,
+    chat: &Chat,
+    create_new: bool,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let button = if create_new { text::NO_PHOTOS } else { text::SAVE_PHOTOS };
+    let keyboard = vec![vec![KeyboardButton::new(button.get(lang))]];
     let keyboard_markup = KeyboardMarkup::new(keyboard).resize_keyboard(true);
-    bot.send_message(chat.id, text::REQUEST_SET_PHOTOS)
+    bot.send_message(
+        chat.id,
+        prompt(bot, chat, text::REQUEST_SET_PHOTOS, lang),
+    )
+    .reply_markup(keyboard_markup)
+    .await?;
+    Ok(())
+}
+
+pub async fn manage_photos(
+    bot: &Bot,
+    chat: &Chat,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let keyboard = vec![vec![
+        KeyboardButton::new(text::ADD_PHOTOS.get(lang)),
+        KeyboardButton::new(text::PHOTOS_DONE.get(lang)),
+    ]];
+    let keyboard_markup = KeyboardMarkup::new(keyboard).resize_keyboard(true);
+    bot.send_message(chat.id, text::MANAGE_PHOTOS.get(lang))
         .reply_markup(keyboard_markup)
         .await?;
     Ok(())
 }
 
-pub async fn edit_profile(bot: &Bot, chat: &Chat) -> anyhow::Result<()> {
-    let keyboard: Vec<Vec<_>> =
-        ["Имя", "Предметы", "О себе", "Город", "Фото", "Отмена"]
-            .into_iter()
-            .map(|i| InlineKeyboardButton::callback(i, format!("e{i}")))
-            .chunks(3)
-            .into_iter()
-            .map(|row| row.collect())
-            .collect();
-
-    bot.send_message(chat.id, text::REQUEST_EDIT)
+pub async fn chatting(
+    bot: &Bot,
+    chat: &Chat,
+    name: &str,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    bot.send_message(
+        chat.id,
+        format!(
+            "{}\n\n{}",
+            text::CHATTING_WITH.fill(lang, &[&name]),
+            text::CHAT_STARTED.get(lang)
+        ),
+    )
+    .reply_markup(KeyboardRemove::new())
+    .await?;
+    Ok(())
+}
+
+/// Labels of profile edit buttons with their callback data
+pub const EDIT_OPTIONS: [(Text, &str); 6] = [
+    (text::EDIT_NAME, "name"),
+    (text::EDIT_SUBJECTS_BUTTON, "subjects"),
+    (text::EDIT_ABOUT_BUTTON, "about"),
+    (text::EDIT_CITY, "city"),
+    (text::EDIT_PHOTOS, "photos"),
+    (text::CANCEL, "cancel"),
+];
+
+pub async fn edit_profile(
+    bot: &Bot,
+    chat: &Chat,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let keyboard: Vec<Vec<_>> = EDIT_OPTIONS
+        .into_iter()
+        .map(|(label, data)| {
+            InlineKeyboardButton::callback(label.get(lang), format!("e{data}"))
+        })
+        .chunks(3)
+        .into_iter()
+        .map(|row| row.collect())
+        .collect();
+
+    bot.send_message(chat.id, text::REQUEST_EDIT.get(lang))
         .reply_markup(InlineKeyboardMarkup::new(keyboard))
         .await?;
     Ok(())
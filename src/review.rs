@@ -0,0 +1,128 @@
+//! Pre-moderation of profiles in the review chat, see
+//! [`crate::moderation::ModerationSettings::review_chat`]
+
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode},
+};
+
+use crate::{
+    callbacks::{Callback, ReviewCode},
+    db::Database,
+    text::{self, Lang},
+    types::PublicProfile,
+    Bot,
+};
+
+/// Hide the profile which isn't complete yet, it's sent to the review chat by
+/// [`submit`]
+pub async fn hold(db: &Database, bot: &Bot, user: i64) -> anyhow::Result<()> {
+    if bot.config().moderation.review_chat.is_some() {
+        db.hold_for_review(user).await?;
+    }
+    Ok(())
+}
+
+/// Hide the new or changed profile and send it to the review chat
+pub async fn submit(
+    db: &Database,
+    bot: &Bot,
+    user: i64,
+    lang: Lang,
+) -> anyhow::Result<()> {
+    let Some(chat) = bot.config().moderation.review_chat else {
+        return Ok(());
+    };
+    let version = db.hold_for_review(user).await?;
+    let profile =
+        db.get_user(user).await?.context("user to review not found")?;
+    let profile: PublicProfile = (&profile).try_into()?;
+
+    // Moderators read the profile in Russian like the admin commands
+    crate::datings::send_user_photos(bot, db, user, chat, Lang::Ru).await?;
+    let button = |label: &str, code| {
+        InlineKeyboardButton::callback(
+            label,
+            Callback::Review { user_id: user, version, code }.to_string(),
+        )
+    };
+    let keyboard = InlineKeyboardMarkup::new([
+        vec![button("✅ Одобрить", ReviewCode::Approve)],
+        vec![
+            button("🔤 Имя", ReviewCode::RejectName),
+            button("📝 Описание", ReviewCode::RejectAbout),
+        ],
+        vec![
+            button("🖼 Фото", ReviewCode::RejectPhotos),
+            button("🚫 Другое", ReviewCode::RejectOther),
+        ],
+    ]);
+    bot.send_message(
+        ChatId(chat),
+        format!(
+            "Анкета {user} на проверке, нижние кнопки отклоняют её с \
+             причиной\n\n{}",
+            profile.describe(Lang::Ru)
+        ),
+    )
+    .parse_mode(ParseMode::Html)
+    .reply_markup(keyboard)
+    .await?;
+
+    bot.send_message(ChatId(user), text::PROFILE_ON_REVIEW.get(lang)).await?;
+    Ok(())
+}
+
+/// Save the decision of a member of the review chat and notify the user
+#[tracing::instrument(err, skip(db, bot))]
+pub async fn handle_callback(
+    db: Arc<Database>,
+    bot: Bot,
+    q: CallbackQuery,
+) -> anyhow::Result<()> {
+    let (Some(data), Some(msg)) = (&q.data, &q.message) else {
+        return Ok(());
+    };
+    let Callback::Review { user_id, version, code } = data.parse()? else {
+        bail!("wrong callback type");
+    };
+    let approved = code == ReviewCode::Approve;
+    let saved = db.set_review_status(user_id, version, approved).await?;
+    // The buttons stay for another try if the decision isn't saved
+    bot.edit_message_reply_markup(msg.chat.id, msg.id).await?;
+    if !saved {
+        bot.answer_callback_query(&q.id)
+            .text("Анкета уже проверена или изменилась")
+            .await?;
+        return Ok(());
+    }
+    let verdict =
+        if approved { "одобрена" } else { "отклонена" };
+    bot.send_message(
+        msg.chat.id,
+        format!("Анкета {user_id} {verdict}, {}", q.from.full_name()),
+    )
+    .reply_to_message_id(msg.id)
+    .await?;
+    bot.answer_callback_query(&q.id).await?;
+
+    let lang = db.get_language(user_id).await?;
+    let reason = match code {
+        ReviewCode::Approve => None,
+        ReviewCode::RejectName => Some(text::REJECT_REASON_NAME),
+        ReviewCode::RejectAbout => Some(text::REJECT_REASON_ABOUT),
+        ReviewCode::RejectPhotos => Some(text::REJECT_REASON_PHOTOS),
+        ReviewCode::RejectOther => Some(text::REJECT_REASON_OTHER),
+    };
+    let notice = reason.map_or_else(
+        || text::PROFILE_APPROVED.get(lang).into_owned(),
+        |r| text::PROFILE_REJECTED.fill(lang, &[&r.get(lang)]),
+    );
+    if let Err(e) = bot.send_message(ChatId(user_id), notice).await {
+        tracing::warn!("can't notify {user_id} about the review: {e}");
+    }
+    Ok(())
+}
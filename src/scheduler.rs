@@ -0,0 +1,93 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use serde::Deserialize;
+use teloxide::{prelude::*, types::MessageId};
+use tracing::*;
+
+use crate::{db::Database, Bot};
+
+/// Settings of unanswered likes processing
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LikesSettings {
+    /// Hours after which partner is reminded about pending like
+    pub remind_after: u32,
+    /// Hours after which pending like is marked expired
+    pub expire_after: u32,
+    /// Tell initiator that partner didn't respond to the like
+    pub notify_expired: bool,
+}
+
+impl Default for LikesSettings {
+    fn default() -> Self {
+        Self { remind_after: 24, expire_after: 24 * 7, notify_expired: false }
+    }
+}
+
+/// Periodically process unanswered likes
+pub async fn run(bot: Bot, db: Arc<Database>, settings: LikesSettings) {
+    let mut interval = tokio::time::interval(Duration::from_secs(30));
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = remind_pending_likes(&db, &settings).await {
+            sentry_anyhow::capture_anyhow(
+                &e.context("error while reminding about pending likes"),
+            );
+        }
+        if let Err(e) = expire_pending_likes(&bot, &db, &settings).await {
+            sentry_anyhow::capture_anyhow(
+                &e.context("error while expiring pending likes"),
+            );
+        }
+    }
+}
+
+#[instrument(level = "debug", skip(db))]
+async fn remind_pending_likes(
+    db: &Database,
+    settings: &LikesSettings,
+) -> anyhow::Result<()> {
+    let likes =
+        db.get_pending_likes(settings.remind_after, Some(false)).await?;
+
+    // Remind every partner only once regardless of likes count
+    let mut partners: HashMap<i64, Vec<i32>> = HashMap::new();
+    for like in likes {
+        partners.entry(like.partner_id).or_default().push(like.id);
+    }
+
+    for (partner, datings) in partners {
+        db.remind_about_likes(partner, datings).await?;
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip(bot, db))]
+async fn expire_pending_likes(
+    bot: &Bot,
+    db: &Database,
+    settings: &LikesSettings,
+) -> anyhow::Result<()> {
+    let likes = db.get_pending_likes(settings.expire_after, None).await?;
+
+    for like in likes {
+        db.expire_like(&like, settings.notify_expired).await?;
+
+        // Remove stale reaction buttons
+        if let Some(msg) = like.partner_msg_id {
+            if let Err(e) = bot
+                .edit_message_reply_markup(
+                    ChatId(like.partner_id),
+                    MessageId(msg),
+                )
+                .await
+            {
+                warn!("can't remove buttons of expired like: {e}");
+            }
+        }
+    }
+
+    Ok(())
+}
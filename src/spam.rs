@@ -0,0 +1,210 @@
+//! Heuristics of spam and bot accounts, suspicious users are shadow banned:
+//! they keep using the bot, but aren't recommended and their likes and
+//! messages aren't delivered
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use chrono::{Duration, NaiveDateTime};
+use entities::sea_orm_active_enums::EventKind;
+use serde::Deserialize;
+
+use crate::{db::Database, Bot};
+
+/// Score of a link or a contact in the profile description
+const ABOUT_CONTACT_WEIGHT: f64 = 0.6;
+/// Score of a link or a contact in a message sent with a like
+const MESSAGE_CONTACT_WEIGHT: f64 = 0.5;
+/// Score of rating profiles faster than a human reads them
+const FAST_REACTIONS_WEIGHT: f64 = 0.5;
+/// Score of the same message sent to several partners
+const DUPLICATE_MESSAGES_WEIGHT: f64 = 0.6;
+/// Signals of new accounts are multiplied by this
+const NEW_ACCOUNT_FACTOR: f64 = 1.5;
+
+/// Like messages which are checked
+const CHECKED_MESSAGES: u64 = 20;
+/// Reactions during this number of hours are checked
+const REACTIONS_HOURS: u32 = 1;
+/// Timing of fewer reactions says nothing
+const MIN_REACTIONS: usize = 10;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpamSettings {
+    /// Users with at least this score are shadow banned, 0 disables the
+    /// checks
+    pub threshold: f64,
+    /// Median time between reactions which is too fast for a human
+    pub fast_reaction_secs: u32,
+    /// Identical like messages to different partners
+    pub duplicate_messages: usize,
+    /// Accounts are new during this number of hours
+    pub new_account_hours: u32,
+}
+
+impl Default for SpamSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 1.0,
+            fast_reaction_secs: 3,
+            duplicate_messages: 3,
+            new_account_hours: 24,
+        }
+    }
+}
+
+/// What is known about the user for the spam check
+struct Signals {
+    /// HTML of the profile description
+    about: String,
+    /// Like messages which had contacts before they were moderated
+    like_contacts: u64,
+    /// Recent like messages after moderation
+    messages: Vec<String>,
+    /// Times of the recent reactions, oldest first
+    reactions: Vec<NaiveDateTime>,
+    account_age: Duration,
+}
+
+/// Score the user and shadow ban them if it's too high. It's called when the
+/// user changes the description or likes someone.
+pub async fn check(
+    db: &Database,
+    bot: &Bot,
+    user_id: i64,
+) -> anyhow::Result<()> {
+    let settings = &bot.config().spam;
+    if settings.threshold <= 0.0 {
+        return Ok(());
+    }
+    let user =
+        db.get_user(user_id).await?.context("user to check not found")?;
+    if user.shadow_banned_at.is_some() {
+        return Ok(());
+    }
+
+    let signals = Signals {
+        about: user.about,
+        like_contacts: db.count_events(user_id, EventKind::LikeContact).await?,
+        messages: db.get_like_messages(user_id, CHECKED_MESSAGES).await?,
+        reactions: db
+            .get_recent_reactions(user_id, REACTIONS_HOURS)
+            .await?
+            .into_iter()
+            .map(|(created, _)| created)
+            .collect(),
+        account_age: db.get_account_age(user_id).await?,
+    };
+    let (score, reasons) = score(settings, &signals);
+
+    if score >= settings.threshold {
+        let details = format!("оценка {score:.2}: {}", reasons.join(", "));
+        if db.shadow_ban(user_id, details.clone()).await? {
+            tracing::info!("user {user_id} is shadow banned: {details}");
+        }
+    }
+    Ok(())
+}
+
+/// Score of the signals and their descriptions for admins
+fn score(settings: &SpamSettings, signals: &Signals) -> (f64, Vec<String>) {
+    let mut score = 0.0;
+    let mut reasons = Vec::new();
+    if crate::moderation::has_contact(&signals.about) {
+        score += ABOUT_CONTACT_WEIGHT;
+        reasons.push("ссылка или контакт в анкете".to_owned());
+    }
+
+    // Masked contacts aren't in the messages, so they're counted by events
+    let messages = &signals.messages;
+    if signals.like_contacts > 0
+        || messages.iter().any(|m| crate::moderation::has_contact(m))
+    {
+        score += MESSAGE_CONTACT_WEIGHT;
+        reasons.push("ссылка или контакт в сообщении".to_owned());
+    }
+    let repeats = max_repeats(messages);
+    if settings.duplicate_messages > 0 && repeats >= settings.duplicate_messages
+    {
+        score += DUPLICATE_MESSAGES_WEIGHT;
+        reasons.push(format!("одно сообщение {repeats} раз"));
+    }
+
+    let reactions = &signals.reactions;
+    let mut gaps: Vec<_> = reactions.windows(2).map(|w| w[1] - w[0]).collect();
+    gaps.sort_unstable();
+    if let Some(&median) = gaps.get(gaps.len() / 2) {
+        if reactions.len() >= MIN_REACTIONS
+            && median < Duration::seconds(settings.fast_reaction_secs.into())
+        {
+            score += FAST_REACTIONS_WEIGHT;
+            reasons.push(format!(
+                "{} реакций, медиана {} с",
+                reactions.len(),
+                median.num_seconds()
+            ));
+        }
+    }
+
+    let age = signals.account_age;
+    if age < Duration::hours(settings.new_account_hours.into()) {
+        score *= NEW_ACCOUNT_FACTOR;
+        reasons.push(format!("анкете {} ч", age.num_hours()));
+    }
+    (score, reasons)
+}
+
+/// Occurrences of the most repeated message, case and spaces are ignored
+fn max_repeats(messages: &[String]) -> usize {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for message in messages {
+        let normalized = message
+            .to_lowercase()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        *counts.entry(normalized).or_default() += 1;
+    }
+    counts.into_values().max().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signals(messages: &[&str], like_contacts: u64) -> Signals {
+        Signals {
+            about: String::new(),
+            like_contacts,
+            messages: messages.iter().map(|&m| m.to_owned()).collect(),
+            reactions: Vec::new(),
+            account_age: Duration::days(30),
+        }
+    }
+
+    #[test]
+    fn masked_contacts_count() {
+        let settings = SpamSettings::default();
+        // The message as it's saved with the default masking policy
+        let masked = signals(&["write me *********"], 1);
+        let (total, reasons) = score(&settings, &masked);
+        assert!((total - MESSAGE_CONTACT_WEIGHT).abs() < f64::EPSILON);
+        assert_eq!(reasons, ["ссылка или контакт в сообщении"]);
+
+        let (total, _) = score(&settings, &signals(&["hello"], 0));
+        assert!(total.abs() < f64::EPSILON);
+        let (total, _) = score(&settings, &signals(&["t.me/spam"], 0));
+        assert!((total - MESSAGE_CONTACT_WEIGHT).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn new_accounts_are_suspicious() {
+        let settings = SpamSettings::default();
+        let mut new = signals(&["write me *********"], 1);
+        new.account_age = Duration::hours(1);
+        let (total, _) = score(&settings, &new);
+        let expected = MESSAGE_CONTACT_WEIGHT * NEW_ACCOUNT_FACTOR;
+        assert!((total - expected).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,150 @@
+use std::{collections::HashSet, path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::{bail, Context};
+use entities::{images, sea_orm_active_enums::ImageKind};
+use object_store::{
+    aws::AmazonS3Builder, local::LocalFileSystem, path::Path,
+    prefix::PrefixStore, ObjectStore,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use teloxide::prelude::*;
+use tracing::*;
+
+use crate::{db::Database, Bot};
+
+/// Archive of profile media used to restore invalidated Telegram file ids
+pub struct Storage {
+    store: Box<dyn ObjectStore>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    Local,
+    /// S3-compatible bucket configured by `AWS_*` variables
+    S3,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageSettings {
+    pub backend: StorageBackend,
+    /// Directory of the local storage
+    pub path: PathBuf,
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        Self { backend: StorageBackend::Local, path: PathBuf::from("media") }
+    }
+}
+
+impl Storage {
+    pub fn new(settings: &StorageSettings) -> anyhow::Result<Self> {
+        let store: Box<dyn ObjectStore> = match settings.backend {
+            StorageBackend::S3 => Box::new(PrefixStore::new(
+                AmazonS3Builder::from_env()
+                    .build()
+                    .context("can't configure S3 storage")?,
+                "media",
+            )),
+            StorageBackend::Local => {
+                std::fs::create_dir_all(&settings.path)?;
+                Box::new(LocalFileSystem::new_with_prefix(&settings.path)?)
+            }
+        };
+        Ok(Self { store })
+    }
+
+    /// Download the file from Telegram and save it, returns its checksum
+    /// and content
+    pub async fn archive(
+        &self,
+        bot: &Bot,
+        file_id: &str,
+    ) -> anyhow::Result<(String, Vec<u8>)> {
+        let file = bot.get_file(file_id).await?;
+        let mut data = Vec::new();
+        bot.download_file(&file.path, &mut data).await?;
+
+        let checksum = hex::encode(Sha256::digest(&data));
+        self.store
+            .put(&Path::from(checksum.as_str()), data.clone().into())
+            .await?;
+        Ok((checksum, data))
+    }
+
+    /// Load archived file ensuring it's not corrupted
+    pub async fn load(&self, checksum: &str) -> anyhow::Result<Vec<u8>> {
+        let file = self.store.get(&Path::from(checksum)).await?;
+        let data = file.bytes().await?.to_vec();
+        if hex::encode(Sha256::digest(&data)) != checksum {
+            bail!("checksum mismatch of archived file {checksum}");
+        }
+        Ok(data)
+    }
+}
+
+/// Periodically archive newly uploaded media
+pub async fn run(bot: Bot, db: Arc<Database>) {
+    // Images which can't be archived are skipped until restart
+    let mut failed = HashSet::new();
+    let mut interval = tokio::time::interval(Duration::from_secs(30));
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = archive_new_images(&bot, &db, &mut failed).await {
+            sentry_anyhow::capture_anyhow(
+                &e.context("error while archiving media"),
+            );
+        }
+    }
+}
+
+async fn archive_new_images(
+    bot: &Bot,
+    db: &Database,
+    failed: &mut HashSet<i32>,
+) -> anyhow::Result<()> {
+    let images = db.get_unarchived_images(20, failed).await?;
+    for image in images {
+        match bot.storage().archive(bot, &image.telegram_id).await {
+            Ok((checksum, data)) => {
+                db.set_image_checksum(image.id, checksum).await?;
+                if image.kind == ImageKind::Image {
+                    check_photo(db, &image, data).await?;
+                }
+            }
+            // e.g. files larger than the download limit
+            Err(e) => {
+                warn!("can't archive image {}: {e}", image.id);
+                failed.insert(image.id);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Hash the archived photo and flag the user if it looks like a photo of
+/// another user
+async fn check_photo(
+    db: &Database,
+    image: &images::Model,
+    data: Vec<u8>,
+) -> anyhow::Result<()> {
+    let hash = tokio::task::spawn_blocking(move || {
+        crate::moderation::photo_hash(&data)
+    })
+    .await?;
+    let hash = match hash {
+        Ok(hash) => hash,
+        Err(e) => {
+            warn!("can't hash image {}: {e}", image.id);
+            return Ok(());
+        }
+    };
+    db.set_image_phash(image.id, hash).await?;
+    crate::moderation::check_duplicate_photo(db, image.user_id, hash).await
+}
@@ -1,90 +1,903 @@
-pub const REQUEST_NAME: &str = "Как вас называть?";
-
-pub const REQUEST_GENDER: &str = "Теперь выберите ваш пол";
-pub const GENDER_MALE: &str = "Я парень";
-pub const GENDER_FEMALE: &str = "Я девушка";
-
-pub const REQUEST_GENDER_FILTER: &str = "Кого вы хотите заботать?";
-pub const GENDER_FILTER_MALE: &str = "Парня";
-pub const GENDER_FILTER_FEMALE: &str = "Девушку";
-pub const GENDER_FILTER_ANY: &str = "Не важно";
-
-pub const REQUEST_GRADE: &str = "В каком вы сейчас классе?\n Примечание: если \
-                                 вы, например, окончили 9-ый класс, но ещё не \
-                                 поступили в 10-ый - вы в 9-ом.";
-
-pub const EDIT_SUBJECTS: &str = "Какие предметы вы ботаете? Нажмите на \
-                                 предмет, чтобы добавить или убрать его.";
-
-pub const EDIT_PARTNER_SUBJECTS: &str =
-    "Выберите предметы, хотя бы один из которых должен ботать тот, кого вы \
-     ищете. Нажмите на предмет, чтобы добавить или убрать его.";
-
-pub const EDIT_ABOUT: &str =
-    "Расскажите о себе: чем занимаетесь, кого хотите найти";
-pub const SUBJECTS_CONTINUE: &str = "Продолжить";
-pub const SUBJECTS_PARTNER_EMPTY: &str = "Не важно";
-pub const SUBJECTS_USER_EMPTY: &str = "Никакие";
-
-pub const EDIT_LOCATION_FILTER: &str =
-    "Где вы хотите искать людей?\nПо всей стране, в своём федеральном округе, \
-     в своём субъекте федерации или только в своём городе?";
-
-pub const NO_CITY: &str =
-    "Так как вы не указали свой город, мы будем искать людей по всей России";
-
-pub const REQUEST_SET_PHOTOS: &str = "Отправьте парочку своих фото или видео";
-
-pub const PROFILE_CREATION_STARTED: &str =
-    "Начинаем создавать анкету, это не займёт у вас много времени.\nНе \
-     волнуйтесь, если где-то ошибётесь: вы можете изменить её после \
-     регистрации командой /edit.";
-
-pub const REQUEST_CITY: &str =
-    "Напишите название города, в котором вы живёте. Система найдёт город с \
-     наиболее похожим названием и попросит подтвердить.\n\nСовет: даже если \
-     вы живёте в очень маленьком городе, всё равно лучше указать именно его: \
-     на следующем шаге вы сможете выбрать, что ищете людей в своей области \
-     или по всей России.";
-
-pub const CANT_FIND_CITY: &str = "Не удалось найти город! Попробуйте ввести его \
-    имя более точно.\nСовет: посмотрите список городов \
-    https://ru.wikipedia.org/wiki/Список_городов_России.";
-
-pub const REQUEST_SET_DATING_PURPOSE: &str =
-    "Ради чего вы хотите познакомиться? Можно выбрать несколько вариантов.";
-
-pub const PLEASE_CREATE_PROFILE: &str = "Чтобы начать смотреть анкеты, \
-                                         сначала необходимо заполнить свою. \
-                                         Воспользуйтесь командой /create";
-
-pub const PARTNER_NOT_FOUND: &str =
-    "К сожалению, не удалось никого найти.\nСовет: попробуйте ослабить \
-     фильтры или просто немного подождать, так как наш бот не отправляет \
-     анкеты одних и тех же людей чаще одного раза в 4 часа.";
-
-pub const READY_FOR_DATINGS: &str = "Готовы начать путешествие? Жмите кнопку \
-                                     ниже или используйте команду /date!";
-
-pub const START: &str = "Добро пожаловь в бот знакомств для олимпиадников По \
-                         ЛюБВИ! Рекомендуем подписаться на наш канал: \
-                         https://t.me/bvilove, там мы будем публиковать новости \
-                         об обновлениях и проводить опросы о желаемом \
-                         функционале. \nДавайте начнём с создания анкеты.\n\nВ случае \
-                         возникновение любых проблем или вопросов обращайтесь \
-                         к @averyanalex.";
-
-pub const SEND_LIKE: &str = "Введите сообщение, которое вы хотите отправить \
-                             вместе с лайком.Отправить можно только текст.";
-
-pub const REQUEST_EDIT: &str = "Что вы хотите изменить?";
-
-pub const PLEASE_ALLOW_FORWARDING: &str =
-    "Пожалуйста, создайте имя пользователя или разрешите пересылку своих \
-     сообщений в настройках конфиденциальности Telegram. Без этого бот не \
-     сможет отправить ссылку на вас другому человеку!";
-
-pub const PROFILE_ENABLED: &str = "Ваша анкета включена ✅";
-pub const PROFILE_DISABLED: &str = "Ваша анкета выключена ❌";
-pub const SUBSCRIBE_TEXT: &str =
-    "Пожалуйста, подпишитесь на наш канал https://t.me/bvilove";
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt::Display,
+    str::FromStr,
+    sync::{PoisonError, RwLock},
+};
+
+use crate::html;
+
+/// Language of the interface
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Lang {
+    #[default]
+    Ru,
+    En,
+}
+
+impl Lang {
+    pub const ALL: [Self; 2] = [Self::Ru, Self::En];
+
+    /// Language for Telegram's `language_code`, Russian if it's unknown
+    pub fn from_code(code: Option<&str>) -> Self {
+        match code.and_then(|c| c.split(['-', '_']).next()) {
+            Some(code) if !Self::Ru.codes().contains(&code) => Self::En,
+            _ => Self::Ru,
+        }
+    }
+
+    /// Telegram's language codes of the users who get the language, English
+    /// is also used for other codes
+    pub const fn codes(self) -> &'static [&'static str] {
+        match self {
+            Self::Ru => &["ru", "uk", "be", "kk"],
+            Self::En => &["en"],
+        }
+    }
+
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::Ru => "ru",
+            Self::En => "en",
+        }
+    }
+}
+
+impl FromStr for Lang {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "ru" => Ok(Self::Ru),
+            "en" => Ok(Self::En),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Texts changed by admins, see [`set_override`]
+static OVERRIDES: RwLock<BTreeMap<(&str, Lang), String>> =
+    RwLock::new(BTreeMap::new());
+
+/// Text of the message catalog in all supported languages
+#[derive(Clone, Copy, Debug)]
+pub struct Text {
+    pub key: &'static str,
+    ru: &'static str,
+    en: &'static str,
+}
+
+impl Text {
+    /// Text in the language, taking changes made by admins into account
+    pub fn get(&self, lang: Lang) -> Cow<'static, str> {
+        let overrides =
+            OVERRIDES.read().unwrap_or_else(PoisonError::into_inner);
+        overrides.get(&(self.key, lang)).map_or_else(
+            || Cow::Borrowed(self.builtin(lang)),
+            |v| Cow::Owned(v.clone()),
+        )
+    }
+
+    /// Text from the source code
+    pub const fn builtin(&self, lang: Lang) -> &'static str {
+        match lang {
+            Lang::Ru => self.ru,
+            Lang::En => self.en,
+        }
+    }
+
+    /// Replace `{}` placeholders with `args` in order
+    pub fn fill(&self, lang: Lang, args: &[&(dyn Display + Sync)]) -> String {
+        let text = self.get(lang);
+        let mut parts = text.split("{}");
+        let mut result = parts.next().unwrap_or_default().to_owned();
+        for (i, part) in parts.enumerate() {
+            if let Some(arg) = args.get(i) {
+                result.push_str(&arg.to_string());
+            }
+            result.push_str(part);
+        }
+        result
+    }
+
+    /// Text escaped for HTML parse mode
+    pub fn html(&self, lang: Lang) -> String {
+        html::escape(&self.get(lang))
+    }
+
+    /// Same as `fill` for HTML parse mode, `args` must be already escaped
+    pub fn fill_html(&self, lang: Lang, args: &[&str]) -> String {
+        let text = self.get(lang);
+        let mut parts = text.split("{}");
+        let mut result = html::escape(parts.next().unwrap_or_default());
+        for (i, part) in parts.enumerate() {
+            result.push_str(args.get(i).copied().unwrap_or_default());
+            result.push_str(&html::escape(part));
+        }
+        result
+    }
+
+    /// Extract the value of the only placeholder, opposite to `fill`
+    pub fn strip<'a>(&self, lang: Lang, input: &'a str) -> Option<&'a str> {
+        let text = self.get(lang);
+        let (prefix, suffix) = text.split_once("{}")?;
+        input.strip_prefix(prefix)?.strip_suffix(suffix)
+    }
+
+    /// Number of `{}` placeholders in the builtin text
+    pub fn placeholders(&self, lang: Lang) -> usize {
+        self.builtin(lang).matches("{}").count()
+    }
+}
+
+/// Find the option which label is the user's input, used for reply keyboards
+pub fn choose<T>(
+    lang: Lang,
+    input: &str,
+    options: impl IntoIterator<Item = (Text, T)>,
+) -> Option<T> {
+    options.into_iter().find(|(label, _)| label.get(lang) == input).map(|o| o.1)
+}
+
+/// Text of the catalog by its key
+pub fn find(key: &str) -> Option<Text> {
+    ALL.iter().find(|t| t.key == key).copied()
+}
+
+/// Replace the text in the language, `None` restores the builtin one
+pub fn set_override(text: Text, lang: Lang, value: Option<String>) {
+    let mut overrides =
+        OVERRIDES.write().unwrap_or_else(PoisonError::into_inner);
+    match value {
+        Some(value) => overrides.insert((text.key, lang), value),
+        None => overrides.remove(&(text.key, lang)),
+    };
+}
+
+/// Whether the text in the language is changed by admins
+pub fn is_overridden(text: Text, lang: Lang) -> bool {
+    OVERRIDES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .contains_key(&(text.key, lang))
+}
+
+macro_rules! texts {
+    ($($name:ident { ru: $ru:expr, en: $en:expr $(,)? })*) => {
+        $(pub const $name: Text = Text { key: stringify!($name), ru: $ru, en: $en };)*
+
+        /// All texts of the catalog
+        pub const ALL: &[Text] = &[$($name),*];
+    };
+}
+
+texts! {
+    REQUEST_NAME {
+        ru: "Как вас называть?",
+        en: "What should we call you?",
+    }
+
+    REQUEST_GENDER {
+        ru: "Теперь выберите ваш пол",
+        en: "Now choose your gender",
+    }
+    GENDER_MALE {
+        ru: "Я парень",
+        en: "I'm a guy",
+    }
+    GENDER_FEMALE {
+        ru: "Я девушка",
+        en: "I'm a girl",
+    }
+
+    REQUEST_GENDER_FILTER {
+        ru: "Кого вы хотите заботать?",
+        en: "Who do you want to study with?",
+    }
+    GENDER_FILTER_MALE {
+        ru: "Парня",
+        en: "A guy",
+    }
+    GENDER_FILTER_FEMALE {
+        ru: "Девушку",
+        en: "A girl",
+    }
+    GENDER_FILTER_ANY {
+        ru: "Не важно",
+        en: "Doesn't matter",
+    }
+
+    REQUEST_GRADE {
+        ru: "В каком вы сейчас классе?\n Примечание: если вы, например, \
+            окончили 9-ый класс, но ещё не поступили в 10-ый - вы в 9-ом.",
+        en: "What grade are you in?\n Note: if you have, for example, \
+            finished the 9th grade but haven't started the 10th yet, you are \
+            in the 9th.",
+    }
+    GRADE {
+        ru: "{} класс",
+        en: "grade {}",
+    }
+
+    EDIT_SUBJECTS {
+        ru: "Какие предметы вы ботаете? Нажмите на предмет, чтобы добавить \
+            или убрать его.",
+        en: "Which subjects do you study? Tap a subject to add or remove it.",
+    }
+
+    EDIT_PARTNER_SUBJECTS {
+        ru: "Выберите предметы, хотя бы один из которых должен ботать тот, \
+            кого вы ищете. Нажмите на предмет, чтобы добавить или убрать его.",
+        en: "Choose subjects the person you are looking for should study at \
+            least one of. Tap a subject to add or remove it.",
+    }
+
+    EDIT_ABOUT {
+        ru: "Расскажите о себе: чем занимаетесь, кого хотите найти",
+        en: "Tell about yourself: what you do and who you want to find",
+    }
+    SUBJECTS_CONTINUE {
+        ru: "Продолжить",
+        en: "Continue",
+    }
+    SUBJECTS_PARTNER_EMPTY {
+        ru: "Не важно",
+        en: "Doesn't matter",
+    }
+    SUBJECTS_USER_EMPTY {
+        ru: "Никакие",
+        en: "None",
+    }
+    SUBJECTS_USER_SELECTED {
+        ru: "Предметы, которые вы ботаете: {}.",
+        en: "Subjects you study: {}.",
+    }
+    SUBJECTS_USER_NONE {
+        ru: "Вы ничего не ботаете.",
+        en: "You don't study anything.",
+    }
+    SUBJECTS_PARTNER_SELECTED {
+        ru: "Предметы, хотя бы один из которых должен ботать тот, кого вы \
+            ищете: {}.",
+        en: "Subjects the person you are looking for should study at least \
+            one of: {}.",
+    }
+    SUBJECTS_PARTNER_NONE {
+        ru: "Не важно, что ботает другой человек.",
+        en: "It doesn't matter what the other person studies.",
+    }
+    STUDIES {
+        ru: "Ботает: {}",
+        en: "Studies: {}",
+    }
+    STUDIES_NOTHING {
+        ru: "Ничего не ботает",
+        en: "Doesn't study anything",
+    }
+
+    SUBJECT_ART {
+        ru: "Искусство 🎨",
+        en: "Art 🎨",
+    }
+    SUBJECT_ASTRONOMY {
+        ru: "Астрономия 🌌",
+        en: "Astronomy 🌌",
+    }
+    SUBJECT_BIOLOGY {
+        ru: "Биология 🔬",
+        en: "Biology 🔬",
+    }
+    SUBJECT_CHEMISTRY {
+        ru: "Химия 🧪",
+        en: "Chemistry 🧪",
+    }
+    SUBJECT_CHINESE {
+        ru: "Китайский 🇨🇳",
+        en: "Chinese 🇨🇳",
+    }
+    SUBJECT_ECOLOGY {
+        ru: "Экология ♻️",
+        en: "Ecology ♻️",
+    }
+    SUBJECT_ECONOMICS {
+        ru: "Экономика 💶",
+        en: "Economics 💶",
+    }
+    SUBJECT_ENGLISH {
+        ru: "Английский 🇬🇧",
+        en: "English 🇬🇧",
+    }
+    SUBJECT_FRENCH {
+        ru: "Французский 🇫🇷",
+        en: "French 🇫🇷",
+    }
+    SUBJECT_GEOGRAPHY {
+        ru: "География 🌎",
+        en: "Geography 🌎",
+    }
+    SUBJECT_GERMAN {
+        ru: "Немецкий 🇩🇪",
+        en: "German 🇩🇪",
+    }
+    SUBJECT_HISTORY {
+        ru: "История 📰",
+        en: "History 📰",
+    }
+    SUBJECT_INFORMATICS {
+        ru: "Информатика 💻",
+        en: "Informatics 💻",
+    }
+    SUBJECT_ITALIAN {
+        ru: "Итальянский 🇮🇹",
+        en: "Italian 🇮🇹",
+    }
+    SUBJECT_LAW {
+        ru: "Право 👨‍⚖️",
+        en: "Law 👨‍⚖️",
+    }
+    SUBJECT_LITERATURE {
+        ru: "Литература 📖",
+        en: "Literature 📖",
+    }
+    SUBJECT_MATH {
+        ru: "Математика 📐",
+        en: "Math 📐",
+    }
+    SUBJECT_PHYSICS {
+        ru: "Физика ☢️",
+        en: "Physics ☢️",
+    }
+    SUBJECT_RUSSIAN {
+        ru: "Русский 🇷🇺",
+        en: "Russian 🇷🇺",
+    }
+    SUBJECT_SAFETY {
+        ru: "ОБЖ 🪖",
+        en: "Life safety 🪖",
+    }
+    SUBJECT_SOCIAL {
+        ru: "Обществознание 👫",
+        en: "Social studies 👫",
+    }
+    SUBJECT_SPANISH {
+        ru: "Испанский 🇪🇸",
+        en: "Spanish 🇪🇸",
+    }
+    SUBJECT_SPORT {
+        ru: "Физкультура 🏐",
+        en: "Physical education 🏐",
+    }
+    SUBJECT_TECHNOLOGY {
+        ru: "Технология 🚜",
+        en: "Technology 🚜",
+    }
+
+    PURPOSE_FRIENDSHIP {
+        ru: "Дружба 🧑‍🤝‍🧑",
+        en: "Friendship 🧑‍🤝‍🧑",
+    }
+    PURPOSE_STUDIES {
+        ru: "Учёба 📚",
+        en: "Studies 📚",
+    }
+    PURPOSE_RELATIONSHIP {
+        ru: "Отношения 💕",
+        en: "Relationship 💕",
+    }
+    PURPOSE {
+        ru: "Интересует: {}",
+        en: "Looking for: {}",
+    }
+    PURPOSE_SELECTED {
+        ru: "Вас интересует: {}.",
+        en: "You are looking for: {}.",
+    }
+
+    EDIT_LOCATION_FILTER {
+        ru: "Где вы хотите искать людей?\nПо всей стране, в своём федеральном \
+            округе, в своём субъекте федерации или только в своём городе?",
+        en: "Where do you want to look for people?\nAll over the country, in \
+            your federal district, in your region or only in your city?",
+    }
+    LOCATION_COUNTRY {
+        ru: "Вся Россия",
+        en: "All Russia",
+    }
+    COUNTY {
+        ru: "{} ФО",
+        en: "{} Federal District",
+    }
+    CITY_NOT_SET {
+        ru: "Город не указан",
+        en: "City is not specified",
+    }
+
+    NO_CITY {
+        ru: "Так как вы не указали свой город, мы будем искать людей по всей \
+            России",
+        en: "As you haven't specified your city, we will look for people all \
+            over Russia",
+    }
+
+    REQUEST_SET_PHOTOS {
+        ru: "Отправьте парочку своих фото или видео. Можно добавить короткое \
+            голосовое сообщение о себе или кружочек (до минуты).",
+        en: "Send a couple of your photos or videos. You can also add a short \
+            voice message about yourself or a video message (up to a minute).",
+    }
+    NO_PHOTOS {
+        ru: "Без фото",
+        en: "No photos",
+    }
+    SAVE_PHOTOS {
+        ru: "Сохранить",
+        en: "Save",
+    }
+    PHOTOS_ADDED {
+        ru: "Добавлено {}/10 фото/видео. Добавить ещё?",
+        en: "Added {}/10 photos/videos. Add more?",
+    }
+    TOO_MANY_PHOTOS {
+        ru: "Невозможно добавить более 10 фото/видео",
+        en: "You can't add more than 10 photos/videos",
+    }
+
+    PROFILE_CREATION_STARTED {
+        ru: "Начинаем создавать анкету, это не займёт у вас много \
+            времени.\nНе волнуйтесь, если где-то ошибётесь: вы можете \
+            изменить её после регистрации командой /edit.",
+        en: "Let's create your profile, it won't take long.\nDon't worry if \
+            you make a mistake: you can change it after registration with the \
+            /edit command.",
+    }
+
+    REQUEST_CITY {
+        ru: "Напишите название города, в котором вы живёте. Система найдёт \
+            город с наиболее похожим названием и попросит \
+            подтвердить.\n\nСовет: даже если вы живёте в очень маленьком \
+            городе, всё равно лучше указать именно его: на следующем шаге вы \
+            сможете выбрать, что ищете людей в своей области или по всей \
+            России.",
+        en: "Write the name of the city you live in. The bot will find the \
+            city with the most similar name and ask you to confirm \
+            it.\n\nTip: even if you live in a very small town, it's better to \
+            specify it: on the next step you will be able to look for people \
+            in your region or all over Russia.",
+    }
+    CONFIRM_CITY {
+        ru: "Ваш город - {}?",
+        en: "Is your city {}?",
+    }
+    CITY_CORRECT {
+        ru: "Верно",
+        en: "Correct",
+    }
+    SKIP_CITY {
+        ru: "Не указывать",
+        en: "Don't specify",
+    }
+
+    CANT_FIND_CITY {
+        ru: "Не удалось найти город! Попробуйте ввести его имя более \
+            точно.\nСовет: посмотрите список городов \
+            https://ru.wikipedia.org/wiki/Список_городов_России.",
+        en: "Can't find the city! Try to enter its name more precisely.\nTip: \
+            see the list of cities \
+            https://en.wikipedia.org/wiki/List_of_cities_and_towns_in_Russia.",
+    }
+
+    REQUEST_SET_DATING_PURPOSE {
+        ru: "Ради чего вы хотите познакомиться? Можно выбрать несколько \
+            вариантов.",
+        en: "Why do you want to meet people? You can choose several options.",
+    }
+
+    PLEASE_CREATE_PROFILE {
+        ru: "Чтобы начать смотреть анкеты, сначала необходимо заполнить свою. \
+            Воспользуйтесь командой /create",
+        en: "To start browsing profiles, you need to fill in yours first. Use \
+            the /create command",
+    }
+    CREATE_PROFILE {
+        ru: "Заполнить анкету ✍",
+        en: "Fill in the profile ✍",
+    }
+
+    PARTNER_NOT_FOUND {
+        ru: "К сожалению, не удалось никого найти.\nСовет: попробуйте \
+            ослабить фильтры или просто немного подождать, так как наш бот не \
+            отправляет анкеты одних и тех же людей чаще одного раза в 4 часа.",
+        en: "Unfortunately, nobody was found.\nTip: try to loosen the filters \
+            or just wait a bit, as the bot doesn't show the same people more \
+            often than once in 4 hours.",
+    }
+    TRY_AGAIN {
+        ru: "Попробовать ещё раз",
+        en: "Try again",
+    }
+
+    READY_FOR_DATINGS {
+        ru: "Готовы начать путешествие? Жмите кнопку ниже или используйте \
+            команду /date!",
+        en: "Ready to start the journey? Press the button below or use the \
+            /date command!",
+    }
+    FIND_PARTNER {
+        ru: "Смотреть анкеты 🚀",
+        en: "Browse profiles 🚀",
+    }
+    YOUR_PROFILE {
+        ru: "Так выглядит ваша анкета:",
+        en: "This is how your profile looks:",
+    }
+
+    START {
+        ru: "Добро пожаловь в бот знакомств для олимпиадников По ЛюБВИ! \
+            Рекомендуем подписаться на наш канал: https://t.me/bvilove, там \
+            мы будем публиковать новости об обновлениях и проводить опросы о \
+            желаемом функционале. \nДавайте начнём с создания анкеты.\n\nВ \
+            случае возникновение любых проблем или вопросов обращайтесь к \
+            @averyanalex.",
+        en: "Welcome to По ЛюБВИ, the dating bot for olympiad students! We \
+            recommend subscribing to our channel: https://t.me/bvilove, we \
+            publish news about updates and polls about desired features \
+            there. \nLet's start with creating your profile.\n\nIf you have \
+            any problems or questions, contact @averyanalex.",
+    }
+
+    HELP {
+        ru: "Доступные команды:\n/create - заполнить анкету\n/profile - \
+            показать мою анкету\n/edit - изменить анкету\n/date - найти \
+            партнёра\n/leave - выйти из чата\n/reveal - обменяться контактами \
+            с собеседником\n/enable - включить анкету\n/disable - выключить \
+            анкету\n/language - выбрать язык\n/start - приветственное \
+            сообщение\n/help - помощь по командам",
+        en: "Available commands:\n/create - fill in the profile\n/profile - \
+            show my profile\n/edit - edit the profile\n/date - find a \
+            partner\n/leave - leave the chat\n/reveal - exchange contacts \
+            with the partner\n/enable - enable the profile\n/disable - \
+            disable the profile\n/language - choose the language\n/start - \
+            welcome message\n/help - help on commands",
+    }
+
+    // Descriptions of the commands in the menu, see `Command`
+    COMMAND_CREATE {
+        ru: "заполнить анкету",
+        en: "fill in the profile",
+    }
+    COMMAND_PROFILE {
+        ru: "показать мою анкету",
+        en: "show my profile",
+    }
+    COMMAND_EDIT {
+        ru: "изменить анкету",
+        en: "edit the profile",
+    }
+    COMMAND_DATE {
+        ru: "найти партнёра",
+        en: "find a partner",
+    }
+    COMMAND_LEAVE {
+        ru: "выйти из чата",
+        en: "leave the chat",
+    }
+    COMMAND_REVEAL {
+        ru: "обменяться контактами с собеседником",
+        en: "exchange contacts with the partner",
+    }
+    COMMAND_ENABLE {
+        ru: "включить анкету",
+        en: "enable the profile",
+    }
+    COMMAND_DISABLE {
+        ru: "выключить анкету",
+        en: "disable the profile",
+    }
+    COMMAND_LANGUAGE {
+        ru: "выбрать язык",
+        en: "choose the language",
+    }
+    COMMAND_START {
+        ru: "приветственное сообщение",
+        en: "welcome message",
+    }
+    COMMAND_HELP {
+        ru: "помощь по командам",
+        en: "help on commands",
+    }
+
+    LANGUAGE_USAGE {
+        ru: "Выберите язык: /language ru или /language en. Команда /language \
+            auto вернёт язык из настроек Telegram.",
+        en: "Choose the language: /language ru or /language en. The /language \
+            auto command restores the language from Telegram settings.",
+    }
+    LANGUAGE_CHANGED {
+        ru: "Язык изменён",
+        en: "The language is changed",
+    }
+
+    SEND_LIKE {
+        ru: "Отправьте сообщение, которое получит человек вместе с лайком. \
+            Это может быть текст, фото, стикер, голосовое сообщение или \
+            кружочек.",
+        en: "Send a message the person will receive with your like. It can be \
+            a text, a photo, a sticker, a voice message or a video message.",
+    }
+    LIKE_PREVIEW {
+        ru: "Так ваше сообщение увидит получатель. Отправить его или записать \
+            другое?",
+        en: "This is how the recipient will see your message. Send it or \
+            record another one?",
+    }
+    LIKE_MEDIA_TOO_LARGE {
+        ru: "Файл слишком большой, попробуйте что-нибудь покороче",
+        en: "The file is too large, try something shorter",
+    }
+    SEND {
+        ru: "Отправить",
+        en: "Send",
+    }
+    CANCEL {
+        ru: "Отмена",
+        en: "Cancel",
+    }
+    LIKE_CANCELLED {
+        ru: "Отправка лайка отменена",
+        en: "The like is cancelled",
+    }
+    LIKE_SENT {
+        ru: "Лайк отправлен!",
+        en: "Like is sent!",
+    }
+    LIKES_LIMIT {
+        ru: "На сегодня лайки закончились 🙂 Новые можно будет отправить через \
+            {} ч {} мин",
+        en: "You're out of likes for today 🙂 You can send more in {} h {} min",
+    }
+    MESSAGE_LIKES_LIMIT {
+        ru: "На сегодня лайки с сообщением закончились 🙂 Новые можно будет \
+            отправить через {} ч {} мин, а пока можно ставить обычные лайки",
+        en: "You're out of likes with a message for today 🙂 You can send more \
+            in {} h {} min, and regular likes are still available",
+    }
+    TEXT_REJECTED {
+        ru: "Такой текст нельзя отправить: уберите ругательства, ссылки и \
+            контакты",
+        en: "This text can't be sent: please remove swearing, links and \
+            contacts",
+    }
+    PROFILE_ON_REVIEW {
+        ru: "Анкета отправлена на проверку, мы сообщим, когда её начнут \
+            показывать другим",
+        en: "Your profile is sent for review, we'll let you know when others \
+            can see it",
+    }
+    PROFILE_APPROVED {
+        ru: "Анкета прошла проверку, теперь её видят другие 🎉",
+        en: "Your profile is approved, now others can see it 🎉",
+    }
+    PROFILE_REJECTED {
+        ru: "Анкета не прошла проверку: {}. Исправьте её с помощью /edit, и \
+            мы проверим её снова",
+        en: "Your profile wasn't approved: {}. Fix it with /edit and we'll \
+            review it again",
+    }
+    REJECT_REASON_NAME {
+        ru: "неподходящее имя",
+        en: "inappropriate name",
+    }
+    REJECT_REASON_ABOUT {
+        ru: "неподходящее описание",
+        en: "inappropriate description",
+    }
+    REJECT_REASON_PHOTOS {
+        ru: "неподходящие фото",
+        en: "inappropriate photos",
+    }
+    REJECT_REASON_OTHER {
+        ru: "анкета нарушает правила",
+        en: "the profile breaks the rules",
+    }
+    TOO_MANY_REQUESTS {
+        ru: "Вы отправляете сообщения слишком часто, подождите немного",
+        en: "You're sending messages too fast, please wait a bit",
+    }
+
+    LIKE_RECEIVED {
+        ru: "Кому-то понравилась ваша анкета:",
+        en: "Somebody liked your profile:",
+    }
+    LIKE_RECEIVED_MESSAGE {
+        ru: "Кому-то понравилась ваша анкета и он оставил вам сообщение:\n{}",
+        en: "Somebody liked your profile and left you a message:\n{}",
+    }
+    LIKE_RECEIVED_ATTACHMENT {
+        ru: "Кому-то понравилась ваша анкета и он оставил вам сообщение выше",
+        en: "Somebody liked your profile and left you the message above",
+    }
+    MUTUAL_LIKE {
+        ru: "Взаимный лайк!",
+        en: "Mutual like!",
+    }
+
+    REQUEST_EDIT {
+        ru: "Что вы хотите изменить?",
+        en: "What do you want to change?",
+    }
+    EDIT_NAME {
+        ru: "Имя",
+        en: "Name",
+    }
+    EDIT_SUBJECTS_BUTTON {
+        ru: "Предметы",
+        en: "Subjects",
+    }
+    EDIT_ABOUT_BUTTON {
+        ru: "О себе",
+        en: "About",
+    }
+    EDIT_CITY {
+        ru: "Город",
+        en: "City",
+    }
+    EDIT_PHOTOS {
+        ru: "Фото",
+        en: "Photos",
+    }
+    LEAVE_EDITING {
+        ru: "Сначала выйдите из режима редактирования!",
+        en: "Finish editing first!",
+    }
+
+    PROFILE_ENABLED {
+        ru: "Ваша анкета включена ✅",
+        en: "Your profile is enabled ✅",
+    }
+    PROFILE_DISABLED {
+        ru: "Ваша анкета выключена ❌",
+        en: "Your profile is disabled ❌",
+    }
+    SUBSCRIBE_TEXT {
+        ru: "Пожалуйста, подпишитесь на наш канал https://t.me/bvilove",
+        en: "Please subscribe to our channel https://t.me/bvilove",
+    }
+    SUBSCRIBED {
+        ru: "Я подписался на канал",
+        en: "I've subscribed",
+    }
+
+    LIKE_REMINDER {
+        ru: "Вашу анкету кто-то лайкнул, но вы ещё не ответили. Пролистайте \
+            чат выше и нажмите ❤ или 💔, пока лайк не устарел!",
+        en: "Somebody liked your profile, but you haven't answered yet. \
+            Scroll the chat up and press ❤ or 💔 before the like expires!",
+    }
+    LIKE_EXPIRED {
+        ru: "К сожалению, человек, которому вы поставили лайк, так и не \
+            ответил. Не расстраивайтесь и продолжайте смотреть анкеты!",
+        en: "Unfortunately, the person you liked hasn't answered. Don't be \
+            upset and keep browsing profiles!",
+    }
+
+    PROFILE_CARD {
+        ru: "Карточка вашей анкеты, ей можно поделиться с друзьями",
+        en: "The card of your profile, you can share it with friends",
+    }
+
+    MANAGE_PHOTOS {
+        ru: "Кнопки под фото позволяют удалить его, переместить выше или ниже \
+            и сделать обложкой анкеты. Новые фото добавятся в конец.",
+        en: "Buttons under a photo allow to delete it, move it up or down and \
+            make it the cover of the profile. New photos are added to the \
+            end.",
+    }
+    ADD_PHOTOS {
+        ru: "Добавить фото",
+        en: "Add photos",
+    }
+    PHOTOS_DONE {
+        ru: "Готово",
+        en: "Done",
+    }
+    PHOTO_COVER {
+        ru: "⭐ Обложка",
+        en: "⭐ Cover",
+    }
+    PHOTO_DELETED {
+        ru: "Фото удалено",
+        en: "The photo is deleted",
+    }
+    PHOTO_MOVED {
+        ru: "Теперь это фото №{}",
+        en: "Now it's photo #{}",
+    }
+    OPEN_PHOTOS_FROM_EDIT {
+        ru: "Откройте фото через редактирование анкеты!",
+        en: "Open photos from the profile editing!",
+    }
+
+    START_CHAT {
+        ru: "Написать 💬",
+        en: "Write 💬",
+    }
+    CHATTING_WITH {
+        ru: "Вы в чате с {}.",
+        en: "You are chatting with {}.",
+    }
+    CHAT_STARTED {
+        ru: "Все сообщения, которые вы отправите, бот перешлёт собеседнику. \
+            Поддерживаются текст, фото, видео, голосовые, кружочки и \
+            стикеры.\n\n/reveal - обменяться контактами, когда оба \
+            согласны\n/leave - выйти из чата",
+        en: "The bot will forward all messages you send to your partner. \
+            Text, photos, videos, voice and video messages and stickers are \
+            supported.\n\n/reveal - exchange contacts when both agree\n/leave \
+            - leave the chat",
+    }
+    REPLY_TO {
+        ru: "Ответить {}",
+        en: "Reply to {}",
+    }
+    CHAT_LEFT {
+        ru: "Вы вышли из чата",
+        en: "You have left the chat",
+    }
+    CHAT_PARTNER_LEFT {
+        ru: "Собеседник вышел из чата и больше не получит ваших \
+            сообщений.\n\n/leave - выйти из чата",
+        en: "Your partner has left the chat and won't receive your messages \
+            anymore.\n\n/leave - leave the chat",
+    }
+    NOT_IN_CHAT {
+        ru: "Вы сейчас не в чате",
+        en: "You are not in a chat now",
+    }
+    CHAT_UNSUPPORTED {
+        ru: "Такие сообщения нельзя переслать собеседнику",
+        en: "Such messages can't be forwarded to your partner",
+    }
+    CHAT_NOT_DELIVERED {
+        ru: "Не удалось доставить сообщение собеседнику",
+        en: "Failed to deliver the message to your partner",
+    }
+    REVEAL_REQUESTED {
+        ru: "Мы предложили собеседнику обменяться контактами. Как только он \
+            согласится, вы получите ссылки друг на друга.",
+        en: "We have offered your partner to exchange contacts. As soon as \
+            they agree, you will get links to each other.",
+    }
+    REVEAL_ALREADY_REQUESTED {
+        ru: "Вы уже предложили обменяться контактами, ждём ответа \
+            собеседника.",
+        en: "You have already offered to exchange contacts, waiting for your \
+            partner.",
+    }
+    REVEAL_PROPOSED {
+        ru: "Собеседник предлагает обменяться контактами. Зайдите в чат и \
+            отправьте /reveal, если согласны.",
+        en: "Your partner offers to exchange contacts. Enter the chat and \
+            send /reveal if you agree.",
+    }
+    CONTACT_REVEALED {
+        ru: "Вы обменялись контактами!",
+        en: "You have exchanged contacts!",
+    }
+    CONTACT_ALREADY_REVEALED {
+        ru: "Вы уже обменялись контактами.",
+        en: "You have already exchanged contacts.",
+    }
+    OPEN_CHAT {
+        ru: "Открыть чат",
+        en: "Open chat",
+    }
+    CONTACT_HIDDEN {
+        ru: "Вы обменялись контактами, но у собеседника нет имени \
+            пользователя, а пересылка его сообщений запрещена настройками \
+            конфиденциальности. Продолжайте общаться через бота!",
+        en: "You have exchanged contacts, but your partner has no username \
+            and forwarding of their messages is forbidden by privacy \
+            settings. Keep chatting through the bot!",
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fmt::Display, str::FromStr};
+use std::fmt::Display;
 
 use anyhow::bail;
 use bitflags::bitflags;
@@ -6,7 +6,11 @@ use chrono::Datelike;
 use entities::{sea_orm_active_enums::Gender, users};
 use itertools::Itertools;
 
-use crate::cities::City;
+use crate::{
+    cities::City,
+    html,
+    text::{self, Lang, Text},
+};
 
 /// Gender of user
 pub enum UserGender {
@@ -32,18 +36,6 @@ impl From<UserGender> for Gender {
     }
 }
 
-impl FromStr for UserGender {
-    type Err = ();
-
-    fn from_str(value: &str) -> Result<Self, Self::Err> {
-        match value {
-            "Я парень" => Ok(Self::Male),
-            "Я девушка" => Ok(Self::Female),
-            _ => Err(()),
-        }
-    }
-}
-
 impl Display for UserGender {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let emoji = match self {
@@ -82,31 +74,6 @@ impl From<GenderFilter> for Option<Gender> {
     }
 }
 
-impl FromStr for GenderFilter {
-    type Err = ();
-
-    fn from_str(value: &str) -> Result<Self, Self::Err> {
-        match value {
-            "Девушку" => Ok(Self::Female),
-            "Парня" => Ok(Self::Male),
-            "Не важно" => Ok(Self::All),
-            _ => Err(()),
-        }
-    }
-}
-
-impl Display for GenderFilter {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let emoji = match self {
-            Self::Female => "Девушку",
-            Self::Male => "Парня",
-            Self::All => "Не важно",
-        };
-
-        f.write_str(emoji)
-    }
-}
-
 pub struct GraduationYear(i16);
 
 impl From<i16> for GraduationYear {
@@ -135,9 +102,9 @@ impl TryFrom<i8> for Grade {
     }
 }
 
-impl Display for Grade {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("{} класс", self.0))
+impl Grade {
+    pub fn name(&self, lang: Lang) -> String {
+        text::GRADE.fill(lang, &[&self.0])
     }
 }
 
@@ -181,12 +148,13 @@ impl From<GraduationYear> for Grade {
 
 /// Public profile of user
 pub struct PublicProfile {
-    name: String,
-    gender: UserGender,
-    grade: Grade,
-    subjects: UserSubjects,
-    dating_purpose: DatingPurpose,
-    city: City,
+    pub(crate) name: String,
+    pub(crate) gender: UserGender,
+    pub(crate) grade: Grade,
+    pub(crate) subjects: UserSubjects,
+    pub(crate) dating_purpose: DatingPurpose,
+    pub(crate) city: City,
+    /// HTML, see [`crate::html::from_entities`]
     about: String,
 }
 
@@ -206,18 +174,23 @@ impl TryFrom<&users::Model> for PublicProfile {
     }
 }
 
-impl Display for PublicProfile {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!(
-            "{} {}, {}.\n🔎 Интересует: {}.\n📚 {}\n.🧭 {}.\n\n{}",
+impl PublicProfile {
+    /// HTML of the profile in the language of the recipient
+    pub fn describe(&self, lang: Lang) -> String {
+        let details = format!(
+            "{}.\n🔎 {}.\n📚 {}.\n🧭 {}.",
+            self.grade.name(lang),
+            text::PURPOSE.fill(lang, &[&self.dating_purpose.names(lang)]),
+            self.subjects.name(lang),
+            self.city.name(lang),
+        );
+        format!(
+            "{} {}, {}\n\n{}",
             self.gender,
-            self.name,
-            self.grade,
-            self.dating_purpose,
-            self.subjects,
-            self.city,
+            html::bold(&html::escape(&self.name)),
+            html::escape(&details),
             self.about
-        ))
+        )
     }
 }
 
@@ -253,61 +226,54 @@ bitflags! {
 
 impl Subjects {
     /// Name of exactly one subject
-    pub const fn name(&self) -> std::result::Result<&'static str, ()> {
+    pub const fn name(&self) -> std::result::Result<Text, ()> {
         Ok(match *self {
-            Self::Art => "Искусство 🎨",
-            Self::Astronomy => "Астрономия 🌌",
-            Self::Biology => "Биология 🔬",
-            Self::Chemistry => "Химия 🧪",
-            Self::Chinese => "Китайский 🇨🇳",
-            Self::Ecology => "Экология ♻️",
-            Self::Economics => "Экономика 💶",
-            Self::English => "Английский 🇬🇧",
-            Self::French => "Французский 🇫🇷",
-            Self::Geography => "География 🌎",
-            Self::German => "Немецкий 🇩🇪",
-            Self::History => "История 📰",
-            Self::Informatics => "Информатика 💻",
-            Self::Italian => "Итальянский 🇮🇹",
-            Self::Law => "Право 👨‍⚖️",
-            Self::Literature => "Литература 📖",
-            Self::Math => "Математика 📐",
-            Self::Physics => "Физика ☢️",
-            Self::Russian => "Русский 🇷🇺",
-            Self::Safety => "ОБЖ 🪖",
-            Self::Social => "Обществознание 👫",
-            Self::Spanish => "Испанский 🇪🇸",
-            Self::Sport => "Физкультура 🏐",
-            Self::Technology => "Технология 🚜",
+            Self::Art => text::SUBJECT_ART,
+            Self::Astronomy => text::SUBJECT_ASTRONOMY,
+            Self::Biology => text::SUBJECT_BIOLOGY,
+            Self::Chemistry => text::SUBJECT_CHEMISTRY,
+            Self::Chinese => text::SUBJECT_CHINESE,
+            Self::Ecology => text::SUBJECT_ECOLOGY,
+            Self::Economics => text::SUBJECT_ECONOMICS,
+            Self::English => text::SUBJECT_ENGLISH,
+            Self::French => text::SUBJECT_FRENCH,
+            Self::Geography => text::SUBJECT_GEOGRAPHY,
+            Self::German => text::SUBJECT_GERMAN,
+            Self::History => text::SUBJECT_HISTORY,
+            Self::Informatics => text::SUBJECT_INFORMATICS,
+            Self::Italian => text::SUBJECT_ITALIAN,
+            Self::Law => text::SUBJECT_LAW,
+            Self::Literature => text::SUBJECT_LITERATURE,
+            Self::Math => text::SUBJECT_MATH,
+            Self::Physics => text::SUBJECT_PHYSICS,
+            Self::Russian => text::SUBJECT_RUSSIAN,
+            Self::Safety => text::SUBJECT_SAFETY,
+            Self::Social => text::SUBJECT_SOCIAL,
+            Self::Spanish => text::SUBJECT_SPANISH,
+            Self::Sport => text::SUBJECT_SPORT,
+            Self::Technology => text::SUBJECT_TECHNOLOGY,
             _ => return Err(()),
         })
     }
 }
 
-macro_rules! impl_display_bitflags {
+macro_rules! impl_names_bitflags {
     ($type:ident) => {
-        impl Display for $type {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                for (i, name) in Self::all()
+        impl $type {
+            /// Sorted names of all contained flags
+            pub fn names(&self, lang: Lang) -> String {
+                Self::all()
                     .into_iter()
                     .filter(|s| self.contains(*s))
-                    .map(|s| s.name().unwrap())
+                    .map(|s| s.name().unwrap().get(lang))
                     .sorted_unstable_by_key(|n| n.to_lowercase())
-                    .enumerate()
-                {
-                    if i != 0 {
-                        f.write_str(", ")?;
-                    }
-                    f.write_str(name)?;
-                }
-
-                Ok(())
+                    .join(", ")
             }
         }
     };
 }
 
-impl_display_bitflags! {Subjects}
+impl_names_bitflags! {Subjects}
 
 bitflags! {
     #[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
@@ -320,17 +286,17 @@ bitflags! {
 
 impl DatingPurpose {
     /// Name of exactly one purpose
-    pub const fn name(&self) -> std::result::Result<&'static str, ()> {
+    pub const fn name(&self) -> std::result::Result<Text, ()> {
         Ok(match *self {
-            Self::Friendship => "Дружба 🧑‍🤝‍🧑",
-            Self::Studies => "Учёба 📚",
-            Self::Relationship => "Отношения 💕",
+            Self::Friendship => text::PURPOSE_FRIENDSHIP,
+            Self::Studies => text::PURPOSE_STUDIES,
+            Self::Relationship => text::PURPOSE_RELATIONSHIP,
             _ => return Err(()),
         })
     }
 }
 
-impl_display_bitflags! {DatingPurpose}
+impl_names_bitflags! {DatingPurpose}
 
 impl TryFrom<i16> for DatingPurpose {
     type Error = anyhow::Error;
@@ -346,15 +312,13 @@ impl TryFrom<i16> for DatingPurpose {
 
 pub struct UserSubjects(Subjects);
 
-impl Display for UserSubjects {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl UserSubjects {
+    pub fn name(&self, lang: Lang) -> String {
         if self.0.bits() == 0 {
-            f.write_str("Ничего не ботает.")?;
+            text::STUDIES_NOTHING.get(lang).into_owned()
         } else {
-            f.write_fmt(format_args!("Ботает: {}", self.0))?;
+            text::STUDIES.fill(lang, &[&self.0.names(lang)])
         }
-
-        Ok(())
     }
 }
 
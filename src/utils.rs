@@ -9,7 +9,7 @@ use teloxide::{
 
 use crate::{
     callbacks::{Callback, UpdateBitflags},
-    text,
+    text::{self, Lang},
     types::{DatingPurpose, Subjects},
     Bot,
 };
@@ -23,22 +23,18 @@ pub enum SubjectsKeyboardType {
 pub fn make_subjects_keyboard(
     selected: Subjects,
     tp: &SubjectsKeyboardType,
+    lang: Lang,
 ) -> InlineKeyboardMarkup {
     let mut keyboard: Vec<Vec<_>> = Subjects::all()
         .into_iter()
-        .sorted_by(|first, other| {
-            first
-                .name()
-                .unwrap()
-                .to_lowercase()
-                .cmp(&other.name().unwrap().to_lowercase())
-        })
+        .sorted_by_cached_key(|s| s.name().unwrap().get(lang).to_lowercase())
         .map(|subject| {
+            let name = subject.name().unwrap().get(lang);
             InlineKeyboardButton::callback(
                 if selected.contains(subject) {
-                    format!("✅ {}", subject.name().unwrap())
+                    format!("✅ {name}")
                 } else {
-                    subject.name().unwrap().to_owned()
+                    name.into_owned()
                 },
                 match tp {
                     SubjectsKeyboardType::Partner => {
@@ -76,21 +72,23 @@ pub fn make_subjects_keyboard(
             Callback::SetSubjects(UpdateBitflags::Continue).to_string(),
         ),
     };
-    keyboard.push(vec![InlineKeyboardButton::callback(text, cont)]);
+    keyboard.push(vec![InlineKeyboardButton::callback(text.get(lang), cont)]);
     InlineKeyboardMarkup::new(keyboard)
 }
 
 pub fn make_dating_purpose_keyboard(
     selected: DatingPurpose,
+    lang: Lang,
 ) -> InlineKeyboardMarkup {
     let mut keyboard: Vec<Vec<_>> = DatingPurpose::all()
         .into_iter()
         .map(|purpose| {
+            let name = purpose.name().unwrap().get(lang);
             InlineKeyboardButton::callback(
                 if selected.contains(purpose) {
-                    format!("✅ {}", purpose.name().unwrap())
+                    format!("✅ {name}")
                 } else {
-                    purpose.name().unwrap().to_owned()
+                    name.into_owned()
                 },
                 format!("p{}", purpose.bits()),
             )
@@ -102,7 +100,7 @@ pub fn make_dating_purpose_keyboard(
 
     if selected != DatingPurpose::empty() {
         keyboard.push(vec![InlineKeyboardButton::callback(
-            "Продолжить",
+            text::SUBJECTS_CONTINUE.get(lang),
             "pcontinue",
         )]);
     }
@@ -203,8 +201,7 @@ pub async fn check_user_subscribed_channel(
     bot: &Bot,
     user: i64,
 ) -> anyhow::Result<bool> {
-    Ok(if let Ok(channel_id) = std::env::var("CHANNEL_ID") {
-        let channel_id: i64 = channel_id.parse()?;
+    Ok(if let Some(channel_id) = bot.config().channel_id {
         let member = bot
             .get_chat_member(ChatId(channel_id), UserId(user as u64))
             .await?;
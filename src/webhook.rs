@@ -0,0 +1,97 @@
+use std::{convert::Infallible, net::SocketAddr};
+
+use anyhow::{ensure, Context};
+use serde::Deserialize;
+use teloxide::{
+    prelude::*,
+    update_listeners::{webhooks, UpdateListener},
+};
+use url::Url;
+
+use crate::Bot;
+
+/// Receiving updates with a webhook instead of long polling
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookSettings {
+    /// Public URL which Telegram sends updates to
+    url: Url,
+    /// Address of the listener, it serves plain HTTP, so TLS must be
+    /// terminated by a reverse proxy in front of it
+    #[serde(default = "default_address")]
+    address: SocketAddr,
+    /// Path of the listener, if the proxy rewrites the path of `url`
+    #[serde(default)]
+    path: Option<String>,
+    /// Value of `X-Telegram-Bot-Api-Secret-Token` header, random if the
+    /// webhook is registered, required otherwise
+    #[serde(default)]
+    secret: Option<String>,
+    /// Whether to call `setWebhook`, disabled for local testing
+    #[serde(default = "default_register")]
+    register: bool,
+}
+
+fn default_address() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8080))
+}
+
+const fn default_register() -> bool {
+    true
+}
+
+impl WebhookSettings {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            !self.register || self.url.scheme() == "https",
+            "webhook.url must use HTTPS"
+        );
+        // Without a secret any request to the URL would be taken as an update
+        ensure!(
+            self.register || self.secret.is_some(),
+            "webhook.secret must be set when register is false"
+        );
+        ensure!(
+            !self.secret.as_ref().is_some_and(String::is_empty),
+            "webhook.secret is empty"
+        );
+        Ok(())
+    }
+}
+
+/// Start the HTTP server and register it as the bot's webhook
+pub async fn listener(
+    bot: &Bot,
+    settings: &WebhookSettings,
+) -> anyhow::Result<impl UpdateListener<Err = Infallible>> {
+    let mut local_url = settings.url.clone();
+    if let Some(path) = &settings.path {
+        local_url.set_path(path);
+    }
+    let mut options = webhooks::Options::new(settings.address, local_url);
+    if let Some(secret) = &settings.secret {
+        options = options.secret_token(secret.clone());
+    }
+
+    if settings.register {
+        let secret = options.get_or_gen_secret_token().to_owned();
+        bot.set_webhook(settings.url.clone())
+            .secret_token(secret)
+            .await
+            .context("can't set webhook")?;
+        tracing::info!(url = %settings.url, "webhook is set");
+    }
+
+    let (listener, stop_flag, router) = webhooks::axum_no_setup(options);
+    let server = axum::Server::try_bind(&settings.address)?
+        .serve(router.into_make_service())
+        .with_graceful_shutdown(stop_flag);
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            tracing::error!("webhook server error: {e}");
+        }
+    });
+    tracing::info!(address = %settings.address, "listening for updates");
+
+    Ok(listener)
+}